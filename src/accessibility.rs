@@ -0,0 +1,141 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later OR MPL-2.0
+// This file is a part of `windowless`.
+//
+// `windowless` is free software: you can redistribute it and/or modify it under the terms of
+// either:
+//
+// * GNU Lesser General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+// * Mozilla Public License as published by the Mozilla Foundation, version 2.
+//
+// `windowless` is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+// See the GNU Lesser General Public License or the Mozilla Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License and the Mozilla
+// Public License along with `windowless`. If not, see <https://www.gnu.org/licenses/> or
+// <https://www.mozilla.org/en-US/MPL/2.0/>.
+
+//! Exporting the window tree for accessibility tools.
+
+use crate::{Coordinate, Order, Rectangle, WindowKey, WindowTable};
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// A platform-neutral snapshot of the window tree, for accessibility tools.
+///
+/// Returned by [`WindowTable::accessibility_tree`]. The tree follows primary parents, so it
+/// has the same shape as [`WindowTable::traverse`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct AccessTree<T = i32> {
+    /// The root of the tree.
+    root: Option<WindowKey>,
+
+    /// The nodes, depth-first from the root.
+    nodes: Vec<AccessNode<T>>,
+}
+
+/// A window in an [`AccessTree`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct AccessNode<T = i32> {
+    /// The window this node describes.
+    pub key: WindowKey,
+
+    /// The primary parent of the window, or `None` for the root.
+    pub parent: Option<WindowKey>,
+
+    /// The primary children of the window, from the bottom of the stack up.
+    pub children: Vec<WindowKey>,
+
+    /// The rectangle of the window.
+    pub rect: Rectangle<T>,
+
+    /// The name of the window.
+    pub name: Option<String>,
+
+    /// Whether the window is viewable.
+    pub viewable: bool,
+
+    /// Whether the window can be given the focus by traversal.
+    pub focusable: bool,
+}
+
+impl<T> AccessTree<T> {
+    /// Returns the root of the tree.
+    pub fn root(&self) -> Option<WindowKey> {
+        self.root
+    }
+
+    /// Returns the nodes of the tree, depth-first from the root.
+    ///
+    /// Every node comes before its children.
+    pub fn nodes(&self) -> &[AccessNode<T>] {
+        &self.nodes
+    }
+
+    /// Returns the node for a window.
+    pub fn node(&self, key: WindowKey) -> Option<&AccessNode<T>> {
+        self.nodes.iter().find(|node| node.key == key)
+    }
+}
+
+impl<T: Coordinate, const N: usize, const R: usize> WindowTable<T, N, R> {
+    /// Export the window tree for accessibility tools.
+    ///
+    /// The tree is a snapshot; it does not change along with the table.
+    pub fn accessibility_tree(&self) -> AccessTree<T> {
+        let nodes = match self.root {
+            Some(root) => self
+                .traverse(root, Order::DepthFirst)
+                .map(|key| AccessNode {
+                    key,
+                    parent: self.parent(key),
+                    children: self.children(key).collect(),
+                    rect: self.rect(key),
+                    name: self.name(key).map(String::from),
+                    viewable: self.is_viewable(key),
+                    focusable: self.focusable(key),
+                })
+                .collect(),
+            None => Vec::new(),
+        };
+
+        AccessTree {
+            root: self.root,
+            nodes,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Rectangle, WindowTable};
+
+    #[test]
+    fn accessibility_tree() {
+        let mut window_table = WindowTable::new();
+        assert!(window_table.accessibility_tree().nodes().is_empty());
+
+        let root = window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let panel = window_table.insert(Rectangle::new(10, 10, 60, 60)).unwrap();
+        let button = window_table.insert(Rectangle::new(20, 20, 30, 30)).unwrap();
+        window_table.set_name(button, "OK");
+        window_table.unmap(panel);
+
+        let tree = window_table.accessibility_tree();
+        assert_eq!(tree.root(), Some(root));
+        assert!(tree
+            .nodes()
+            .iter()
+            .map(|node| node.key)
+            .eq([root, panel, button]));
+
+        let node = tree.node(button).unwrap();
+        assert_eq!(node.parent, Some(panel));
+        assert!(node.children.is_empty());
+        assert_eq!(node.rect, Rectangle::new(20, 20, 30, 30));
+        assert_eq!(node.name.as_deref(), Some("OK"));
+        assert!(!node.viewable);
+        assert_eq!(tree.node(root).unwrap().children, [panel]);
+    }
+}
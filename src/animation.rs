@@ -0,0 +1,195 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later OR MPL-2.0
+// This file is a part of `windowless`.
+//
+// `windowless` is free software: you can redistribute it and/or modify it under the terms of
+// either:
+//
+// * GNU Lesser General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+// * Mozilla Public License as published by the Mozilla Foundation, version 2.
+//
+// `windowless` is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+// See the GNU Lesser General Public License or the Mozilla Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License and the Mozilla
+// Public License along with `windowless`. If not, see <https://www.gnu.org/licenses/> or
+// <https://www.mozilla.org/en-US/MPL/2.0/>.
+
+//! Animating windows between rectangles.
+
+use crate::{ConfigureError, Coordinate, Rectangle, Region, WindowKey, WindowTable};
+
+/// A window moving from one rectangle to another.
+#[derive(Debug, Clone)]
+pub(crate) struct Animation<T> {
+    /// The window being animated.
+    key: WindowKey,
+
+    /// The rectangle the window started at.
+    from: Rectangle<T>,
+
+    /// The rectangle the window ends at.
+    to: Rectangle<T>,
+
+    /// The number of steps taken so far.
+    step: u32,
+
+    /// The total number of steps.
+    steps: u32,
+}
+
+impl<T: Coordinate> Animation<T> {
+    /// Get the rectangle of the window at the current step.
+    fn rect(&self) -> Rectangle<T> {
+        let t = f64::from(self.step) / f64::from(self.steps);
+        let lerp = |from: T, to: T| {
+            let from = from.to_f64();
+            T::from_f64(from + (to.to_f64() - from) * t)
+        };
+
+        Rectangle::new(
+            lerp(self.from.left, self.to.left),
+            lerp(self.from.top, self.to.top),
+            lerp(self.from.right, self.to.right),
+            lerp(self.from.bottom, self.to.bottom),
+        )
+    }
+}
+
+impl<T: Coordinate, const N: usize, const R: usize> WindowTable<T, N, R> {
+    /// Start moving a window to a new rectangle over a number of steps.
+    ///
+    /// Each call to [`tick`](Self::tick) moves the window one step closer, so that it reaches
+    /// the rectangle after `steps` ticks. Any animation already running on the window is
+    /// replaced, starting from wherever the window is now. With zero steps, the window is moved
+    /// straight away.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the window can't be given the rectangle, as with
+    /// [`resize_window`](Self::resize_window).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn animate_to(
+        &mut self,
+        key: WindowKey,
+        rect: Rectangle<T>,
+        steps: u32,
+    ) -> Result<(), ConfigureError> {
+        let to = self.check_configure(key, rect)?;
+        self.cancel_animation(key);
+
+        if steps == 0 {
+            self.resize_window(key, to)?;
+            return Ok(());
+        }
+
+        self.animations.push(Animation {
+            key,
+            from: self.rect(key),
+            to,
+            step: 0,
+            steps,
+        });
+        Ok(())
+    }
+
+    /// Stop animating a window, leaving it where it is.
+    ///
+    /// Returns `false` if the window was not being animated.
+    pub fn cancel_animation(&mut self, key: WindowKey) -> bool {
+        let len = self.animations.len();
+        self.animations.retain(|animation| animation.key != key);
+        self.animations.len() != len
+    }
+
+    /// Tell if a window is being animated.
+    pub fn is_animating(&self, key: WindowKey) -> bool {
+        self.animations.iter().any(|animation| animation.key == key)
+    }
+
+    /// Tell if any windows are being animated.
+    pub fn has_animations(&self) -> bool {
+        !self.animations.is_empty()
+    }
+
+    /// Move every animated window one step closer to its rectangle.
+    ///
+    /// All of the windows are moved in a single [transaction](Self::transaction). Animations that
+    /// have finished, or whose windows were removed, are dropped. Returns the region of the
+    /// screen damaged by this frame.
+    pub fn tick(&mut self) -> Region<T, R> {
+        let mut animations = core::mem::take(&mut self.animations);
+        animations.retain(|animation| self.windows.contains_key(animation.key.0));
+
+        let mut transaction = self.transaction();
+        for animation in &mut animations {
+            animation.step += 1;
+
+            // Frames the window can't be given are skipped.
+            transaction
+                .resize_window(animation.key, animation.rect())
+                .ok();
+        }
+        let damage = transaction.commit();
+
+        animations.retain(|animation| animation.step < animation.steps);
+        self.animations = animations;
+        damage
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Rectangle, WindowTable};
+
+    #[test]
+    fn animate() {
+        let mut window_table = WindowTable::new();
+
+        window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let a = window_table.insert(Rectangle::new(0, 0, 10, 10)).unwrap();
+        window_table
+            .animate_to(a, Rectangle::new(30, 0, 40, 10), 3)
+            .unwrap();
+        assert!(window_table.is_animating(a));
+        window_table.take_damage();
+
+        let damage = window_table.tick();
+        assert_eq!(window_table.rect(a), Rectangle::new(10, 0, 20, 10));
+        assert_eq!(damage.area(), 200);
+        assert_eq!(window_table.take_damage().area(), 200);
+
+        window_table.tick();
+        window_table.tick();
+        assert_eq!(window_table.rect(a), Rectangle::new(30, 0, 40, 10));
+        assert!(!window_table.has_animations());
+        assert!(window_table.tick().is_empty());
+
+        // Starting over replaces the animation, and removed windows are dropped.
+        window_table
+            .animate_to(a, Rectangle::new(0, 0, 10, 10), 10)
+            .unwrap();
+        window_table
+            .animate_to(a, Rectangle::new(30, 50, 40, 60), 5)
+            .unwrap();
+        window_table.tick();
+        assert_eq!(window_table.rect(a), Rectangle::new(30, 10, 40, 20));
+        assert!(window_table.cancel_animation(a));
+        assert!(!window_table.cancel_animation(a));
+
+        window_table
+            .animate_to(a, Rectangle::new(0, 0, 10, 10), 0)
+            .unwrap();
+        assert_eq!(window_table.rect(a), Rectangle::new(0, 0, 10, 10));
+        window_table
+            .animate_to(a, Rectangle::new(30, 50, 40, 60), 5)
+            .unwrap();
+        window_table.remove(a);
+        window_table.tick();
+        assert!(!window_table.has_animations());
+    }
+}
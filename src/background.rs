@@ -0,0 +1,146 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later OR MPL-2.0
+// This file is a part of `windowless`.
+//
+// `windowless` is free software: you can redistribute it and/or modify it under the terms of
+// either:
+//
+// * GNU Lesser General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+// * Mozilla Public License as published by the Mozilla Foundation, version 2.
+//
+// `windowless` is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+// See the GNU Lesser General Public License or the Mozilla Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License and the Mozilla
+// Public License along with `windowless`. If not, see <https://www.gnu.org/licenses/> or
+// <https://www.mozilla.org/en-US/MPL/2.0/>.
+
+//! The background window, which is kept beneath every other window.
+
+use crate::{Anchors, ConfigureError, Coordinate, Layer, WindowKey, WindowTable};
+
+impl<T: Coordinate, const N: usize, const R: usize> WindowTable<T, N, R> {
+    /// Make a window the background window, or pass `None` to have no background window.
+    ///
+    /// The background window is given the rectangle of the root window, and is anchored to
+    /// its edges so that it follows the root when it is resized. It is moved into the
+    /// [`Layer::Background`] layer, stacked beneath every other window above the root, and
+    /// can't be raised. Since it covers the root, it is exposed wherever windows are removed or
+    /// moved away, instead of the root. The previous background window becomes an ordinary
+    /// window.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the window is the root window, in which case nothing changes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn set_background(&mut self, key: Option<WindowKey>) -> Result<(), ConfigureError> {
+        let key = match key {
+            Some(key) => key,
+            None => {
+                if let Some(old) = self.background.take() {
+                    self.set_anchors(old, None);
+                }
+                return Ok(());
+            }
+        };
+
+        assert!(self.contains_key(key), "window does not exist");
+        if self.root == Some(key) {
+            return Err(ConfigureError::RootWindow);
+        }
+
+        if let Some(old) = self.background.take() {
+            self.set_anchors(old, None);
+        }
+
+        // Lower the window before growing it, so that the windows it comes to cover stay above
+        // it.
+        let root = self.rect(self.root.unwrap());
+        let rect = self.rect(key);
+        let mut transaction = self.transaction();
+        transaction.set_layer(key, Layer::Background);
+        transaction.lower(key);
+        if rect != root {
+            transaction.resize_window(key, root)?;
+        }
+        transaction.commit();
+
+        // Lower it beneath the other windows in the background layer too. The whole root was
+        // damaged by growing the window, so this needs no more damage.
+        self.background = Some(key);
+        let order = self.stacking_order();
+        self.relink(&order);
+        self.set_anchors(
+            key,
+            Some(Anchors {
+                left: Some(T::ZERO),
+                top: Some(T::ZERO),
+                right: Some(T::ZERO),
+                bottom: Some(T::ZERO),
+                ..Anchors::default()
+            }),
+        );
+
+        Ok(())
+    }
+
+    /// Returns the background window, if there is one.
+    pub fn background(&self) -> Option<WindowKey> {
+        self.background
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Layer, Rectangle, WindowTable};
+    use alloc::vec::Vec;
+
+    #[test]
+    fn background() {
+        let mut window_table = WindowTable::new();
+
+        let root = window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let a = window_table.insert(Rectangle::new(10, 10, 50, 50)).unwrap();
+        let background = window_table.insert(Rectangle::new(60, 60, 80, 80)).unwrap();
+        let b = window_table.insert(Rectangle::new(40, 40, 70, 70)).unwrap();
+        window_table.set_layer(a, Layer::Background);
+
+        assert!(window_table.set_background(Some(root)).is_err());
+        window_table.set_background(Some(background)).unwrap();
+        assert_eq!(window_table.background(), Some(background));
+        assert_eq!(
+            window_table.rect(background),
+            Rectangle::new(0, 0, 100, 100)
+        );
+
+        let order =
+            |table: &WindowTable| table.paint_order().map(|(key, _)| key).collect::<Vec<_>>();
+        assert_eq!(order(&window_table), [root, background, a, b]);
+
+        window_table.raise(background);
+        assert_eq!(order(&window_table), [root, background, a, b]);
+        window_table.validate().unwrap();
+
+        // Removing a window exposes the background rather than the root.
+        window_table.take_exposures();
+        window_table.remove(b);
+        let exposures = window_table.take_exposures();
+        assert!(exposures.iter().all(|&(key, _)| key != root));
+        assert!(exposures.iter().any(|&(key, _)| key == background));
+
+        window_table
+            .resize_root(Rectangle::new(0, 0, 200, 150))
+            .unwrap();
+        assert_eq!(
+            window_table.rect(background),
+            Rectangle::new(0, 0, 200, 150)
+        );
+
+        window_table.remove(background);
+        assert_eq!(window_table.background(), None);
+    }
+}
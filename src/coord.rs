@@ -0,0 +1,357 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later OR MPL-2.0
+// This file is a part of `windowless`.
+//
+// `windowless` is free software: you can redistribute it and/or modify it under the terms of
+// either:
+//
+// * GNU Lesser General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+// * Mozilla Public License as published by the Mozilla Foundation, version 2.
+//
+// `windowless` is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+// See the GNU Lesser General Public License or the Mozilla Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License and the Mozilla
+// Public License along with `windowless`. If not, see <https://www.gnu.org/licenses/> or
+// <https://www.mozilla.org/en-US/MPL/2.0/>.
+
+//! The scalar types used for coordinates.
+
+use core::fmt::Debug;
+use core::ops::{Add, Div, Mul, Neg, Sub};
+
+/// A scalar type that can be used for the coordinates of a [`Rectangle`](crate::Rectangle).
+///
+/// This is implemented for `i32`, `i64`, `f32` and `f64`. Integer coordinates are used by
+/// default. Floating-point coordinates are useful when composing with renderers that lay out
+/// windows at fractional positions. With the `fixed` feature, it is also implemented for
+/// `Fixed`, which lays out windows at fractional positions without floating-point math.
+pub trait Coordinate:
+    Copy
+    + Default
+    + PartialOrd
+    + Debug
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Neg<Output = Self>
+{
+    /// A wider type that products of two coordinates fit into.
+    type Wide: Copy
+        + Default
+        + PartialOrd
+        + Debug
+        + Add<Output = Self::Wide>
+        + Sub<Output = Self::Wide>
+        + Mul<Output = Self::Wide>
+        + Div<Output = Self::Wide>;
+
+    /// The zero coordinate.
+    const ZERO: Self;
+
+    /// Convert an integer into a coordinate.
+    fn from_i32(value: i32) -> Self;
+
+    /// Convert this coordinate into the wider type.
+    fn widen(self) -> Self::Wide;
+
+    /// Convert a value of the wider type back into a coordinate, saturating if it doesn't fit.
+    fn narrow(wide: Self::Wide) -> Self;
+
+    /// Add two coordinates, returning `None` on overflow.
+    fn checked_add(self, other: Self) -> Option<Self>;
+
+    /// Subtract two coordinates, returning `None` on overflow.
+    fn checked_sub(self, other: Self) -> Option<Self>;
+
+    /// Multiply two coordinates, returning `None` on overflow.
+    fn checked_mul(self, other: Self) -> Option<Self>;
+
+    /// Add two coordinates, saturating at the bounds of the type.
+    fn saturating_add(self, other: Self) -> Self;
+
+    /// Subtract two coordinates, saturating at the bounds of the type.
+    fn saturating_sub(self, other: Self) -> Self;
+
+    /// Multiply two coordinates, saturating at the bounds of the type.
+    fn saturating_mul(self, other: Self) -> Self;
+
+    /// Convert a floating-point number into a coordinate.
+    ///
+    /// Integer coordinates round to the nearest integer, saturating if it doesn't fit.
+    fn from_f64(value: f64) -> Self;
+
+    /// Convert this coordinate into a floating-point number.
+    fn to_f64(self) -> f64;
+
+    /// Round this coordinate down to an integer, saturating if it doesn't fit.
+    fn floor_i32(self) -> i32;
+
+    /// Round this coordinate up to an integer, saturating if it doesn't fit.
+    fn ceil_i32(self) -> i32;
+
+    /// Round this coordinate down to a 64-bit integer, saturating if it doesn't fit.
+    ///
+    /// By default, this rounds to a 32-bit integer.
+    fn floor_i64(self) -> i64 {
+        self.floor_i32().into()
+    }
+
+    /// Round this coordinate up to a 64-bit integer, saturating if it doesn't fit.
+    ///
+    /// By default, this rounds to a 32-bit integer.
+    fn ceil_i64(self) -> i64 {
+        self.ceil_i32().into()
+    }
+}
+
+macro_rules! impl_int {
+    ($($ty:ty => $wide:ty),*) => {$(
+        impl Coordinate for $ty {
+            type Wide = $wide;
+
+            const ZERO: Self = 0;
+
+            fn from_i32(value: i32) -> Self {
+                value.into()
+            }
+
+            fn widen(self) -> Self::Wide {
+                self.into()
+            }
+
+            fn narrow(wide: Self::Wide) -> Self {
+                wide.clamp(<$ty>::MIN.into(), <$ty>::MAX.into()) as $ty
+            }
+
+            fn checked_add(self, other: Self) -> Option<Self> {
+                <$ty>::checked_add(self, other)
+            }
+
+            fn checked_sub(self, other: Self) -> Option<Self> {
+                <$ty>::checked_sub(self, other)
+            }
+
+            fn checked_mul(self, other: Self) -> Option<Self> {
+                <$ty>::checked_mul(self, other)
+            }
+
+            fn saturating_add(self, other: Self) -> Self {
+                <$ty>::saturating_add(self, other)
+            }
+
+            fn saturating_sub(self, other: Self) -> Self {
+                <$ty>::saturating_sub(self, other)
+            }
+
+            fn saturating_mul(self, other: Self) -> Self {
+                <$ty>::saturating_mul(self, other)
+            }
+
+            fn from_f64(value: f64) -> Self {
+                // Casts saturate, but round towards zero.
+                if value >= 0.0 {
+                    (value + 0.5) as $ty
+                } else {
+                    (value - 0.5) as $ty
+                }
+            }
+
+            fn to_f64(self) -> f64 {
+                self as f64
+            }
+
+            fn floor_i32(self) -> i32 {
+                self.clamp(i32::MIN.into(), i32::MAX.into()) as i32
+            }
+
+            fn ceil_i32(self) -> i32 {
+                self.floor_i32()
+            }
+
+            fn floor_i64(self) -> i64 {
+                self.into()
+            }
+
+            fn ceil_i64(self) -> i64 {
+                self.floor_i64()
+            }
+        }
+    )*};
+}
+
+macro_rules! impl_float {
+    ($($ty:ty => $wide:ty),*) => {$(
+        impl Coordinate for $ty {
+            type Wide = $wide;
+
+            const ZERO: Self = 0.0;
+
+            fn from_i32(value: i32) -> Self {
+                value as $ty
+            }
+
+            fn widen(self) -> Self::Wide {
+                self.into()
+            }
+
+            fn narrow(wide: Self::Wide) -> Self {
+                wide as $ty
+            }
+
+            // Floating-point math overflows to infinity.
+            fn checked_add(self, other: Self) -> Option<Self> {
+                Some(self + other).filter(|value| value.is_finite())
+            }
+
+            fn checked_sub(self, other: Self) -> Option<Self> {
+                Some(self - other).filter(|value| value.is_finite())
+            }
+
+            fn checked_mul(self, other: Self) -> Option<Self> {
+                Some(self * other).filter(|value| value.is_finite())
+            }
+
+            fn saturating_add(self, other: Self) -> Self {
+                self + other
+            }
+
+            fn saturating_sub(self, other: Self) -> Self {
+                self - other
+            }
+
+            fn saturating_mul(self, other: Self) -> Self {
+                self * other
+            }
+
+            fn from_f64(value: f64) -> Self {
+                value as $ty
+            }
+
+            fn to_f64(self) -> f64 {
+                self.into()
+            }
+
+            fn floor_i32(self) -> i32 {
+                // Casts saturate, but round towards zero.
+                let truncated = self as i32;
+                if (truncated as $ty) > self {
+                    truncated.saturating_sub(1)
+                } else {
+                    truncated
+                }
+            }
+
+            fn ceil_i32(self) -> i32 {
+                let truncated = self as i32;
+                if (truncated as $ty) < self {
+                    truncated.saturating_add(1)
+                } else {
+                    truncated
+                }
+            }
+
+            fn floor_i64(self) -> i64 {
+                let truncated = self as i64;
+                if (truncated as $ty) > self {
+                    truncated.saturating_sub(1)
+                } else {
+                    truncated
+                }
+            }
+
+            fn ceil_i64(self) -> i64 {
+                let truncated = self as i64;
+                if (truncated as $ty) < self {
+                    truncated.saturating_add(1)
+                } else {
+                    truncated
+                }
+            }
+        }
+    )*};
+}
+
+impl_int!(i32 => i64, i64 => i128);
+impl_float!(f32 => f64, f64 => f64);
+
+/// Get the smaller of two coordinates.
+pub(crate) fn min<T: PartialOrd>(a: T, b: T) -> T {
+    if b < a {
+        b
+    } else {
+        a
+    }
+}
+
+/// Get the larger of two coordinates.
+pub(crate) fn max<T: PartialOrd>(a: T, b: T) -> T {
+    if b > a {
+        b
+    } else {
+        a
+    }
+}
+
+/// Get the distance between two coordinates, saturating at the bounds of the type.
+pub(crate) fn distance<T: Coordinate>(a: T, b: T) -> T {
+    if a < b {
+        b.saturating_sub(a)
+    } else {
+        a.saturating_sub(b)
+    }
+}
+
+/// Get the distance between two coordinates, returning `None` on overflow.
+pub(crate) fn checked_distance<T: Coordinate>(a: T, b: T) -> Option<T> {
+    if a < b {
+        b.checked_sub(a)
+    } else {
+        a.checked_sub(b)
+    }
+}
+
+/// Get the point halfway between two coordinates, without overflowing.
+pub(crate) fn midpoint<T: Coordinate>(a: T, b: T) -> T {
+    T::narrow((a.widen() + b.widen()) / T::from_i32(2).widen())
+}
+
+/// Shrink an offset so that moving `low` and `high` by it overflows neither of them.
+pub(crate) fn clamp_offset<T: Coordinate>(low: T, high: T, offset: T) -> T {
+    let low = low.saturating_add(offset).saturating_sub(low);
+    let high = high.saturating_add(offset).saturating_sub(high);
+
+    if offset < T::ZERO {
+        max(low, high)
+    } else {
+        min(low, high)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Coordinate;
+
+    #[test]
+    fn rounding() {
+        assert_eq!(1.5f64.floor_i32(), 1);
+        assert_eq!(1.5f64.ceil_i32(), 2);
+        assert_eq!((-1.5f32).floor_i32(), -2);
+        assert_eq!((-1.5f32).ceil_i32(), -1);
+        assert_eq!(2.0f64.ceil_i32(), 2);
+        assert_eq!(i64::MAX.floor_i32(), i32::MAX);
+        assert_eq!(i64::MAX.floor_i64(), i64::MAX);
+        assert_eq!((-1.5e12f64).floor_i64(), -1_500_000_000_000);
+        assert_eq!(1.5f64.ceil_i64(), 2);
+        assert_eq!(f64::INFINITY.ceil_i64(), i64::MAX);
+        assert_eq!(7i32.ceil_i64(), 7);
+        assert_eq!(i32::narrow(i64::MIN), i32::MIN);
+        assert_eq!(i32::from_f64(1.5), 2);
+        assert_eq!(i32::from_f64(-1.5), -2);
+        assert_eq!(i32::from_f64(1.4), 1);
+        assert_eq!(i32::from_f64(1e20), i32::MAX);
+        assert_eq!(i64::from_f64(f64::NAN), 0);
+    }
+}
@@ -0,0 +1,1084 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later OR MPL-2.0
+// This file is a part of `windowless`.
+//
+// `windowless` is free software: you can redistribute it and/or modify it under the terms of
+// either:
+//
+// * GNU Lesser General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+// * Mozilla Public License as published by the Mozilla Foundation, version 2.
+//
+// `windowless` is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+// See the GNU Lesser General Public License or the Mozilla Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License and the Mozilla
+// Public License along with `windowless`. If not, see <https://www.gnu.org/licenses/> or
+// <https://www.mozilla.org/en-US/MPL/2.0/>.
+
+//! Pointer tracking.
+
+use crate::{coord, Coordinate, ModalBlocked, Point, WindowKey, WindowTable};
+use alloc::collections::BTreeMap;
+use smallvec::SmallVec;
+
+/// The current cursor state.
+#[derive(Debug, Default)]
+pub struct CursorState<T = i32> {
+    /// Last known cursor position.
+    position: Point<T>,
+
+    /// Windows currently under the cursor.
+    windows: SmallVec<[WindowKey; 3]>,
+
+    /// The window that has grabbed the pointer.
+    grab: Option<WindowKey>,
+
+    /// Whether the grab was made by pressing a button, and ends when the buttons are released.
+    implicit: bool,
+
+    /// The buttons that are held down.
+    buttons: SmallVec<[Button; 2]>,
+
+    /// The window that the pointer is confined to.
+    confine: Option<WindowKey>,
+}
+
+/// The identifier of a pointer, such as a mouse or a finger on a touchscreen.
+///
+/// The meaning of the identifier is up to the caller; it is usually the device or touch ID
+/// reported by the windowing system.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct PointerId(pub u64);
+
+/// The state of several pointers at once.
+///
+/// Every pointer is tracked by its own [`CursorState`], with its own position, windows and
+/// grab. A pointer starts being tracked the first time it is moved.
+#[derive(Debug)]
+pub struct PointerState<T = i32> {
+    /// The state of each pointer.
+    pointers: BTreeMap<PointerId, CursorState<T>>,
+}
+
+/// An event generated by the cursor moving between windows.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum CursorEvent {
+    /// The cursor has left this window.
+    Leave(WindowKey, CrossingDetail),
+
+    /// The cursor has entered this window.
+    Enter(WindowKey, CrossingDetail),
+}
+
+/// How the window in a [`CursorEvent`] is related to the other end of the crossing.
+///
+/// These follow the `detail` of X11's `EnterNotify` and `LeaveNotify` events. When the cursor
+/// moves from a window to one of its descendants, the window it came from is left with
+/// [`Inferior`](Self::Inferior) even though the cursor is still over it.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum CrossingDetail {
+    /// The cursor moved between this window and one of its descendants.
+    Ancestor,
+
+    /// The cursor moved between an ancestor and a descendant of this window.
+    Virtual,
+
+    /// The cursor moved between this window and one of its ancestors.
+    Inferior,
+
+    /// The cursor moved between this window and a window that is neither its ancestor nor its
+    /// descendant.
+    Nonlinear,
+
+    /// The cursor moved between a descendant of this window and a window that is neither its
+    /// ancestor nor its descendant.
+    NonlinearVirtual,
+}
+
+/// A button on a pointer.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Button {
+    /// The left, or primary, button.
+    Left,
+
+    /// The middle button.
+    Middle,
+
+    /// The right, or secondary, button.
+    Right,
+
+    /// Another button, identified by the windowing system's number for it.
+    Other(u16),
+}
+
+/// A button press or release, routed to a window.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct ButtonEvent<T = i32> {
+    /// The window that receives the event.
+    pub window: WindowKey,
+
+    /// The button that was pressed or released.
+    pub button: Button,
+
+    /// Whether the button was pressed, rather than released.
+    pub pressed: bool,
+
+    /// The position of the cursor, relative to the window's top-left corner.
+    pub position: Point<T>,
+}
+
+/// The shape of the cursor over a window.
+///
+/// The meaning of the identifier is up to the caller; it usually names a cursor image or one
+/// of the windowing system's standard cursors.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct CursorShape(pub u64);
+
+/// Where the pointer is relative to a window, like the reply to X11's `QueryPointer`.
+///
+/// Returned by [`CursorState::query_pointer`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct PointerQuery<T = i32> {
+    /// Whether the pointer is inside of the root window.
+    pub same_screen: bool,
+
+    /// The child of the window that the pointer is over, if any.
+    pub child: Option<WindowKey>,
+
+    /// The position of the pointer.
+    pub root_pos: Point<T>,
+
+    /// The position of the pointer, relative to the window's top-left corner.
+    pub win_pos: Point<T>,
+}
+
+/// Error with grabbing the pointer.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum GrabError {
+    /// The pointer is already grabbed by another window.
+    AlreadyGrabbed,
+
+    /// The window is not viewable.
+    NotViewable,
+}
+
+impl<T: Coordinate> CursorState<T> {
+    /// Creates a new cursor state.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the last known cursor position.
+    pub fn position(&self) -> Point<T> {
+        self.position
+    }
+
+    /// Returns the windows currently under the cursor.
+    ///
+    /// The list begins with the root window and ends with the topmost window under the cursor.
+    pub fn windows(&self) -> &[WindowKey] {
+        &self.windows
+    }
+
+    /// Returns the topmost window under the cursor.
+    pub fn window(&self) -> Option<WindowKey> {
+        self.windows.last().copied()
+    }
+
+    /// Returns the window that pointer events should be delivered to.
+    ///
+    /// This is the grabbing window if the pointer is grabbed, and the topmost window under the
+    /// cursor otherwise.
+    pub fn target(&self) -> Option<WindowKey> {
+        self.grab.or_else(|| self.window())
+    }
+
+    /// Returns the window that has grabbed the pointer.
+    pub fn grab(&self) -> Option<WindowKey> {
+        self.grab
+    }
+
+    /// Grab the pointer, delivering all pointer events to this window until it is ungrabbed.
+    ///
+    /// Grabbing the pointer again with the grabbing window is allowed.
+    pub fn grab_pointer<const N: usize, const R: usize>(
+        &mut self,
+        table: &WindowTable<T, N, R>,
+        key: WindowKey,
+    ) -> Result<(), GrabError> {
+        match self.grab {
+            Some(grab) if grab != key => return Err(GrabError::AlreadyGrabbed),
+            _ => {}
+        }
+
+        if !table.is_viewable(key) {
+            return Err(GrabError::NotViewable);
+        }
+
+        self.grab = Some(key);
+        self.implicit = false;
+        Ok(())
+    }
+
+    /// Confine the pointer to a window, so that it can't be moved outside of its rectangle.
+    ///
+    /// Later positions passed to [`update`](Self::update) are clamped to the window's
+    /// rectangle, and [`position`](Self::position) returns the clamped position. The pointer
+    /// is moved into the window right away, which returns the events for that move. The
+    /// confinement is released if the window stops being viewable.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the window is not viewable.
+    pub fn confine_pointer<const N: usize, const R: usize>(
+        &mut self,
+        table: &WindowTable<T, N, R>,
+        key: WindowKey,
+    ) -> Result<SmallVec<[CursorEvent; 4]>, GrabError> {
+        if !table.is_viewable(key) {
+            return Err(GrabError::NotViewable);
+        }
+
+        self.confine = Some(key);
+        Ok(self.update(table, self.position))
+    }
+
+    /// Stop confining the pointer.
+    pub fn unconfine_pointer(&mut self) {
+        self.confine = None;
+    }
+
+    /// Returns the window that the pointer is confined to.
+    pub fn confinement(&self) -> Option<WindowKey> {
+        self.confine
+    }
+
+    /// Release the pointer grab.
+    ///
+    /// Crossing events are not generated while the pointer is grabbed, so this returns the
+    /// events needed to bring the windows under the cursor up to date.
+    pub fn ungrab_pointer<const N: usize, const R: usize>(
+        &mut self,
+        table: &WindowTable<T, N, R>,
+    ) -> SmallVec<[CursorEvent; 4]> {
+        self.implicit = false;
+        if self.grab.take().is_none() {
+            return SmallVec::new();
+        }
+
+        self.update(table, self.position)
+    }
+
+    /// Move the cursor to a new position.
+    ///
+    /// Returns the windows that the cursor has left, innermost first, followed by the windows
+    /// that the cursor has entered, outermost first, each with its [`CrossingDetail`]. While
+    /// the pointer is grabbed, only the position is updated and no events are generated. The
+    /// grab is released if the grabbing window stops being viewable.
+    pub fn update<const N: usize, const R: usize>(
+        &mut self,
+        table: &WindowTable<T, N, R>,
+        position: impl Into<Point<T>>,
+    ) -> SmallVec<[CursorEvent; 4]> {
+        let mut position = position.into();
+        if let Some(confine) = self.confine {
+            if table.is_viewable(confine) {
+                // Keep the position inside of the rectangle, which excludes its right and
+                // bottom edges.
+                let rect = table.rect(confine);
+                let one = T::from_i32(1);
+                let right = coord::max(rect.right.saturating_sub(one), rect.left);
+                let bottom = coord::max(rect.bottom.saturating_sub(one), rect.top);
+                position = Point::new(
+                    coord::min(coord::max(position.x, rect.left), right),
+                    coord::min(coord::max(position.y, rect.top), bottom),
+                );
+            } else {
+                self.confine = None;
+            }
+        }
+        self.position = position;
+
+        if let Some(grab) = self.grab {
+            if table.is_viewable(grab) {
+                return SmallVec::new();
+            }
+
+            self.grab = None;
+            self.implicit = false;
+        }
+
+        let windows = table.hit_path(position);
+        let events = crossing(&self.windows, &windows);
+        self.windows = windows;
+        events
+    }
+
+    /// Returns the cursor shape that should be shown.
+    ///
+    /// This is the shape of the [target](Self::target) window, or of its nearest ancestor that
+    /// has a shape if it has none. Returns `None` if no window in the chain has a shape.
+    pub fn cursor<const N: usize, const R: usize>(
+        &self,
+        table: &WindowTable<T, N, R>,
+    ) -> Option<CursorShape> {
+        let mut window = self.target().filter(|&key| table.contains_key(key));
+        while let Some(key) = window {
+            if let Some(shape) = table.cursor(key) {
+                return Some(shape);
+            }
+
+            window = table.parent(key);
+        }
+
+        None
+    }
+
+    /// Find where the pointer is relative to a window.
+    ///
+    /// The child is the primary child of the window that leads to the topmost window under the
+    /// pointer, or `None` if the pointer is not over one of the window's descendants.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn query_pointer<const N: usize, const R: usize>(
+        &self,
+        table: &WindowTable<T, N, R>,
+        key: WindowKey,
+    ) -> PointerQuery<T> {
+        let rect = table.rect(key);
+        let path = table.hit_path(self.position);
+        let child = path
+            .iter()
+            .position(|&window| window == key)
+            .and_then(|index| path.get(index + 1).copied());
+
+        PointerQuery {
+            same_screen: matches!(
+                table.root(),
+                Some(root) if table.rect(root).contains(self.position)
+            ),
+            child,
+            root_pos: self.position,
+            win_pos: Point::new(
+                self.position.x.saturating_sub(rect.left),
+                self.position.y.saturating_sub(rect.top),
+            ),
+        }
+    }
+
+    /// Move the pointer to a position on behalf of the program, rather than the user.
+    ///
+    /// This generates the same events as [`update`](Self::update) would for the user moving the
+    /// pointer there, so that programmatic moves behave like real ones.
+    pub fn warp_to<const N: usize, const R: usize>(
+        &mut self,
+        table: &WindowTable<T, N, R>,
+        position: impl Into<Point<T>>,
+    ) -> SmallVec<[CursorEvent; 4]> {
+        self.update(table, position)
+    }
+
+    /// Move the pointer to a position relative to a window's top-left corner.
+    ///
+    /// See [`warp_to`](Self::warp_to).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn warp_to_window<const N: usize, const R: usize>(
+        &mut self,
+        table: &WindowTable<T, N, R>,
+        key: WindowKey,
+        position: impl Into<Point<T>>,
+    ) -> SmallVec<[CursorEvent; 4]> {
+        let rect = table.rect(key);
+        let position = position.into();
+        self.warp_to(
+            table,
+            Point::new(
+                rect.left.saturating_add(position.x),
+                rect.top.saturating_add(position.y),
+            ),
+        )
+    }
+
+    /// Returns the buttons that are held down, in the order they were pressed.
+    pub fn buttons(&self) -> &[Button] {
+        &self.buttons
+    }
+
+    /// Press a button, returning the event for the window that receives it.
+    ///
+    /// The event goes to the [target](Self::target) window. If the pointer is not already
+    /// grabbed, pressing the first button grabs it for that window until every button is
+    /// released, so that the release goes to the same window as the press. Returns `None` if
+    /// there is no window under the cursor.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the window is blocked by a [modal window](WindowTable::set_modal).
+    /// The button is not pressed and the pointer is not grabbed.
+    pub fn button_down<const N: usize, const R: usize>(
+        &mut self,
+        table: &WindowTable<T, N, R>,
+        button: Button,
+    ) -> Result<Option<ButtonEvent<T>>, ModalBlocked> {
+        let window = match self.target().filter(|&key| table.is_viewable(key)) {
+            Some(window) => window,
+            None => return Ok(None),
+        };
+        if let Some(modal) = table.blocking_modal(window) {
+            return Err(ModalBlocked { window, modal });
+        }
+
+        if !self.buttons.contains(&button) {
+            self.buttons.push(button);
+        }
+
+        if self.grab.is_none() {
+            self.grab = Some(window);
+            self.implicit = true;
+        }
+
+        Ok(Some(self.button_event(table, window, button, true)))
+    }
+
+    /// Release a button, returning the event for the window that receives it.
+    ///
+    /// Releasing the last button ends the grab made by pressing it, which also returns the
+    /// crossing events needed to bring the windows under the cursor up to date. Releasing a
+    /// button that is not held down does nothing.
+    pub fn button_up<const N: usize, const R: usize>(
+        &mut self,
+        table: &WindowTable<T, N, R>,
+        button: Button,
+    ) -> (Option<ButtonEvent<T>>, SmallVec<[CursorEvent; 4]>) {
+        let index = match self.buttons.iter().position(|&held| held == button) {
+            Some(index) => index,
+            None => return (None, SmallVec::new()),
+        };
+        self.buttons.remove(index);
+
+        let event = self
+            .target()
+            .filter(|&key| table.is_viewable(key))
+            .map(|window| self.button_event(table, window, button, false));
+
+        let events = if self.buttons.is_empty() && self.implicit {
+            self.ungrab_pointer(table)
+        } else {
+            SmallVec::new()
+        };
+
+        (event, events)
+    }
+
+    /// Build a button event for a window.
+    fn button_event<const N: usize, const R: usize>(
+        &self,
+        table: &WindowTable<T, N, R>,
+        window: WindowKey,
+        button: Button,
+        pressed: bool,
+    ) -> ButtonEvent<T> {
+        let rect = table.rect(window);
+        ButtonEvent {
+            window,
+            button,
+            pressed,
+            position: Point::new(
+                self.position.x.saturating_sub(rect.left),
+                self.position.y.saturating_sub(rect.top),
+            ),
+        }
+    }
+}
+
+impl<T> Default for PointerState<T> {
+    fn default() -> Self {
+        Self {
+            pointers: BTreeMap::new(),
+        }
+    }
+}
+
+impl<T: Coordinate> PointerState<T> {
+    /// Creates a new state with no pointers.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of pointers being tracked.
+    pub fn len(&self) -> usize {
+        self.pointers.len()
+    }
+
+    /// Tell if no pointers are being tracked.
+    pub fn is_empty(&self) -> bool {
+        self.pointers.is_empty()
+    }
+
+    /// Iterate over the pointers and their states.
+    pub fn iter(&self) -> impl Iterator<Item = (PointerId, &CursorState<T>)> + '_ {
+        self.pointers.iter().map(|(&id, state)| (id, state))
+    }
+
+    /// Returns the state of a pointer, or `None` if it is not being tracked.
+    pub fn pointer(&self, id: PointerId) -> Option<&CursorState<T>> {
+        self.pointers.get(&id)
+    }
+
+    /// Returns the state of a pointer mutably, or `None` if it is not being tracked.
+    pub fn pointer_mut(&mut self, id: PointerId) -> Option<&mut CursorState<T>> {
+        self.pointers.get_mut(&id)
+    }
+
+    /// Returns the pointers that are over a window or one of its descendants.
+    pub fn pointers_in(&self, key: WindowKey) -> impl Iterator<Item = PointerId> + '_ {
+        self.pointers
+            .iter()
+            .filter(move |(_, state)| state.windows.contains(&key))
+            .map(|(&id, _)| id)
+    }
+
+    /// Move a pointer to a new position, starting to track it if it is new.
+    ///
+    /// Returns the crossing events for this pointer, as returned by [`CursorState::update`].
+    pub fn update<const N: usize, const R: usize>(
+        &mut self,
+        table: &WindowTable<T, N, R>,
+        id: PointerId,
+        position: impl Into<Point<T>>,
+    ) -> SmallVec<[CursorEvent; 4]> {
+        self.pointers.entry(id).or_default().update(table, position)
+    }
+
+    /// Stop tracking a pointer, such as when a finger is lifted from a touchscreen.
+    ///
+    /// Returns the windows that the pointer has left, innermost first.
+    pub fn remove(&mut self, id: PointerId) -> SmallVec<[CursorEvent; 4]> {
+        match self.pointers.remove(&id) {
+            Some(state) => crossing(&state.windows, &[]),
+            None => SmallVec::new(),
+        }
+    }
+
+    /// Grab a pointer, delivering all of its events to a window until it is ungrabbed.
+    ///
+    /// Other pointers are not affected.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the pointer is not being tracked.
+    pub fn grab_pointer<const N: usize, const R: usize>(
+        &mut self,
+        table: &WindowTable<T, N, R>,
+        id: PointerId,
+        key: WindowKey,
+    ) -> Result<(), GrabError> {
+        self.pointers
+            .get_mut(&id)
+            .expect("pointer is not being tracked")
+            .grab_pointer(table, key)
+    }
+
+    /// Release the grab on a pointer.
+    ///
+    /// Returns the crossing events for this pointer, as returned by
+    /// [`CursorState::ungrab_pointer`]. Does nothing if the pointer is not being tracked.
+    pub fn ungrab_pointer<const N: usize, const R: usize>(
+        &mut self,
+        table: &WindowTable<T, N, R>,
+        id: PointerId,
+    ) -> SmallVec<[CursorEvent; 4]> {
+        match self.pointers.get_mut(&id) {
+            Some(state) => state.ungrab_pointer(table),
+            None => SmallVec::new(),
+        }
+    }
+}
+
+impl<T: Coordinate, const N: usize, const R: usize> WindowTable<T, N, R> {
+    /// Set the shape of the cursor while it is over a window.
+    ///
+    /// Passing `None` makes the window use the shape of its primary parent, which is the
+    /// default. See [`CursorState::cursor`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn set_cursor(&mut self, key: WindowKey, shape: Option<CursorShape>) {
+        let window = &mut self.windows[key.0];
+        if shape.is_some() || window.attributes.is_some() {
+            window.attributes_mut().cursor = shape;
+        }
+    }
+
+    /// Returns the shape of the cursor set on a window.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn cursor(&self, key: WindowKey) -> Option<CursorShape> {
+        self.windows[key.0]
+            .attributes()
+            .and_then(|attributes| attributes.cursor)
+    }
+}
+
+/// Generate the crossing events for the cursor moving from one path of windows to another.
+fn crossing(old: &[WindowKey], new: &[WindowKey]) -> SmallVec<[CursorEvent; 4]> {
+    // Find the length of the path that both stacks share.
+    let common = old
+        .iter()
+        .zip(new.iter())
+        .take_while(|(old, new)| old == new)
+        .count();
+
+    let mut events = SmallVec::new();
+    if old.len() == new.len() && common == old.len() {
+        return events;
+    }
+
+    let (leave, enter) = (&old[common..], &new[common..]);
+    if common == old.len() && !old.is_empty() {
+        // The cursor moved into a descendant.
+        events.push(CursorEvent::Leave(
+            old[common - 1],
+            CrossingDetail::Inferior,
+        ));
+        events.extend(
+            enter[..enter.len() - 1]
+                .iter()
+                .map(|&key| CursorEvent::Enter(key, CrossingDetail::Virtual)),
+        );
+        events.push(CursorEvent::Enter(
+            enter[enter.len() - 1],
+            CrossingDetail::Ancestor,
+        ));
+    } else if common == new.len() && !new.is_empty() {
+        // The cursor moved out to an ancestor.
+        events.push(CursorEvent::Leave(
+            leave[leave.len() - 1],
+            CrossingDetail::Ancestor,
+        ));
+        events.extend(
+            leave[..leave.len() - 1]
+                .iter()
+                .rev()
+                .map(|&key| CursorEvent::Leave(key, CrossingDetail::Virtual)),
+        );
+        events.push(CursorEvent::Enter(
+            new[common - 1],
+            CrossingDetail::Inferior,
+        ));
+    } else {
+        if let Some((&last, rest)) = leave.split_last() {
+            events.push(CursorEvent::Leave(last, CrossingDetail::Nonlinear));
+            events.extend(
+                rest.iter()
+                    .rev()
+                    .map(|&key| CursorEvent::Leave(key, CrossingDetail::NonlinearVirtual)),
+            );
+        }
+        if let Some((&last, rest)) = enter.split_last() {
+            events.extend(
+                rest.iter()
+                    .map(|&key| CursorEvent::Enter(key, CrossingDetail::NonlinearVirtual)),
+            );
+            events.push(CursorEvent::Enter(last, CrossingDetail::Nonlinear));
+        }
+    }
+
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Button, ButtonEvent, CrossingDetail, CursorEvent, CursorShape, CursorState, GrabError,
+        PointerId, PointerQuery, PointerState,
+    };
+    use crate::{ModalBlocked, ModalScope, Point, Rectangle, WindowTable};
+
+    #[test]
+    fn cursor_crossing() {
+        let mut window_table = WindowTable::new();
+
+        let root = window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let panel = window_table.insert(Rectangle::new(10, 10, 60, 60)).unwrap();
+
+        let mut cursor = CursorState::new();
+        let events = cursor.update(&window_table, (15, 15));
+        assert_eq!(
+            &events[..],
+            &[
+                CursorEvent::Enter(root, CrossingDetail::NonlinearVirtual),
+                CursorEvent::Enter(panel, CrossingDetail::Nonlinear)
+            ]
+        );
+
+        let events = cursor.update(&window_table, (5, 5));
+        assert_eq!(
+            &events[..],
+            &[
+                CursorEvent::Leave(panel, CrossingDetail::Ancestor),
+                CursorEvent::Enter(root, CrossingDetail::Inferior)
+            ]
+        );
+        assert_eq!(cursor.window(), Some(root));
+        assert!(cursor.update(&window_table, (6, 6)).is_empty());
+    }
+
+    #[test]
+    fn crossing_detail() {
+        let mut window_table = WindowTable::new();
+
+        let root = window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let panel = window_table.insert(Rectangle::new(10, 10, 60, 60)).unwrap();
+        let button = window_table.insert(Rectangle::new(20, 20, 30, 30)).unwrap();
+        let other = window_table.insert(Rectangle::new(70, 70, 90, 90)).unwrap();
+
+        let mut cursor = CursorState::new();
+        cursor.update(&window_table, (5, 5));
+
+        // Moving into a descendant passes through the windows in between.
+        assert_eq!(
+            &cursor.update(&window_table, (25, 25))[..],
+            &[
+                CursorEvent::Leave(root, CrossingDetail::Inferior),
+                CursorEvent::Enter(panel, CrossingDetail::Virtual),
+                CursorEvent::Enter(button, CrossingDetail::Ancestor)
+            ]
+        );
+
+        // Moving to an unrelated window goes through the common ancestor.
+        assert_eq!(
+            &cursor.update(&window_table, (80, 80))[..],
+            &[
+                CursorEvent::Leave(button, CrossingDetail::Nonlinear),
+                CursorEvent::Leave(panel, CrossingDetail::NonlinearVirtual),
+                CursorEvent::Enter(other, CrossingDetail::Nonlinear)
+            ]
+        );
+    }
+
+    #[test]
+    fn grab() {
+        let mut window_table = WindowTable::new();
+
+        window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let panel = window_table.insert(Rectangle::new(10, 10, 60, 60)).unwrap();
+        let other = window_table.insert(Rectangle::new(70, 70, 90, 90)).unwrap();
+
+        let mut cursor = CursorState::new();
+        cursor.update(&window_table, (15, 15));
+        cursor.grab_pointer(&window_table, panel).unwrap();
+        assert_eq!(
+            cursor.grab_pointer(&window_table, other),
+            Err(GrabError::AlreadyGrabbed)
+        );
+
+        // Motion outside of the grabbing window still targets it.
+        assert!(cursor.update(&window_table, (80, 80)).is_empty());
+        assert_eq!(cursor.target(), Some(panel));
+
+        let events = cursor.ungrab_pointer(&window_table);
+        assert_eq!(
+            &events[..],
+            &[
+                CursorEvent::Leave(panel, CrossingDetail::Nonlinear),
+                CursorEvent::Enter(other, CrossingDetail::Nonlinear)
+            ]
+        );
+        assert_eq!(cursor.target(), Some(other));
+    }
+
+    #[test]
+    fn multiple_pointers() {
+        let mut window_table = WindowTable::new();
+
+        let root = window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let left = window_table.insert(Rectangle::new(0, 0, 40, 40)).unwrap();
+        let right = window_table
+            .insert(Rectangle::new(60, 60, 100, 100))
+            .unwrap();
+
+        let (mouse, finger) = (PointerId(0), PointerId(1));
+        let mut pointers = PointerState::new();
+        assert_eq!(
+            &pointers.update(&window_table, mouse, (10, 10))[..],
+            &[
+                CursorEvent::Enter(root, CrossingDetail::NonlinearVirtual),
+                CursorEvent::Enter(left, CrossingDetail::Nonlinear)
+            ]
+        );
+        assert_eq!(
+            &pointers.update(&window_table, finger, (70, 70))[..],
+            &[
+                CursorEvent::Enter(root, CrossingDetail::NonlinearVirtual),
+                CursorEvent::Enter(right, CrossingDetail::Nonlinear)
+            ]
+        );
+        assert_eq!(pointers.len(), 2);
+        assert!(pointers.pointers_in(root).eq([mouse, finger]));
+        assert!(pointers.pointers_in(left).eq([mouse]));
+
+        // Grabs only apply to one pointer.
+        pointers.grab_pointer(&window_table, mouse, left).unwrap();
+        assert!(pointers.update(&window_table, mouse, (80, 80)).is_empty());
+        assert_eq!(
+            &pointers.update(&window_table, finger, (10, 10))[..],
+            &[
+                CursorEvent::Leave(right, CrossingDetail::Nonlinear),
+                CursorEvent::Enter(left, CrossingDetail::Nonlinear)
+            ]
+        );
+        assert_eq!(pointers.pointer(mouse).unwrap().target(), Some(left));
+
+        assert_eq!(
+            &pointers.remove(finger)[..],
+            &[
+                CursorEvent::Leave(left, CrossingDetail::Nonlinear),
+                CursorEvent::Leave(root, CrossingDetail::NonlinearVirtual)
+            ]
+        );
+        assert!(pointers.pointer(finger).is_none());
+        assert_eq!(
+            &pointers.ungrab_pointer(&window_table, mouse)[..],
+            &[
+                CursorEvent::Leave(left, CrossingDetail::Nonlinear),
+                CursorEvent::Enter(right, CrossingDetail::Nonlinear)
+            ]
+        );
+    }
+
+    #[test]
+    fn buttons() {
+        let mut window_table = WindowTable::new();
+
+        window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let panel = window_table.insert(Rectangle::new(10, 10, 60, 60)).unwrap();
+        let other = window_table.insert(Rectangle::new(70, 70, 90, 90)).unwrap();
+
+        let mut cursor = CursorState::new();
+        cursor.update(&window_table, (15, 15));
+        assert_eq!(
+            cursor.button_down(&window_table, Button::Left),
+            Ok(Some(ButtonEvent {
+                window: panel,
+                button: Button::Left,
+                pressed: true,
+                position: Point::new(5, 5),
+            }))
+        );
+        assert_eq!(cursor.grab(), Some(panel));
+
+        // The release goes to the window that was pressed, even after dragging off of it.
+        assert!(cursor.update(&window_table, (80, 80)).is_empty());
+        cursor
+            .button_down(&window_table, Button::Right)
+            .unwrap()
+            .unwrap();
+        let (event, events) = cursor.button_up(&window_table, Button::Left);
+        assert_eq!(event.unwrap().window, panel);
+        assert!(events.is_empty());
+        assert_eq!(cursor.buttons(), [Button::Right]);
+
+        let (event, events) = cursor.button_up(&window_table, Button::Right);
+        assert_eq!(
+            event,
+            Some(ButtonEvent {
+                window: panel,
+                button: Button::Right,
+                pressed: false,
+                position: Point::new(70, 70),
+            })
+        );
+        assert_eq!(
+            &events[..],
+            &[
+                CursorEvent::Leave(panel, CrossingDetail::Nonlinear),
+                CursorEvent::Enter(other, CrossingDetail::Nonlinear)
+            ]
+        );
+        assert_eq!(cursor.grab(), None);
+        assert_eq!(
+            cursor.button_up(&window_table, Button::Right),
+            (None, smallvec::SmallVec::new())
+        );
+
+        // Explicit grabs outlast the buttons.
+        cursor.grab_pointer(&window_table, panel).unwrap();
+        cursor
+            .button_down(&window_table, Button::Left)
+            .unwrap()
+            .unwrap();
+        cursor.button_up(&window_table, Button::Left);
+        assert_eq!(cursor.grab(), Some(panel));
+    }
+
+    #[test]
+    fn buttons_blocked_by_modal() {
+        let mut window_table = WindowTable::new();
+
+        window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let panel = window_table.insert(Rectangle::new(10, 10, 60, 60)).unwrap();
+        let dialog = window_table.insert(Rectangle::new(70, 70, 90, 90)).unwrap();
+        window_table.set_modal(dialog, ModalScope::Root);
+
+        let mut cursor = CursorState::new();
+        cursor.update(&window_table, (15, 15));
+        assert_eq!(
+            cursor.button_down(&window_table, Button::Left),
+            Err(ModalBlocked {
+                window: panel,
+                modal: dialog,
+            })
+        );
+        assert!(cursor.buttons().is_empty());
+        assert_eq!(cursor.grab(), None);
+        assert_eq!(
+            cursor.button_up(&window_table, Button::Left),
+            (None, smallvec::SmallVec::new())
+        );
+
+        // The dialog itself still takes the press.
+        cursor.update(&window_table, (75, 75));
+        let event = cursor.button_down(&window_table, Button::Left).unwrap();
+        assert_eq!(event.unwrap().window, dialog);
+
+        // Once the dialog is unmapped, the panel is no longer blocked.
+        cursor.button_up(&window_table, Button::Left);
+        window_table.unmap(dialog);
+        cursor.update(&window_table, (15, 15));
+        let event = cursor.button_down(&window_table, Button::Left).unwrap();
+        assert_eq!(event.unwrap().window, panel);
+    }
+
+    #[test]
+    fn cursor_shape() {
+        let mut window_table = WindowTable::new();
+
+        let root = window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let panel = window_table.insert(Rectangle::new(10, 10, 60, 60)).unwrap();
+        let button = window_table.insert(Rectangle::new(20, 20, 30, 30)).unwrap();
+
+        let mut cursor = CursorState::new();
+        cursor.update(&window_table, (25, 25));
+        assert_eq!(cursor.cursor(&window_table), None);
+
+        window_table.set_cursor(root, Some(CursorShape(1)));
+        assert_eq!(cursor.cursor(&window_table), Some(CursorShape(1)));
+
+        window_table.set_cursor(button, Some(CursorShape(2)));
+        assert_eq!(window_table.cursor(button), Some(CursorShape(2)));
+        assert_eq!(cursor.cursor(&window_table), Some(CursorShape(2)));
+
+        cursor.update(&window_table, (50, 50));
+        assert_eq!(cursor.window(), Some(panel));
+        assert_eq!(cursor.cursor(&window_table), Some(CursorShape(1)));
+
+        window_table.set_cursor(button, None);
+        assert_eq!(window_table.cursor(button), None);
+    }
+
+    #[test]
+    fn query_pointer() {
+        let mut window_table = WindowTable::new();
+
+        let root = window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let panel = window_table.insert(Rectangle::new(10, 10, 60, 60)).unwrap();
+        let button = window_table.insert(Rectangle::new(20, 20, 30, 30)).unwrap();
+
+        let mut cursor = CursorState::new();
+        cursor.update(&window_table, (25, 25));
+        assert_eq!(
+            cursor.query_pointer(&window_table, root),
+            PointerQuery {
+                same_screen: true,
+                child: Some(panel),
+                root_pos: Point::new(25, 25),
+                win_pos: Point::new(25, 25),
+            }
+        );
+        assert_eq!(
+            cursor.query_pointer(&window_table, panel).child,
+            Some(button)
+        );
+
+        let query = cursor.query_pointer(&window_table, button);
+        assert_eq!(query.child, None);
+        assert_eq!(query.win_pos, Point::new(5, 5));
+
+        cursor.update(&window_table, (150, 150));
+        let query = cursor.query_pointer(&window_table, panel);
+        assert!(!query.same_screen);
+        assert_eq!(query.child, None);
+        assert_eq!(query.win_pos, Point::new(140, 140));
+    }
+
+    #[test]
+    fn warp() {
+        let mut window_table = WindowTable::new();
+
+        let root = window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let panel = window_table.insert(Rectangle::new(10, 10, 60, 60)).unwrap();
+
+        let mut cursor = CursorState::new();
+        cursor.update(&window_table, (5, 5));
+        assert_eq!(
+            &cursor.warp_to_window(&window_table, panel, (5, 5))[..],
+            &[
+                CursorEvent::Leave(root, CrossingDetail::Inferior),
+                CursorEvent::Enter(panel, CrossingDetail::Ancestor)
+            ]
+        );
+        assert_eq!(cursor.position(), Point::new(15, 15));
+
+        assert_eq!(
+            &cursor.warp_to(&window_table, (80, 80))[..],
+            &[
+                CursorEvent::Leave(panel, CrossingDetail::Ancestor),
+                CursorEvent::Enter(root, CrossingDetail::Inferior)
+            ]
+        );
+        assert_eq!(cursor.window(), Some(root));
+    }
+
+    #[test]
+    fn confine() {
+        let mut window_table = WindowTable::new();
+
+        let root = window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let panel = window_table.insert(Rectangle::new(10, 10, 60, 60)).unwrap();
+
+        let mut cursor = CursorState::new();
+        cursor.update(&window_table, (5, 5));
+        assert_eq!(
+            &cursor.confine_pointer(&window_table, panel).unwrap()[..],
+            &[
+                CursorEvent::Leave(root, CrossingDetail::Inferior),
+                CursorEvent::Enter(panel, CrossingDetail::Ancestor)
+            ]
+        );
+        assert_eq!(cursor.position(), Point::new(10, 10));
+        assert_eq!(cursor.confinement(), Some(panel));
+
+        assert!(cursor.update(&window_table, (80, 30)).is_empty());
+        assert_eq!(cursor.position(), Point::new(59, 30));
+
+        // The confinement ends with the window.
+        window_table.unmap(panel);
+        cursor.update(&window_table, (80, 30));
+        assert_eq!(cursor.position(), Point::new(80, 30));
+        assert_eq!(cursor.confinement(), None);
+        assert_eq!(
+            cursor.confine_pointer(&window_table, panel),
+            Err(GrabError::NotViewable)
+        );
+    }
+}
@@ -0,0 +1,169 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later OR MPL-2.0
+// This file is a part of `windowless`.
+//
+// `windowless` is free software: you can redistribute it and/or modify it under the terms of
+// either:
+//
+// * GNU Lesser General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+// * Mozilla Public License as published by the Mozilla Foundation, version 2.
+//
+// `windowless` is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+// See the GNU Lesser General Public License or the Mozilla Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License and the Mozilla
+// Public License along with `windowless`. If not, see <https://www.gnu.org/licenses/> or
+// <https://www.mozilla.org/en-US/MPL/2.0/>.
+
+//! Pushing damage to a sink as it happens.
+
+use crate::{Coordinate, Rectangle, Region, WindowTable};
+use alloc::boxed::Box;
+use core::fmt;
+
+/// Receives the damaged parts of the screen as soon as they are damaged.
+///
+/// Register a sink with [`WindowTable::set_damage_sink`]. Any closure that takes a rectangle
+/// can be used as a sink.
+pub trait DamageSink<T: Coordinate = i32> {
+    /// A rectangle of the screen was damaged.
+    ///
+    /// The same area may be reported more than once, and rectangles may overlap.
+    fn damage(&mut self, rect: Rectangle<T>);
+}
+
+impl<T: Coordinate, F: FnMut(Rectangle<T>)> DamageSink<T> for F {
+    fn damage(&mut self, rect: Rectangle<T>) {
+        self(rect)
+    }
+}
+
+/// The damage sink registered on a table, and whether the table keeps damage too.
+///
+/// The sink is not copied when the table is cloned.
+pub(crate) struct Sink<T: Coordinate> {
+    /// The sink.
+    sink: Option<Box<dyn DamageSink<T> + Send>>,
+
+    /// Whether damage is also accumulated for `take_damage`.
+    accumulate: bool,
+}
+
+impl<T: Coordinate> Default for Sink<T> {
+    fn default() -> Self {
+        Self {
+            sink: None,
+            accumulate: true,
+        }
+    }
+}
+
+impl<T: Coordinate> Clone for Sink<T> {
+    fn clone(&self) -> Self {
+        Self {
+            sink: None,
+            accumulate: self.accumulate,
+        }
+    }
+}
+
+impl<T: Coordinate> fmt::Debug for Sink<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sink")
+            .field("sink", &self.sink.is_some())
+            .field("accumulate", &self.accumulate)
+            .finish()
+    }
+}
+
+impl<T: Coordinate, const N: usize, const R: usize> WindowTable<T, N, R> {
+    /// Register a sink to push damage to as soon as it happens, returning the previous one.
+    ///
+    /// Every change that damages the screen passes the damaged rectangles to the sink right
+    /// away, rather than waiting for [`take_damage`](Self::take_damage). The damage is still
+    /// accumulated as well, unless [turned off](Self::set_accumulate_damage). Sinks are not
+    /// copied when the table is cloned.
+    pub fn set_damage_sink(
+        &mut self,
+        sink: impl DamageSink<T> + Send + 'static,
+    ) -> Option<Box<dyn DamageSink<T> + Send>> {
+        self.damage_sink.sink.replace(Box::new(sink))
+    }
+
+    /// Unregister the damage sink, returning it.
+    pub fn remove_damage_sink(&mut self) -> Option<Box<dyn DamageSink<T> + Send>> {
+        self.damage_sink.sink.take()
+    }
+
+    /// Set whether damage is accumulated for [`take_damage`](Self::take_damage).
+    ///
+    /// Damage is accumulated by default. Turning it off is useful when a
+    /// [damage sink](Self::set_damage_sink) handles all of the damage, and throws away the
+    /// damage accumulated so far. Without it, [`take_damage`](Self::take_damage),
+    /// [`damage_spans`](Self::damage_spans) and
+    /// [`take_output_damage`](Self::take_output_damage) report nothing.
+    pub fn set_accumulate_damage(&mut self, accumulate: bool) {
+        self.damage_sink.accumulate = accumulate;
+        if !accumulate {
+            self.damage = Region::default();
+        }
+    }
+
+    /// Tell if damage is accumulated for [`take_damage`](Self::take_damage).
+    pub fn accumulates_damage(&self) -> bool {
+        self.damage_sink.accumulate
+    }
+
+    /// Damage a rectangle of the screen.
+    pub(crate) fn add_damage_rect(&mut self, rect: Rectangle<T>) {
+        if let Some(sink) = &mut self.damage_sink.sink {
+            sink.damage(rect);
+        }
+        if self.damage_sink.accumulate {
+            self.damage.union_rect(rect);
+        }
+    }
+
+    /// Damage a region of the screen.
+    pub(crate) fn add_damage(&mut self, region: &Region<T, R>) {
+        if let Some(sink) = &mut self.damage_sink.sink {
+            for &rect in region.rects() {
+                sink.damage(rect);
+            }
+        }
+        if self.damage_sink.accumulate {
+            self.damage.union(region);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Rectangle, WindowTable};
+    use alloc::sync::Arc;
+    use core::sync::atomic::{AtomicI32, Ordering};
+
+    #[test]
+    fn damage_sink() {
+        let mut window_table = WindowTable::new();
+        let area = Arc::new(AtomicI32::new(0));
+
+        let sink = area.clone();
+        window_table.set_damage_sink(move |rect: Rectangle| {
+            sink.fetch_add(rect.saturating_area(), Ordering::Relaxed);
+        });
+
+        window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let a = window_table.insert(Rectangle::new(10, 10, 50, 50)).unwrap();
+        assert_eq!(area.load(Ordering::Relaxed), 10000 + 1600);
+        assert_eq!(window_table.take_damage().area(), 10000);
+
+        window_table.set_accumulate_damage(false);
+        window_table.set_opacity(a, 100);
+        assert_eq!(area.load(Ordering::Relaxed), 10000 + 1600 + 1600);
+        assert!(window_table.take_damage().is_empty());
+
+        assert!(window_table.remove_damage_sink().is_some());
+    }
+}
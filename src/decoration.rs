@@ -0,0 +1,329 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later OR MPL-2.0
+// This file is a part of `windowless`.
+//
+// `windowless` is free software: you can redistribute it and/or modify it under the terms of
+// either:
+//
+// * GNU Lesser General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+// * Mozilla Public License as published by the Mozilla Foundation, version 2.
+//
+// `windowless` is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+// See the GNU Lesser General Public License or the Mozilla Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License and the Mozilla
+// Public License along with `windowless`. If not, see <https://www.gnu.org/licenses/> or
+// <https://www.mozilla.org/en-US/MPL/2.0/>.
+
+//! Frames that decorate client windows with a border and a title bar.
+
+use crate::{coord, Anchors, Coordinate, InsertError, Point, Rectangle, WindowKey, WindowTable};
+
+/// The size of the decorations around a client window.
+///
+/// Used by [`WindowTable::insert_framed`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Decoration<T = i32> {
+    /// The height of the title bar, which sits above the client.
+    pub title_height: T,
+
+    /// The width of the border around the title bar and the client.
+    pub border: T,
+}
+
+impl<T: Coordinate> Decoration<T> {
+    /// Create a new decoration.
+    pub fn new(title_height: T, border: T) -> Self {
+        Self {
+            title_height,
+            border,
+        }
+    }
+
+    /// Returns the rectangle of the frame that fits around a client rectangle.
+    pub fn frame_rect(&self, client: Rectangle<T>) -> Rectangle<T> {
+        Rectangle {
+            left: client.left.saturating_sub(self.border),
+            top: client
+                .top
+                .saturating_sub(self.title_height)
+                .saturating_sub(self.border),
+            right: client.right.saturating_add(self.border),
+            bottom: client.bottom.saturating_add(self.border),
+        }
+    }
+
+    /// Returns the rectangle of the client inside a frame rectangle.
+    pub fn client_rect(&self, frame: Rectangle<T>) -> Rectangle<T> {
+        self.client_anchors().resolve(frame, frame)
+    }
+
+    /// Returns the rectangle of the title bar inside a frame rectangle.
+    pub fn title_rect(&self, frame: Rectangle<T>) -> Rectangle<T> {
+        self.title_anchors().resolve(frame, frame)
+    }
+
+    /// The anchors that keep the title bar in place.
+    fn title_anchors(&self) -> Anchors<T> {
+        Anchors {
+            left: Some(self.border),
+            top: Some(self.border),
+            right: Some(self.border),
+            height: Some(self.title_height),
+            ..Anchors::default()
+        }
+    }
+
+    /// The anchors that keep the client in place.
+    fn client_anchors(&self) -> Anchors<T> {
+        Anchors {
+            left: Some(self.border),
+            top: Some(self.border.saturating_add(self.title_height)),
+            right: Some(self.border),
+            bottom: Some(self.border),
+            ..Anchors::default()
+        }
+    }
+}
+
+/// The windows that make up a decorated client.
+///
+/// Returned by [`WindowTable::insert_framed`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Frame {
+    /// The frame, which the title bar and the client are stacked on.
+    pub frame: WindowKey,
+
+    /// The title bar.
+    pub title_bar: WindowKey,
+
+    /// The client.
+    pub client: WindowKey,
+}
+
+/// The part of a window that a point lies on, for picking a resize handle.
+///
+/// Returned by [`WindowTable::hit_test_frame`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum HitRegion {
+    /// The point is outside of the window.
+    Outside,
+
+    /// The point is inside of the window, away from its edges and title bar.
+    Interior,
+
+    /// The point is on the title bar of a [framed](WindowTable::insert_framed) window.
+    TitleBar,
+
+    /// The point is on the left edge.
+    EdgeLeft,
+
+    /// The point is on the top edge.
+    EdgeTop,
+
+    /// The point is on the right edge.
+    EdgeRight,
+
+    /// The point is on the bottom edge.
+    EdgeBottom,
+
+    /// The point is on the top-left corner.
+    CornerNW,
+
+    /// The point is on the top-right corner.
+    CornerNE,
+
+    /// The point is on the bottom-left corner.
+    CornerSW,
+
+    /// The point is on the bottom-right corner.
+    CornerSE,
+}
+
+impl<T: Coordinate, const N: usize, const R: usize> WindowTable<T, N, R> {
+    /// Insert a client window along with a frame around it and a title bar above it.
+    ///
+    /// The frame is sized to fit around the client with [`Decoration::frame_rect`]. The title
+    /// bar and the client are [anchored](Self::set_anchors) to the frame, so moving or
+    /// resizing the frame keeps them in place. To resize the client, resize the frame to the
+    /// matching frame rectangle.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the windows could not be inserted, in which case none of them
+    /// are.
+    pub fn insert_framed(
+        &mut self,
+        client: Rectangle<T>,
+        decoration: Decoration<T>,
+    ) -> Result<Frame, InsertError> {
+        let client = client.normalize();
+        let frame_rect = decoration.frame_rect(client);
+
+        let frame = self.insert(frame_rect)?;
+        let title_bar = match self.insert(decoration.title_rect(frame_rect)) {
+            Ok(title_bar) => title_bar,
+            Err(err) => {
+                self.remove(frame);
+                return Err(err);
+            }
+        };
+        let client = match self.insert(client) {
+            Ok(client) => client,
+            Err(err) => {
+                self.remove(title_bar);
+                self.remove(frame);
+                return Err(err);
+            }
+        };
+
+        self.windows[frame.0].attributes_mut().decoration = Some(decoration);
+        self.set_anchors(title_bar, Some(decoration.title_anchors()));
+        self.set_anchors(client, Some(decoration.client_anchors()));
+
+        Ok(Frame {
+            frame,
+            title_bar,
+            client,
+        })
+    }
+
+    /// Find the part of a window that a point lies on.
+    ///
+    /// Points within `handle_size` of an edge, or within the window's
+    /// [border](Self::set_border_width) if that is wider, are on that edge, and points on two
+    /// edges at once are on a corner. Points on the title bar of a frame created by
+    /// [`insert_framed`](Self::insert_framed) are on the title bar. Points in the window's
+    /// [input margin](Self::set_input_margin) are on the nearest edge or corner.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn hit_test_frame(
+        &self,
+        key: WindowKey,
+        point: impl Into<Point<T>>,
+        handle_size: T,
+    ) -> HitRegion {
+        let point = point.into();
+        let window = &self.windows[key.0];
+        let rect = window.rect;
+        if !window.input_rect().contains(point) {
+            return HitRegion::Outside;
+        }
+
+        let handle = coord::max(handle_size, T::from_i32(window.border_width.into()));
+        let left = point.x < rect.left.saturating_add(handle);
+        let top = point.y < rect.top.saturating_add(handle);
+        let right = point.x >= rect.right.saturating_sub(handle);
+        let bottom = point.y >= rect.bottom.saturating_sub(handle);
+
+        match (left, top, right, bottom) {
+            (true, true, _, _) => HitRegion::CornerNW,
+            (_, true, true, _) => HitRegion::CornerNE,
+            (true, _, _, true) => HitRegion::CornerSW,
+            (_, _, true, true) => HitRegion::CornerSE,
+            (true, _, _, _) => HitRegion::EdgeLeft,
+            (_, true, _, _) => HitRegion::EdgeTop,
+            (_, _, true, _) => HitRegion::EdgeRight,
+            (_, _, _, true) => HitRegion::EdgeBottom,
+            _ => match window
+                .attributes()
+                .and_then(|attributes| attributes.decoration)
+            {
+                Some(decoration) if decoration.title_rect(rect).contains(point) => {
+                    HitRegion::TitleBar
+                }
+                _ => HitRegion::Interior,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Decoration, HitRegion};
+    use crate::{InsertError, Rectangle, WindowTable};
+
+    #[test]
+    fn insert_framed() {
+        let mut window_table = WindowTable::new();
+        window_table.insert(Rectangle::new(0, 0, 200, 200)).unwrap();
+
+        let decoration = Decoration::new(20, 5);
+        let frame = window_table
+            .insert_framed(Rectangle::new(20, 40, 120, 140), decoration)
+            .unwrap();
+        assert_eq!(
+            window_table.rect(frame.frame),
+            Rectangle::new(15, 15, 125, 145)
+        );
+        assert_eq!(
+            window_table.rect(frame.title_bar),
+            Rectangle::new(20, 20, 120, 40)
+        );
+        assert_eq!(
+            window_table.rect(frame.client),
+            Rectangle::new(20, 40, 120, 140)
+        );
+
+        window_table.move_window(frame.frame, (25, 25)).unwrap();
+        assert_eq!(
+            window_table.rect(frame.title_bar),
+            Rectangle::new(30, 30, 130, 50)
+        );
+        assert_eq!(
+            window_table.rect(frame.client),
+            Rectangle::new(30, 50, 130, 150)
+        );
+
+        let client = Rectangle::new(30, 50, 180, 190);
+        window_table
+            .resize_window(frame.frame, decoration.frame_rect(client))
+            .unwrap();
+        assert_eq!(
+            window_table.rect(frame.title_bar),
+            Rectangle::new(30, 30, 180, 50)
+        );
+        assert_eq!(window_table.rect(frame.client), client);
+        assert_eq!(window_table.validate(), Ok(()));
+
+        // A title bar with no height can't be inserted, so nothing is.
+        let len = window_table.len();
+        assert!(matches!(
+            window_table.insert_framed(Rectangle::new(0, 20, 10, 30), Decoration::new(0, 1)),
+            Err(InsertError::EmptyRectangle)
+        ));
+        assert_eq!(window_table.len(), len);
+    }
+
+    #[test]
+    fn hit_test_frame() {
+        let mut window_table = WindowTable::new();
+        window_table.insert(Rectangle::new(0, 0, 200, 200)).unwrap();
+
+        let frame = window_table
+            .insert_framed(Rectangle::new(20, 40, 120, 140), Decoration::new(20, 5))
+            .unwrap();
+        let hit = |point: (i32, i32)| window_table.hit_test_frame(frame.frame, point, 3);
+
+        assert_eq!(hit((0, 0)), HitRegion::Outside);
+        assert_eq!(hit((15, 15)), HitRegion::CornerNW);
+        assert_eq!(hit((124, 15)), HitRegion::CornerNE);
+        assert_eq!(hit((15, 144)), HitRegion::CornerSW);
+        assert_eq!(hit((124, 144)), HitRegion::CornerSE);
+        assert_eq!(hit((16, 80)), HitRegion::EdgeLeft);
+        assert_eq!(hit((70, 17)), HitRegion::EdgeTop);
+        assert_eq!(hit((123, 80)), HitRegion::EdgeRight);
+        assert_eq!(hit((70, 142)), HitRegion::EdgeBottom);
+        assert_eq!(hit((70, 30)), HitRegion::TitleBar);
+        assert_eq!(hit((70, 80)), HitRegion::Interior);
+
+        // Windows without decorations have no title bar.
+        assert_eq!(
+            window_table.hit_test_frame(frame.client, (70, 45), 3),
+            HitRegion::Interior
+        );
+    }
+}
@@ -0,0 +1,116 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later OR MPL-2.0
+// This file is a part of `windowless`.
+//
+// `windowless` is free software: you can redistribute it and/or modify it under the terms of
+// either:
+//
+// * GNU Lesser General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+// * Mozilla Public License as published by the Mozilla Foundation, version 2.
+//
+// `windowless` is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+// See the GNU Lesser General Public License or the Mozilla Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License and the Mozilla
+// Public License along with `windowless`. If not, see <https://www.gnu.org/licenses/> or
+// <https://www.mozilla.org/en-US/MPL/2.0/>.
+
+//! Exporting the window graph to Graphviz.
+
+use crate::{Coordinate, WindowKey, WindowTable};
+use alloc::string::String;
+use core::fmt::Write;
+
+impl<T: Coordinate, const N: usize, const R: usize> WindowTable<T, N, R> {
+    /// Describe the graph of windows in the Graphviz DOT language.
+    ///
+    /// Every window is a node, labelled with its rectangle and name, and drawn dashed if it is
+    /// not viewable. Every window has an edge from each of its parents, which is solid for its
+    /// primary parent and dashed for the others. The nodes come in stacking order.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph windows {\n    node [shape=box];\n");
+
+        for &key in &self.order {
+            let window = &self.windows[key.0];
+            let rect = window.rect;
+            let _ = write!(
+                dot,
+                "    {} [label=\"{:?}, {:?}, {:?}, {:?}",
+                node(key),
+                rect.left,
+                rect.top,
+                rect.right,
+                rect.bottom
+            );
+            if let Some(name) = self.name(key) {
+                dot.push_str("\\n");
+                for c in name.chars() {
+                    if c == '"' || c == '\\' {
+                        dot.push('\\');
+                    }
+                    dot.push(c);
+                }
+            }
+            dot.push('"');
+            if !self.is_viewable(key) {
+                dot.push_str(", style=dashed");
+            }
+            dot.push_str("];\n");
+        }
+
+        for &key in &self.order {
+            for (i, &parent) in self.windows[key.0].parents.iter().enumerate() {
+                let _ = write!(dot, "    {} -> {}", node(parent), node(key));
+                if i > 0 {
+                    dot.push_str(" [style=dashed]");
+                }
+                dot.push_str(";\n");
+            }
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+/// The DOT identifier of a window.
+fn node(key: WindowKey) -> String {
+    let mut node = String::new();
+    let _ = write!(node, "w{}", key.as_ffi());
+    node
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Rectangle, WindowTable};
+    use alloc::format;
+
+    #[test]
+    fn to_dot() {
+        let mut window_table = WindowTable::new();
+
+        let root = window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let a = window_table.insert(Rectangle::new(10, 10, 40, 40)).unwrap();
+        let b = window_table.insert(Rectangle::new(30, 30, 60, 60)).unwrap();
+        window_table.set_name(a, "say \"hi\"");
+        window_table.unmap(b);
+
+        let (root, a, b) = (root.as_ffi(), a.as_ffi(), b.as_ffi());
+        assert_eq!(
+            window_table.to_dot(),
+            format!(
+                "digraph windows {{
+    node [shape=box];
+    w{root} [label=\"0, 0, 100, 100\"];
+    w{a} [label=\"10, 10, 40, 40\\nsay \\\"hi\\\"\"];
+    w{b} [label=\"30, 30, 60, 60\", style=dashed];
+    w{root} -> w{a};
+    w{root} -> w{b};
+    w{a} -> w{b} [style=dashed];
+}}
+"
+            )
+        );
+    }
+}
@@ -0,0 +1,255 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later OR MPL-2.0
+// This file is a part of `windowless`.
+//
+// `windowless` is free software: you can redistribute it and/or modify it under the terms of
+// either:
+//
+// * GNU Lesser General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+// * Mozilla Public License as published by the Mozilla Foundation, version 2.
+//
+// `windowless` is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+// See the GNU Lesser General Public License or the Mozilla Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License and the Mozilla
+// Public License along with `windowless`. If not, see <https://www.gnu.org/licenses/> or
+// <https://www.mozilla.org/en-US/MPL/2.0/>.
+
+//! Tracking the target of a drag and drop.
+
+use crate::{Coordinate, Point, WindowKey, WindowTable};
+use smallvec::SmallVec;
+
+/// The state of a drag and drop in progress.
+///
+/// Feed the position of the pointer to [`update`](Self::update) as it moves, then finish the
+/// drag with [`drop`](Self::drop) or [`cancel`](Self::cancel). Only windows that
+/// [accept drops](WindowTable::set_accepts_drops) become drop targets.
+#[derive(Debug, Default)]
+pub struct DragState<T = i32> {
+    /// The last known position of the pointer.
+    position: Point<T>,
+
+    /// The window that the drag is over.
+    target: Option<WindowKey>,
+}
+
+/// An event generated by a drag moving between drop targets.
+///
+/// Positions are relative to the top-left corner of the window.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum DragEvent<T = i32> {
+    /// The drag has entered a drop target.
+    Enter {
+        /// The drop target.
+        window: WindowKey,
+
+        /// The position of the pointer.
+        position: Point<T>,
+    },
+
+    /// The drag has moved within a drop target.
+    Motion {
+        /// The drop target.
+        window: WindowKey,
+
+        /// The position of the pointer.
+        position: Point<T>,
+    },
+
+    /// The drag has left a drop target.
+    Leave(WindowKey),
+
+    /// The drag was dropped on a drop target.
+    Drop {
+        /// The drop target.
+        window: WindowKey,
+
+        /// The position of the pointer.
+        position: Point<T>,
+    },
+}
+
+impl<T: Coordinate> DragState<T> {
+    /// Creates a new drag state.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the last known position of the pointer.
+    pub fn position(&self) -> Point<T> {
+        self.position
+    }
+
+    /// Returns the window that the drag is over.
+    pub fn target(&self) -> Option<WindowKey> {
+        self.target
+    }
+
+    /// Move the drag to a new position.
+    ///
+    /// Returns a `Leave` event for the old drop target and an `Enter` event for the new one if
+    /// the target changed, or a `Motion` event if it did not.
+    pub fn update<const N: usize, const R: usize>(
+        &mut self,
+        table: &WindowTable<T, N, R>,
+        position: impl Into<Point<T>>,
+    ) -> SmallVec<[DragEvent<T>; 2]> {
+        let position = position.into();
+        self.position = position;
+
+        let target = table.drop_target(position);
+        let mut events = SmallVec::new();
+
+        if target == self.target {
+            if let Some(window) = target {
+                events.push(DragEvent::Motion {
+                    window,
+                    position: local(table, window, position),
+                });
+            }
+            return events;
+        }
+
+        if let Some(old) = self.target {
+            events.push(DragEvent::Leave(old));
+        }
+        if let Some(window) = target {
+            events.push(DragEvent::Enter {
+                window,
+                position: local(table, window, position),
+            });
+        }
+
+        self.target = target;
+        events
+    }
+
+    /// Drop onto the current drop target, ending the drag.
+    ///
+    /// Returns `None` if the drag is not over a drop target, or if the target stopped being
+    /// viewable.
+    pub fn drop<const N: usize, const R: usize>(
+        &mut self,
+        table: &WindowTable<T, N, R>,
+    ) -> Option<DragEvent<T>> {
+        let window = self.target.take().filter(|&key| table.is_viewable(key))?;
+        Some(DragEvent::Drop {
+            window,
+            position: local(table, window, self.position),
+        })
+    }
+
+    /// Cancel the drag, returning a `Leave` event for the drop target it was over.
+    pub fn cancel(&mut self) -> Option<DragEvent<T>> {
+        self.target.take().map(DragEvent::Leave)
+    }
+}
+
+impl<T: Coordinate, const N: usize, const R: usize> WindowTable<T, N, R> {
+    /// Set whether a window accepts drops.
+    ///
+    /// Windows don't accept drops by default.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn set_accepts_drops(&mut self, key: WindowKey, accepts: bool) {
+        self.windows[key.0].accepts_drops = accepts;
+    }
+
+    /// Tell if a window accepts drops.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn accepts_drops(&self, key: WindowKey) -> bool {
+        self.windows[key.0].accepts_drops
+    }
+
+    /// Find the window that something dropped at a position would be dropped on.
+    ///
+    /// This is the topmost window that accepts input at the position, or the closest of its
+    /// ancestors that accepts drops.
+    pub fn drop_target(&self, position: impl Into<Point<T>>) -> Option<WindowKey> {
+        let hit = self.window_at(position)?;
+        self.ancestors(hit)
+            .find(|ancestor| self.windows[ancestor.0].accepts_drops)
+    }
+}
+
+/// Convert a position to be relative to a window's top-left corner.
+fn local<T: Coordinate, const N: usize, const R: usize>(
+    table: &WindowTable<T, N, R>,
+    key: WindowKey,
+    position: Point<T>,
+) -> Point<T> {
+    let rect = table.rect(key);
+    Point::new(
+        position.x.saturating_sub(rect.left),
+        position.y.saturating_sub(rect.top),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DragEvent, DragState};
+    use crate::{Point, Rectangle, WindowTable};
+
+    #[test]
+    fn drag_and_drop() {
+        let mut window_table = WindowTable::new();
+
+        window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let folder = window_table.insert(Rectangle::new(10, 10, 50, 50)).unwrap();
+        let icon = window_table.insert(Rectangle::new(20, 20, 30, 30)).unwrap();
+        let trash = window_table.insert(Rectangle::new(60, 60, 90, 90)).unwrap();
+        window_table.set_accepts_drops(folder, true);
+        window_table.set_accepts_drops(trash, true);
+
+        let mut drag = DragState::new();
+        assert!(drag.update(&window_table, (5, 5)).is_empty());
+
+        // Dragging over the icon targets the folder it is in.
+        assert_eq!(
+            &drag.update(&window_table, (25, 25))[..],
+            &[DragEvent::Enter {
+                window: folder,
+                position: Point::new(15, 15)
+            }]
+        );
+        assert_eq!(window_table.drop_target((25, 25)), Some(folder));
+        assert!(!window_table.accepts_drops(icon));
+        assert_eq!(
+            &drag.update(&window_table, (40, 40))[..],
+            &[DragEvent::Motion {
+                window: folder,
+                position: Point::new(30, 30)
+            }]
+        );
+        assert_eq!(
+            &drag.update(&window_table, (70, 70))[..],
+            &[
+                DragEvent::Leave(folder),
+                DragEvent::Enter {
+                    window: trash,
+                    position: Point::new(10, 10)
+                }
+            ]
+        );
+
+        assert_eq!(
+            drag.drop(&window_table),
+            Some(DragEvent::Drop {
+                window: trash,
+                position: Point::new(10, 10)
+            })
+        );
+        assert_eq!(drag.target(), None);
+
+        drag.update(&window_table, (25, 25));
+        assert_eq!(drag.cancel(), Some(DragEvent::Leave(folder)));
+        assert_eq!(drag.drop(&window_table), None);
+    }
+}
@@ -0,0 +1,178 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later OR MPL-2.0
+// This file is a part of `windowless`.
+//
+// `windowless` is free software: you can redistribute it and/or modify it under the terms of
+// either:
+//
+// * GNU Lesser General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+// * Mozilla Public License as published by the Mozilla Foundation, version 2.
+//
+// `windowless` is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+// See the GNU Lesser General Public License or the Mozilla Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License and the Mozilla
+// Public License along with `windowless`. If not, see <https://www.gnu.org/licenses/> or
+// <https://www.mozilla.org/en-US/MPL/2.0/>.
+
+//! Drawing the windows as text or SVG, for debugging layouts.
+
+use crate::{Coordinate, Point, WindowTable};
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write;
+
+/// The letters used for windows in ASCII dumps, in stacking order above the root.
+const LETTERS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+
+/// The fill colors used for windows in SVG dumps, in stacking order.
+const COLORS: &[&str] = &[
+    "#e6194b", "#3cb44b", "#ffe119", "#4363d8", "#f58231", "#911eb4", "#46f0f0", "#f032e6",
+];
+
+impl<T: Coordinate, const N: usize, const R: usize> WindowTable<T, N, R> {
+    /// Draw the viewable windows as a grid of characters.
+    ///
+    /// The root window is divided into `columns` by `rows` cells, and each cell shows the
+    /// topmost viewable window at its center. The root window is drawn as `.`, and the windows
+    /// above it get the letters `A` to `Z`, `a` to `z` and `0` to `9` in stacking order, with
+    /// `#` for any windows after that. Each row ends with a newline.
+    pub fn to_ascii(&self, columns: usize, rows: usize) -> String {
+        let root = match self.root {
+            Some(root) => self.windows[root.0].rect,
+            None => return String::new(),
+        };
+
+        // Number the viewable windows, from the top of the stack down.
+        let windows = self
+            .paint_order()
+            .enumerate()
+            .map(|(i, (key, rect))| {
+                let letter = match i.checked_sub(1) {
+                    None => b'.',
+                    Some(i) => LETTERS.get(i).copied().unwrap_or(b'#'),
+                };
+                (Some(key) == self.root, rect, letter as char)
+            })
+            .collect::<Vec<_>>();
+
+        let (width, height) = (root.width().to_f64(), root.height().to_f64());
+        let mut ascii = String::with_capacity((columns + 1) * rows);
+        for y in 0..rows {
+            for x in 0..columns {
+                let point = Point::new(
+                    T::from_f64(root.left.to_f64() + (x as f64 + 0.5) * width / columns as f64),
+                    T::from_f64(root.top.to_f64() + (y as f64 + 0.5) * height / rows as f64),
+                );
+                let cell = windows
+                    .iter()
+                    .rev()
+                    .find(|(is_root, rect, _)| *is_root || rect.contains(point))
+                    .map_or(' ', |&(_, _, letter)| letter);
+                ascii.push(cell);
+            }
+            ascii.push('\n');
+        }
+
+        ascii
+    }
+
+    /// Draw the viewable windows as an SVG image.
+    ///
+    /// Each window is a translucent rectangle, in paint order, with a title showing its key
+    /// and name. The image covers the root window.
+    pub fn to_svg(&self) -> String {
+        let root = match self.root {
+            Some(root) => self.windows[root.0].rect,
+            None => return String::from("<svg xmlns=\"http://www.w3.org/2000/svg\"/>\n"),
+        };
+
+        let mut svg = String::new();
+        let _ = writeln!(
+            svg,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"{:?} {:?} {:?} {:?}\">",
+            root.left,
+            root.top,
+            root.width(),
+            root.height()
+        );
+
+        for (i, (key, rect)) in self.paint_order().enumerate() {
+            let _ = write!(
+                svg,
+                "  <rect x=\"{:?}\" y=\"{:?}\" width=\"{:?}\" height=\"{:?}\" fill=\"{}\" \
+                 fill-opacity=\"0.5\" stroke=\"black\"><title>{}",
+                rect.left,
+                rect.top,
+                rect.width(),
+                rect.height(),
+                COLORS[i % COLORS.len()],
+                key.as_ffi()
+            );
+            if let Some(name) = self.name(key) {
+                svg.push(' ');
+                for c in name.chars() {
+                    match c {
+                        '<' => svg.push_str("&lt;"),
+                        '>' => svg.push_str("&gt;"),
+                        '&' => svg.push_str("&amp;"),
+                        c => svg.push(c),
+                    }
+                }
+            }
+            svg.push_str("</title></rect>\n");
+        }
+
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Rectangle, WindowTable};
+    use alloc::format;
+
+    #[test]
+    fn to_ascii() {
+        let mut window_table = WindowTable::new();
+        assert_eq!(window_table.to_ascii(4, 4), "");
+
+        window_table.insert(Rectangle::new(0, 0, 80, 40)).unwrap();
+        window_table.insert(Rectangle::new(0, 0, 40, 20)).unwrap();
+        window_table.insert(Rectangle::new(30, 10, 60, 30)).unwrap();
+
+        assert_eq!(
+            window_table.to_ascii(8, 4),
+            "AAAA....\n\
+             AAABBB..\n\
+             ...BBB..\n\
+             ........\n"
+        );
+    }
+
+    #[test]
+    fn to_svg() {
+        let mut window_table = WindowTable::new();
+
+        let root = window_table.insert(Rectangle::new(0, 0, 80, 40)).unwrap();
+        let a = window_table.insert(Rectangle::new(10, 10, 30, 20)).unwrap();
+        window_table.set_name(a, "<a>");
+
+        assert_eq!(
+            window_table.to_svg(),
+            format!(
+                "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 80 40\">
+  <rect x=\"0\" y=\"0\" width=\"80\" height=\"40\" fill=\"#e6194b\" fill-opacity=\"0.5\" \
+                 stroke=\"black\"><title>{}</title></rect>
+  <rect x=\"10\" y=\"10\" width=\"20\" height=\"10\" fill=\"#3cb44b\" fill-opacity=\"0.5\" \
+                 stroke=\"black\"><title>{} &lt;a&gt;</title></rect>
+</svg>
+",
+                root.as_ffi(),
+                a.as_ffi()
+            )
+        );
+    }
+}
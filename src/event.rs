@@ -0,0 +1,220 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later OR MPL-2.0
+// This file is a part of `windowless`.
+//
+// `windowless` is free software: you can redistribute it and/or modify it under the terms of
+// either:
+//
+// * GNU Lesser General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+// * Mozilla Public License as published by the Mozilla Foundation, version 2.
+//
+// `windowless` is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+// See the GNU Lesser General Public License or the Mozilla Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License and the Mozilla
+// Public License along with `windowless`. If not, see <https://www.gnu.org/licenses/> or
+// <https://www.mozilla.org/en-US/MPL/2.0/>.
+
+//! Selecting and routing input events.
+
+use crate::{Coordinate, Point, WindowKey, WindowTable};
+use core::ops;
+
+/// The kinds of input events that a window accepts.
+///
+/// Events that a window does not accept are passed on to its ancestors. Every window accepts
+/// every event by default.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct EventMask(u32);
+
+impl EventMask {
+    /// Button presses and releases.
+    pub const BUTTON: Self = Self(1 << 0);
+
+    /// Pointer motion.
+    pub const MOTION: Self = Self(1 << 1);
+
+    /// The pointer entering and leaving the window.
+    pub const CROSSING: Self = Self(1 << 2);
+
+    /// Scrolling.
+    pub const SCROLL: Self = Self(1 << 3);
+
+    /// Key presses and releases.
+    pub const KEY: Self = Self(1 << 4);
+
+    /// Accept no events.
+    pub const fn empty() -> Self {
+        Self(0)
+    }
+
+    /// Accept every event.
+    pub const fn all() -> Self {
+        Self(Self::BUTTON.0 | Self::MOTION.0 | Self::CROSSING.0 | Self::SCROLL.0 | Self::KEY.0)
+    }
+
+    /// Tell if this mask accepts no events.
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Tell if this mask accepts every event in another mask.
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Accept the events in another mask as well.
+    pub fn insert(&mut self, other: Self) {
+        self.0 |= other.0;
+    }
+
+    /// Stop accepting the events in another mask.
+    pub fn remove(&mut self, other: Self) {
+        self.0 &= !other.0;
+    }
+}
+
+impl Default for EventMask {
+    fn default() -> Self {
+        Self::all()
+    }
+}
+
+impl ops::BitOr for EventMask {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl ops::BitOrAssign for EventMask {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.insert(rhs);
+    }
+}
+
+impl ops::BitAnd for EventMask {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self {
+        Self(self.0 & rhs.0)
+    }
+}
+
+/// A scroll, routed to the window that handles it.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct ScrollEvent<T = i32> {
+    /// The window that receives the event.
+    pub window: WindowKey,
+
+    /// The position of the pointer, relative to the window's top-left corner.
+    pub position: Point<T>,
+
+    /// The distance scrolled along each axis.
+    pub delta: Point<T>,
+}
+
+impl<T: Coordinate, const N: usize, const R: usize> WindowTable<T, N, R> {
+    /// Set the kinds of input events that a window accepts.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn set_event_mask(&mut self, key: WindowKey, mask: EventMask) {
+        self.windows[key.0].event_mask = mask;
+    }
+
+    /// Returns the kinds of input events that a window accepts.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn event_mask(&self, key: WindowKey) -> EventMask {
+        self.windows[key.0].event_mask
+    }
+
+    /// Find the window that accepts some kind of event at a position.
+    ///
+    /// This starts at the topmost window that accepts input at the position, and bubbles up
+    /// through its ancestors until one of them accepts the event.
+    pub fn route_event(&self, position: impl Into<Point<T>>, mask: EventMask) -> Option<WindowKey> {
+        let hit = self.window_at(position)?;
+        self.ancestors(hit)
+            .find(|ancestor| self.windows[ancestor.0].event_mask.contains(mask))
+    }
+
+    /// Route a scroll at a position to the window that handles it.
+    ///
+    /// The scroll goes to the topmost window under the position whose event mask accepts
+    /// [`EventMask::SCROLL`], falling back to its ancestors, so that nested scrollable areas
+    /// pass scrolls they don't handle to the areas around them. Returns `None` if no window
+    /// accepts the scroll.
+    pub fn route_scroll(
+        &self,
+        position: impl Into<Point<T>>,
+        delta: impl Into<Point<T>>,
+    ) -> Option<ScrollEvent<T>> {
+        let position = position.into();
+        let window = self.route_event(position, EventMask::SCROLL)?;
+        let rect = self.windows[window.0].rect;
+
+        Some(ScrollEvent {
+            window,
+            position: Point::new(
+                position.x.saturating_sub(rect.left),
+                position.y.saturating_sub(rect.top),
+            ),
+            delta: delta.into(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EventMask, ScrollEvent};
+    use crate::{Point, Rectangle, WindowTable};
+
+    #[test]
+    fn mask() {
+        let mut mask = EventMask::BUTTON | EventMask::SCROLL;
+        assert!(mask.contains(EventMask::SCROLL));
+        assert!(!mask.contains(EventMask::KEY));
+
+        mask.remove(EventMask::SCROLL);
+        assert_eq!(mask, EventMask::BUTTON);
+        assert!(EventMask::all().contains(mask));
+        assert!((mask & EventMask::KEY).is_empty());
+    }
+
+    #[test]
+    fn route_scroll() {
+        let mut window_table = WindowTable::new();
+
+        let root = window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let list = window_table.insert(Rectangle::new(10, 10, 90, 90)).unwrap();
+        let item = window_table.insert(Rectangle::new(20, 20, 80, 40)).unwrap();
+        window_table.set_event_mask(item, EventMask::BUTTON);
+
+        // The item doesn't scroll, so the list around it does.
+        assert_eq!(
+            window_table.route_scroll((30, 30), (0, -3)),
+            Some(ScrollEvent {
+                window: list,
+                position: Point::new(20, 20),
+                delta: Point::new(0, -3),
+            })
+        );
+
+        window_table.set_event_mask(list, EventMask::empty());
+        assert_eq!(
+            window_table.route_scroll((30, 30), (0, -3)).unwrap().window,
+            root
+        );
+
+        window_table.set_event_mask(root, EventMask::KEY);
+        assert_eq!(window_table.route_scroll((30, 30), (0, -3)), None);
+        assert_eq!(window_table.route_scroll((200, 200), (0, -3)), None);
+    }
+}
@@ -0,0 +1,222 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later OR MPL-2.0
+// This file is a part of `windowless`.
+//
+// `windowless` is free software: you can redistribute it and/or modify it under the terms of
+// either:
+//
+// * GNU Lesser General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+// * Mozilla Public License as published by the Mozilla Foundation, version 2.
+//
+// `windowless` is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+// See the GNU Lesser General Public License or the Mozilla Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License and the Mozilla
+// Public License along with `windowless`. If not, see <https://www.gnu.org/licenses/> or
+// <https://www.mozilla.org/en-US/MPL/2.0/>.
+
+//! Extents that a window is painted in or accepts input in beyond its rectangle.
+
+use crate::{Coordinate, Rectangle, WindowKey, WindowTable};
+
+/// Distances that a window reaches beyond each edge of its rectangle.
+///
+/// Set with [`WindowTable::set_visual_extents`] and [`WindowTable::set_input_margin`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Extents<T = i32> {
+    /// The distance beyond the left edge.
+    pub left: T,
+
+    /// The distance beyond the top edge.
+    pub top: T,
+
+    /// The distance beyond the right edge.
+    pub right: T,
+
+    /// The distance beyond the bottom edge.
+    pub bottom: T,
+}
+
+impl<T: Coordinate> Extents<T> {
+    /// Create new extents.
+    pub fn new(left: T, top: T, right: T, bottom: T) -> Self {
+        Self {
+            left,
+            top,
+            right,
+            bottom,
+        }
+    }
+
+    /// Create extents that reach the same distance beyond every edge.
+    pub fn uniform(distance: T) -> Self {
+        Self::new(distance, distance, distance, distance)
+    }
+
+    /// Grow a rectangle by these extents.
+    pub fn outset(&self, rect: Rectangle<T>) -> Rectangle<T> {
+        Rectangle {
+            left: rect.left.saturating_sub(self.left),
+            top: rect.top.saturating_sub(self.top),
+            right: rect.right.saturating_add(self.right),
+            bottom: rect.bottom.saturating_add(self.bottom),
+        }
+    }
+}
+
+impl<T: Coordinate, const N: usize, const R: usize> WindowTable<T, N, R> {
+    /// Set how far a window is painted beyond its rectangle, for drop shadows and the like.
+    ///
+    /// The visual extents are damaged and [composited](Self::composite) along with the window,
+    /// but they don't hide the windows beneath it, don't accept input, and aren't used to link
+    /// the window to the windows beneath it. Passing `None` paints only the rectangle, which is
+    /// the default.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn set_visual_extents(&mut self, key: WindowKey, extents: Option<Extents<T>>) {
+        let old = self.windows[key.0].visual_rect();
+        self.windows[key.0].attributes_mut().visual_extents = extents;
+
+        let new = self.windows[key.0].visual_rect();
+        if old != new && self.is_viewable(key) {
+            self.add_damage_rect(old);
+            self.add_damage_rect(new);
+        }
+    }
+
+    /// Returns how far a window is painted beyond its rectangle.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn visual_extents(&self, key: WindowKey) -> Option<Extents<T>> {
+        self.windows[key.0]
+            .attributes()
+            .and_then(|attributes| attributes.visual_extents)
+    }
+
+    /// Returns the rectangle that a window is painted in, including its visual extents.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn visual_rect(&self, key: WindowKey) -> Rectangle<T> {
+        self.windows[key.0].visual_rect()
+    }
+
+    /// Set how far a window accepts input beyond its rectangle, for easier grabbing of its edges.
+    ///
+    /// Points in the margin hit the window in [`window_at`](Self::window_at) regardless of its
+    /// shape and input region, and [`hit_test_frame`](Self::hit_test_frame) puts them on the
+    /// nearest edge or corner. Windows stacked above still take input over the margin. Passing
+    /// `None` accepts input only within the rectangle, which is the default.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn set_input_margin(&mut self, key: WindowKey, margin: Option<Extents<T>>) {
+        self.windows[key.0].attributes_mut().input_margin = margin;
+
+        if margin.is_some() {
+            self.input_margins.insert(key);
+        } else {
+            self.input_margins.remove(&key);
+        }
+    }
+
+    /// Returns how far a window accepts input beyond its rectangle.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn input_margin(&self, key: WindowKey) -> Option<Extents<T>> {
+        self.windows[key.0]
+            .attributes()
+            .and_then(|attributes| attributes.input_margin)
+    }
+
+    /// Returns the rectangle that a window accepts input in, including its input margin.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn input_rect(&self, key: WindowKey) -> Rectangle<T> {
+        self.windows[key.0].input_rect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Extents;
+    use crate::{HitRegion, Rectangle, WindowTable};
+
+    #[test]
+    fn visual_extents() {
+        let mut window_table = WindowTable::new();
+
+        let root = window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let a = window_table.insert(Rectangle::new(10, 10, 30, 30)).unwrap();
+        let b = window_table.insert(Rectangle::new(40, 40, 60, 60)).unwrap();
+        window_table.take_damage();
+
+        window_table.set_visual_extents(b, Some(Extents::new(2, 2, 12, 12)));
+        assert_eq!(window_table.visual_rect(b), Rectangle::new(38, 38, 72, 72));
+        assert_eq!(
+            window_table.take_damage().bounds(),
+            Some(Rectangle::new(38, 38, 72, 72))
+        );
+
+        // The shadow is damaged when the window moves, but doesn't link it to other windows.
+        window_table.move_window(b, (20, 40)).unwrap();
+        assert_eq!(window_table.parent(b), Some(root));
+        assert_eq!(
+            window_table.take_damage().bounds(),
+            Some(Rectangle::new(18, 38, 72, 72))
+        );
+
+        // The shadow is painted, but doesn't hide the window beneath it.
+        window_table.move_window(b, (32, 10)).unwrap();
+        assert_eq!(window_table.visible_region(a).area(), 400);
+        let mut painted = 0;
+        window_table.composite(|key, rects| {
+            if key == b {
+                painted = rects.iter().map(|rect| rect.width() * rect.height()).sum();
+            }
+        });
+        assert_eq!(painted, 34 * 34);
+    }
+
+    #[test]
+    fn input_margin() {
+        let mut window_table = WindowTable::new();
+
+        let root = window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let a = window_table.insert(Rectangle::new(10, 10, 30, 30)).unwrap();
+        let b = window_table.insert(Rectangle::new(40, 40, 60, 60)).unwrap();
+
+        window_table.set_input_margin(b, Some(Extents::uniform(5)));
+        assert_eq!(window_table.input_rect(b), Rectangle::new(35, 35, 65, 65));
+        assert_eq!(window_table.window_at((37, 50)), Some(b));
+        assert_eq!(window_table.window_at((33, 50)), Some(root));
+        assert_eq!(
+            window_table.hit_test_frame(b, (37, 50), 2),
+            HitRegion::EdgeLeft
+        );
+        assert_eq!(
+            window_table.hit_test_frame(b, (62, 62), 2),
+            HitRegion::CornerSE
+        );
+
+        // Windows above take input over the margin.
+        window_table.set_input_margin(a, Some(Extents::uniform(10)));
+        window_table.raise(a);
+        assert_eq!(window_table.window_at((37, 37)), Some(a));
+
+        window_table.remove(a);
+        window_table.set_input_margin(b, None);
+        assert_eq!(window_table.window_at((37, 50)), Some(root));
+    }
+}
@@ -0,0 +1,249 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later OR MPL-2.0
+// This file is a part of `windowless`.
+//
+// `windowless` is free software: you can redistribute it and/or modify it under the terms of
+// either:
+//
+// * GNU Lesser General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+// * Mozilla Public License as published by the Mozilla Foundation, version 2.
+//
+// `windowless` is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+// See the GNU Lesser General Public License or the Mozilla Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License and the Mozilla
+// Public License along with `windowless`. If not, see <https://www.gnu.org/licenses/> or
+// <https://www.mozilla.org/en-US/MPL/2.0/>.
+
+//! A C interface to the window table.
+//!
+//! Tables are created with [`windowless_table_new`] and destroyed with
+//! [`windowless_table_free`]. Windows are named by the `u64` returned by
+//! [`WindowKey::as_ffi`], and zero is never a valid window, so it is used to signal failure.
+//! Every function that takes a table pointer requires it to be null, or to come from
+//! `windowless_table_new` and not to have been freed. Null pointers are always checked: a
+//! function given one does nothing and returns its failure value.
+
+use crate::{Rectangle, WindowKey, WindowTable};
+use alloc::boxed::Box;
+use core::ffi::c_void;
+
+/// A rectangle, as passed across the C interface.
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct WindowlessRect {
+    /// The left edge.
+    pub left: i32,
+
+    /// The top edge.
+    pub top: i32,
+
+    /// The right edge, exclusive.
+    pub right: i32,
+
+    /// The bottom edge, exclusive.
+    pub bottom: i32,
+}
+
+impl From<Rectangle> for WindowlessRect {
+    fn from(rect: Rectangle) -> Self {
+        Self {
+            left: rect.left,
+            top: rect.top,
+            right: rect.right,
+            bottom: rect.bottom,
+        }
+    }
+}
+
+impl From<WindowlessRect> for Rectangle {
+    fn from(rect: WindowlessRect) -> Self {
+        Rectangle::new(rect.left, rect.top, rect.right, rect.bottom)
+    }
+}
+
+/// Create a new, empty table.
+///
+/// The table must be destroyed with [`windowless_table_free`].
+#[no_mangle]
+pub extern "C" fn windowless_table_new() -> *mut WindowTable {
+    Box::into_raw(Box::new(WindowTable::new()))
+}
+
+/// Destroy a table.
+///
+/// # Safety
+///
+/// The table must be valid, as described in the [module documentation](self), and must not
+/// be used again.
+#[no_mangle]
+pub unsafe extern "C" fn windowless_table_free(table: *mut WindowTable) {
+    if !table.is_null() {
+        drop(Box::from_raw(table));
+    }
+}
+
+/// Insert a window on top of the stack, returning its key, or zero if it could not be inserted.
+///
+/// # Safety
+///
+/// The table must be valid, as described in the [module documentation](self).
+#[no_mangle]
+pub unsafe extern "C" fn windowless_insert(table: *mut WindowTable, rect: WindowlessRect) -> u64 {
+    let table = match table.as_mut() {
+        Some(table) => table,
+        None => return 0,
+    };
+
+    match table.insert(rect.into()) {
+        Ok(key) => key.as_ffi(),
+        Err(_) => 0,
+    }
+}
+
+/// Remove a window, returning `false` if the window does not exist.
+///
+/// The window's children are relinked to the windows beneath them.
+///
+/// # Safety
+///
+/// The table must be valid, as described in the [module documentation](self).
+#[no_mangle]
+pub unsafe extern "C" fn windowless_remove(table: *mut WindowTable, key: u64) -> bool {
+    let table = match table.as_mut() {
+        Some(table) => table,
+        None => return false,
+    };
+    let key = WindowKey::from_ffi(key);
+    if !table.contains_key(key) {
+        return false;
+    }
+
+    table.remove(key);
+    true
+}
+
+/// Get the rectangle of a window, returning `false` if the window does not exist.
+///
+/// # Safety
+///
+/// The table must be valid, as described in the [module documentation](self), and `rect`
+/// must be null or point to writable memory for a rectangle. Nothing is written through a
+/// null `rect`, and `false` is returned.
+#[no_mangle]
+pub unsafe extern "C" fn windowless_rect(
+    table: *const WindowTable,
+    key: u64,
+    rect: *mut WindowlessRect,
+) -> bool {
+    let (table, rect) = match (table.as_ref(), rect.as_mut()) {
+        (Some(table), Some(rect)) => (table, rect),
+        _ => return false,
+    };
+    let key = WindowKey::from_ffi(key);
+    if !table.contains_key(key) {
+        return false;
+    }
+
+    *rect = table.rect(key).into();
+    true
+}
+
+/// Get the topmost window that accepts input at a position, or zero if there is none.
+///
+/// # Safety
+///
+/// The table must be valid, as described in the [module documentation](self).
+#[no_mangle]
+pub unsafe extern "C" fn windowless_hit_test(table: *const WindowTable, x: i32, y: i32) -> u64 {
+    table
+        .as_ref()
+        .and_then(|table| table.window_at((x, y)))
+        .map_or(0, WindowKey::as_ffi)
+}
+
+/// Call a function with the key and rectangle of every viewable window, in paint order.
+///
+/// `user_data` is passed through to the callback untouched.
+///
+/// # Safety
+///
+/// The table must be valid, as described in the [module documentation](self), and the
+/// callback must be safe to call with `user_data`.
+#[no_mangle]
+pub unsafe extern "C" fn windowless_for_each_rect(
+    table: *const WindowTable,
+    callback: extern "C" fn(key: u64, rect: WindowlessRect, user_data: *mut c_void),
+    user_data: *mut c_void,
+) {
+    let table = match table.as_ref() {
+        Some(table) => table,
+        None => return,
+    };
+
+    for (key, rect) in table.paint_order() {
+        callback(key.as_ffi(), rect.into(), user_data);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+    use core::ptr;
+
+    extern "C" fn collect(key: u64, rect: WindowlessRect, user_data: *mut c_void) {
+        let rects = unsafe { &mut *(user_data as *mut Vec<(u64, WindowlessRect)>) };
+        rects.push((key, rect));
+    }
+
+    #[test]
+    fn c_interface() {
+        let rect = |left, top, right, bottom| WindowlessRect {
+            left,
+            top,
+            right,
+            bottom,
+        };
+
+        unsafe {
+            let table = windowless_table_new();
+            let root = windowless_insert(table, rect(0, 0, 100, 100));
+            let a = windowless_insert(table, rect(10, 10, 40, 40));
+            assert_ne!(root, 0);
+            assert_ne!(a, 0);
+            assert_eq!(windowless_insert(table, rect(0, 0, 0, 0)), 0);
+
+            assert_eq!(windowless_hit_test(table, 20, 20), a);
+            assert_eq!(windowless_hit_test(table, 200, 200), 0);
+
+            let mut out = rect(0, 0, 0, 0);
+            assert!(windowless_rect(table, a, &mut out));
+            assert_eq!(out, rect(10, 10, 40, 40));
+
+            let mut rects = Vec::<(u64, WindowlessRect)>::new();
+            windowless_for_each_rect(table, collect, &mut rects as *mut _ as *mut c_void);
+            assert_eq!(
+                rects,
+                [(root, rect(0, 0, 100, 100)), (a, rect(10, 10, 40, 40))]
+            );
+
+            assert!(windowless_remove(table, a));
+            assert!(!windowless_remove(table, a));
+            assert!(!windowless_rect(table, a, &mut out));
+            assert!(!windowless_rect(table, root, ptr::null_mut()));
+
+            windowless_table_free(table);
+
+            let table = ptr::null_mut::<WindowTable>();
+            assert_eq!(windowless_insert(table, rect(0, 0, 100, 100)), 0);
+            assert!(!windowless_remove(table, root));
+            assert!(!windowless_rect(table, root, &mut out));
+            assert_eq!(windowless_hit_test(table, 20, 20), 0);
+            windowless_for_each_rect(table, collect, &mut rects as *mut _ as *mut c_void);
+            assert_eq!(rects.len(), 2);
+            windowless_table_free(table);
+        }
+    }
+}
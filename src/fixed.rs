@@ -0,0 +1,261 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later OR MPL-2.0
+// This file is a part of `windowless`.
+//
+// `windowless` is free software: you can redistribute it and/or modify it under the terms of
+// either:
+//
+// * GNU Lesser General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+// * Mozilla Public License as published by the Mozilla Foundation, version 2.
+//
+// `windowless` is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+// See the GNU Lesser General Public License or the Mozilla Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License and the Mozilla
+// Public License along with `windowless`. If not, see <https://www.gnu.org/licenses/> or
+// <https://www.mozilla.org/en-US/MPL/2.0/>.
+
+//! Fixed-point coordinates, for targets without floating-point hardware.
+
+use crate::Coordinate;
+use core::fmt;
+use core::ops::{Add, Div, Mul, Neg, Sub};
+
+/// The number of fractional bits in fixed-point coordinates.
+const FRACTIONAL_BITS: u32 = 16;
+
+/// A 16.16 fixed-point coordinate.
+///
+/// This stores a signed 32-bit integer, of which the low 16 bits are the fraction, so it
+/// represents values between about -32768 and 32768 in steps of 1/65536. Unlike floating-point
+/// coordinates, all math on it is exact integer math, so layouts come out the same on every
+/// target. Arithmetic saturates at the bounds of the type, and results are rounded down to the
+/// nearest step.
+///
+/// When fixed-point rectangles are converted to pixels, for hit testing and for the spans of
+/// damaged regions, their edges are rounded outward to whole pixels.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Fixed(i32);
+
+/// A 48.16 fixed-point number, used to compute products of [`Fixed`] coordinates without
+/// overflowing.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct WideFixed(i64);
+
+macro_rules! impl_fixed {
+    ($($ty:ident($bits:ty => $wide:ty)),*) => {$(
+        impl $ty {
+            /// The smallest value.
+            pub const MIN: Self = Self(<$bits>::MIN);
+
+            /// The largest value.
+            pub const MAX: Self = Self(<$bits>::MAX);
+
+            /// One.
+            pub const ONE: Self = Self(1 << FRACTIONAL_BITS);
+
+            /// Create a number from its raw bits.
+            pub const fn from_bits(bits: $bits) -> Self {
+                Self(bits)
+            }
+
+            /// Returns the raw bits of this number.
+            pub const fn to_bits(self) -> $bits {
+                self.0
+            }
+
+            /// Convert an intermediate result back into this type, saturating if it doesn't
+            /// fit.
+            fn saturate(bits: $wide) -> Self {
+                Self(bits.clamp(<$bits>::MIN.into(), <$bits>::MAX.into()) as $bits)
+            }
+
+            /// Multiply, rounding down, without saturating.
+            fn wide_mul(self, other: Self) -> $wide {
+                (<$wide>::from(self.0) * <$wide>::from(other.0)) >> FRACTIONAL_BITS
+            }
+        }
+
+        impl Add for $ty {
+            type Output = Self;
+
+            fn add(self, other: Self) -> Self {
+                Self(self.0.saturating_add(other.0))
+            }
+        }
+
+        impl Sub for $ty {
+            type Output = Self;
+
+            fn sub(self, other: Self) -> Self {
+                Self(self.0.saturating_sub(other.0))
+            }
+        }
+
+        impl Mul for $ty {
+            type Output = Self;
+
+            fn mul(self, other: Self) -> Self {
+                Self::saturate(self.wide_mul(other))
+            }
+        }
+
+        impl Div for $ty {
+            type Output = Self;
+
+            /// # Panics
+            ///
+            /// Panics if the divisor is zero.
+            #[allow(clippy::suspicious_arithmetic_impl)]
+            fn div(self, other: Self) -> Self {
+                let (dividend, divisor) = (
+                    <$wide>::from(self.0) << FRACTIONAL_BITS,
+                    <$wide>::from(other.0),
+                );
+
+                // Round towards negative infinity, like the shift in multiplication.
+                let mut quotient = dividend / divisor;
+                if dividend % divisor != 0 && (dividend < 0) != (divisor < 0) {
+                    quotient -= 1;
+                }
+                Self::saturate(quotient)
+            }
+        }
+
+        impl Neg for $ty {
+            type Output = Self;
+
+            fn neg(self) -> Self {
+                Self(self.0.saturating_neg())
+            }
+        }
+
+        impl fmt::Debug for $ty {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(
+                    f,
+                    concat!(stringify!($ty), "({})"),
+                    self.0 as f64 / f64::from(1u32 << FRACTIONAL_BITS)
+                )
+            }
+        }
+    )*};
+}
+
+impl_fixed!(Fixed(i32 => i64), WideFixed(i64 => i128));
+
+impl Coordinate for Fixed {
+    type Wide = WideFixed;
+
+    const ZERO: Self = Self(0);
+
+    fn from_i32(value: i32) -> Self {
+        Self::saturate(i64::from(value) << FRACTIONAL_BITS)
+    }
+
+    fn widen(self) -> Self::Wide {
+        WideFixed(self.0.into())
+    }
+
+    fn narrow(wide: Self::Wide) -> Self {
+        Self::saturate(wide.0)
+    }
+
+    fn checked_add(self, other: Self) -> Option<Self> {
+        self.0.checked_add(other.0).map(Self)
+    }
+
+    fn checked_sub(self, other: Self) -> Option<Self> {
+        self.0.checked_sub(other.0).map(Self)
+    }
+
+    fn checked_mul(self, other: Self) -> Option<Self> {
+        i32::try_from(self.wide_mul(other)).ok().map(Self)
+    }
+
+    fn saturating_add(self, other: Self) -> Self {
+        self + other
+    }
+
+    fn saturating_sub(self, other: Self) -> Self {
+        self - other
+    }
+
+    fn saturating_mul(self, other: Self) -> Self {
+        self * other
+    }
+
+    fn from_f64(value: f64) -> Self {
+        Self(i32::from_f64(value * f64::from(1u32 << FRACTIONAL_BITS)))
+    }
+
+    fn to_f64(self) -> f64 {
+        f64::from(self.0) / f64::from(1u32 << FRACTIONAL_BITS)
+    }
+
+    fn floor_i32(self) -> i32 {
+        self.0 >> FRACTIONAL_BITS
+    }
+
+    fn ceil_i32(self) -> i32 {
+        ((i64::from(self.0) + (1 << FRACTIONAL_BITS) - 1) >> FRACTIONAL_BITS) as i32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Fixed;
+    use crate::{Coordinate, Rectangle, Region, WindowTable};
+
+    #[test]
+    fn arithmetic() {
+        let half = Fixed::from_f64(0.5);
+        assert_eq!(half.to_bits(), 0x8000);
+        assert_eq!(half + half, Fixed::ONE);
+        assert_eq!(Fixed::from_i32(3) * half, Fixed::from_f64(1.5));
+        assert_eq!(Fixed::ONE / Fixed::from_i32(4), Fixed::from_f64(0.25));
+        assert_eq!(-half, Fixed::from_f64(-0.5));
+        assert_eq!(Fixed::ONE / Fixed::from_i32(-3), Fixed::from_bits(-21846));
+        assert_eq!(Fixed::MAX + Fixed::ONE, Fixed::MAX);
+        assert_eq!(Fixed::from_i32(40_000), Fixed::MAX);
+        assert_eq!(Fixed::from_i32(300).checked_mul(Fixed::from_i32(300)), None);
+        assert_eq!(Fixed::from_f64(2.75).to_f64(), 2.75);
+
+        assert_eq!(Fixed::from_f64(1.5).floor_i32(), 1);
+        assert_eq!(Fixed::from_f64(1.5).ceil_i32(), 2);
+        assert_eq!(Fixed::from_f64(-1.5).floor_i32(), -2);
+        assert_eq!(Fixed::from_f64(-1.5).ceil_i32(), -1);
+        assert_eq!(Fixed::from_i32(2).ceil_i32(), 2);
+        assert_eq!(Fixed::MAX.ceil_i32(), 32768);
+
+        let wide = Fixed::from_i32(200).widen() * Fixed::from_i32(300).widen();
+        assert_eq!(Fixed::narrow(wide), Fixed::MAX);
+        assert_eq!(
+            Fixed::narrow(wide / Fixed::from_i32(600).widen()),
+            Fixed::from_i32(100)
+        );
+    }
+
+    #[test]
+    fn window_table() {
+        let px = |value: f64| Fixed::from_f64(value);
+        let mut window_table = WindowTable::<Fixed>::default();
+
+        let root = window_table
+            .insert(Rectangle::new(px(0.0), px(0.0), px(100.0), px(100.0)))
+            .unwrap();
+        let a = window_table
+            .insert(Rectangle::new(px(10.25), px(10.5), px(20.75), px(20.5)))
+            .unwrap();
+        assert_eq!(window_table.parent(a), Some(root));
+        assert_eq!(window_table.window_at((px(10.25), px(15.0))), Some(a));
+        assert_eq!(window_table.window_at((px(10.0), px(15.0))), Some(root));
+
+        // Damage is rounded outward to whole pixels.
+        let spans = Region::from(window_table.rect(a)).spans();
+        assert_eq!(spans.first(), Some(&(10, 10, 21)));
+        assert_eq!(spans.last(), Some(&(20, 10, 21)));
+        assert_eq!(spans.len(), 11);
+    }
+}
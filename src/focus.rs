@@ -0,0 +1,754 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later OR MPL-2.0
+// This file is a part of `windowless`.
+//
+// `windowless` is free software: you can redistribute it and/or modify it under the terms of
+// either:
+//
+// * GNU Lesser General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+// * Mozilla Public License as published by the Mozilla Foundation, version 2.
+//
+// `windowless` is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+// See the GNU Lesser General Public License or the Mozilla Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License and the Mozilla
+// Public License along with `windowless`. If not, see <https://www.gnu.org/licenses/> or
+// <https://www.mozilla.org/en-US/MPL/2.0/>.
+
+//! Keyboard focus tracking.
+
+use crate::{
+    ButtonEvent, Coordinate, CrossingDetail, CursorEvent, ModalBlocked, Order, WindowKey,
+    WindowTable,
+};
+use alloc::vec::Vec;
+use smallvec::SmallVec;
+
+/// The current keyboard focus state.
+#[derive(Debug, Default)]
+pub struct FocusState {
+    /// The focused window, preceded by its ancestors.
+    ///
+    /// This is remembered so that focus can revert to an ancestor once the focused window is
+    /// removed from the table.
+    chain: SmallVec<[WindowKey; 3]>,
+
+    /// How the pointer moves the focus.
+    policy: FocusPolicy,
+
+    /// The number of ticks to wait before the focus follows the pointer.
+    delay: u32,
+
+    /// The window that the focus will follow the pointer to, and the ticks left until it does.
+    pending: Option<(WindowKey, u32)>,
+
+    /// The order that traversal moves the focus in, if it isn't the default.
+    traversal: Option<Vec<WindowKey>>,
+}
+
+/// A direction to move the focus in, with [`WindowTable::focus_in_direction`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Direction {
+    /// Towards the left edge of the screen.
+    Left,
+
+    /// Towards the right edge of the screen.
+    Right,
+
+    /// Towards the top edge of the screen.
+    Up,
+
+    /// Towards the bottom edge of the screen.
+    Down,
+}
+
+/// How the pointer moves the keyboard focus.
+///
+/// Set with [`FocusState::set_policy`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub enum FocusPolicy {
+    /// The pointer never moves the focus; it is only changed with
+    /// [`set_focus`](FocusState::set_focus).
+    #[default]
+    Manual,
+
+    /// Pressing a button on a window focuses it.
+    ClickToFocus,
+
+    /// The pointer entering a window focuses it, after the [delay](FocusState::set_delay).
+    FollowsMouse,
+}
+
+/// The path that a keyboard event takes to reach the focused window.
+///
+/// Returned by [`FocusState::route_key_event`]. Toolkits can use this to implement capture and
+/// bubble phases of event delivery.
+#[derive(Debug, Clone)]
+pub struct KeyRoute<E> {
+    /// The event being routed.
+    event: E,
+
+    /// The focused window, followed by its ancestors.
+    path: SmallVec<[WindowKey; 3]>,
+}
+
+impl<E> KeyRoute<E> {
+    /// Returns the event being routed.
+    pub fn event(&self) -> &E {
+        &self.event
+    }
+
+    /// Consumes the route, returning the event.
+    pub fn into_event(self) -> E {
+        self.event
+    }
+
+    /// Returns the window the event is targeted at.
+    pub fn target(&self) -> WindowKey {
+        self.path[0]
+    }
+
+    /// Returns the delivery path, from the focused window up to the root.
+    pub fn path(&self) -> &[WindowKey] {
+        &self.path
+    }
+
+    /// Iterate over the windows in capture order, from the root down to the focused window.
+    pub fn capture(&self) -> impl DoubleEndedIterator<Item = WindowKey> + '_ {
+        self.path.iter().rev().copied()
+    }
+
+    /// Iterate over the windows in bubble order, from the focused window up to the root.
+    pub fn bubble(&self) -> impl DoubleEndedIterator<Item = WindowKey> + '_ {
+        self.path.iter().copied()
+    }
+}
+
+/// An event generated by the keyboard focus changing.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum FocusEvent {
+    /// This window has lost the keyboard focus.
+    FocusOut(WindowKey),
+
+    /// This window has gained the keyboard focus.
+    FocusIn(WindowKey),
+}
+
+impl FocusState {
+    /// Creates a new focus state, with no window focused.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the focused window.
+    pub fn focus(&self) -> Option<WindowKey> {
+        self.chain.last().copied()
+    }
+
+    /// Set the focused window.
+    ///
+    /// Passing `None` removes the focus from every window. If the window is not viewable, the
+    /// focus is left unchanged. Returns the events generated by the change.
+    pub fn set_focus<T: Coordinate, const N: usize, const R: usize>(
+        &mut self,
+        table: &WindowTable<T, N, R>,
+        key: Option<WindowKey>,
+    ) -> SmallVec<[FocusEvent; 2]> {
+        let chain = match key {
+            Some(key) if !table.is_viewable(key) => return SmallVec::new(),
+            Some(key) => ancestor_chain(table, key),
+            None => SmallVec::new(),
+        };
+
+        self.replace(chain)
+    }
+
+    /// Returns how the pointer moves the focus.
+    pub fn policy(&self) -> FocusPolicy {
+        self.policy
+    }
+
+    /// Set how the pointer moves the focus.
+    ///
+    /// The default policy is [`FocusPolicy::Manual`].
+    pub fn set_policy(&mut self, policy: FocusPolicy) {
+        self.policy = policy;
+        self.pending = None;
+    }
+
+    /// Returns the number of ticks the focus waits before following the pointer.
+    pub fn delay(&self) -> u32 {
+        self.delay
+    }
+
+    /// Set the number of [ticks](Self::tick) the focus waits before following the pointer
+    /// into a window, with [`FocusPolicy::FollowsMouse`].
+    ///
+    /// The default delay of zero moves the focus right away.
+    pub fn set_delay(&mut self, delay: u32) {
+        self.delay = delay;
+    }
+
+    /// Move the focus according to the crossing events generated by the pointer.
+    ///
+    /// With [`FocusPolicy::FollowsMouse`], the window that the pointer ends up in is focused,
+    /// or will be once the delay has passed, unless it is blocked by a
+    /// [modal window](WindowTable::set_modal). Otherwise, this does nothing.
+    pub fn pointer_crossed<T: Coordinate, const N: usize, const R: usize>(
+        &mut self,
+        table: &WindowTable<T, N, R>,
+        events: &[CursorEvent],
+    ) -> SmallVec<[FocusEvent; 2]> {
+        if self.policy != FocusPolicy::FollowsMouse {
+            return SmallVec::new();
+        }
+
+        let entered = events.iter().rev().find_map(|event| match *event {
+            // Windows that the pointer passed through on the way aren't focused.
+            CursorEvent::Enter(_, CrossingDetail::Virtual)
+            | CursorEvent::Enter(_, CrossingDetail::NonlinearVirtual)
+            | CursorEvent::Leave(..) => None,
+            CursorEvent::Enter(key, _) => Some(key),
+        });
+
+        match entered {
+            Some(key) if table.blocking_modal(key).is_some() => {
+                self.pending = None;
+                SmallVec::new()
+            }
+            Some(key) if self.delay == 0 => {
+                self.pending = None;
+                self.set_focus(table, Some(key))
+            }
+            Some(key) => {
+                self.pending = Some((key, self.delay));
+                SmallVec::new()
+            }
+            None => SmallVec::new(),
+        }
+    }
+
+    /// Move the focus according to a button event.
+    ///
+    /// With [`FocusPolicy::ClickToFocus`], pressing a button focuses the window that receives
+    /// the press, unless it is blocked by a [modal window](WindowTable::set_modal). Otherwise,
+    /// this does nothing.
+    pub fn button_pressed<T: Coordinate, const N: usize, const R: usize>(
+        &mut self,
+        table: &WindowTable<T, N, R>,
+        event: &ButtonEvent<T>,
+    ) -> SmallVec<[FocusEvent; 2]> {
+        if self.policy != FocusPolicy::ClickToFocus
+            || !event.pressed
+            || table.blocking_modal(event.window).is_some()
+        {
+            return SmallVec::new();
+        }
+
+        self.set_focus(table, Some(event.window))
+    }
+
+    /// Advance the focus delay by one tick.
+    ///
+    /// Once the delay has passed since the pointer entered a window, the window is focused,
+    /// unless a modal window has started blocking it in the meantime.
+    pub fn tick<T: Coordinate, const N: usize, const R: usize>(
+        &mut self,
+        table: &WindowTable<T, N, R>,
+    ) -> SmallVec<[FocusEvent; 2]> {
+        match self.pending {
+            Some((key, ticks)) if ticks <= 1 => {
+                self.pending = None;
+                if table.contains_key(key) && table.blocking_modal(key).is_none() {
+                    self.set_focus(table, Some(key))
+                } else {
+                    SmallVec::new()
+                }
+            }
+            Some((key, ticks)) => {
+                self.pending = Some((key, ticks - 1));
+                SmallVec::new()
+            }
+            None => SmallVec::new(),
+        }
+    }
+
+    /// Set the order that [`focus_next`](Self::focus_next) and
+    /// [`focus_prev`](Self::focus_prev) move the focus in.
+    ///
+    /// Passing `None` goes back to the default order, which visits the windows depth-first
+    /// from the root, with siblings from the bottom of the stack up.
+    pub fn set_traversal_order(&mut self, order: Option<Vec<WindowKey>>) {
+        self.traversal = order;
+    }
+
+    /// Returns the order that traversal moves the focus in, if it isn't the default.
+    pub fn traversal_order(&self) -> Option<&[WindowKey]> {
+        self.traversal.as_deref()
+    }
+
+    /// Move the focus to the next window in the traversal order, wrapping around at the end.
+    ///
+    /// Windows that are not viewable or not [focusable](WindowTable::set_focusable) are
+    /// skipped. If no window is focused, the first window is.
+    pub fn focus_next<T: Coordinate, const N: usize, const R: usize>(
+        &mut self,
+        table: &WindowTable<T, N, R>,
+    ) -> SmallVec<[FocusEvent; 2]> {
+        self.traverse(table, false)
+    }
+
+    /// Move the focus to the previous window in the traversal order, wrapping around at the
+    /// start.
+    ///
+    /// Windows that are not viewable or not [focusable](WindowTable::set_focusable) are
+    /// skipped. If no window is focused, the last window is.
+    pub fn focus_prev<T: Coordinate, const N: usize, const R: usize>(
+        &mut self,
+        table: &WindowTable<T, N, R>,
+    ) -> SmallVec<[FocusEvent; 2]> {
+        self.traverse(table, true)
+    }
+
+    /// Move the focus one step through the traversal order.
+    fn traverse<T: Coordinate, const N: usize, const R: usize>(
+        &mut self,
+        table: &WindowTable<T, N, R>,
+        backwards: bool,
+    ) -> SmallVec<[FocusEvent; 2]> {
+        let mut candidates = match (&self.traversal, table.root()) {
+            (Some(order), _) => order.clone(),
+            (None, Some(root)) => table.traverse(root, Order::DepthFirst).collect(),
+            (None, None) => Vec::new(),
+        };
+        candidates.retain(|&key| table.is_viewable(key) && table.focusable(key));
+        if backwards {
+            candidates.reverse();
+        }
+
+        let next = match self
+            .focus()
+            .and_then(|focus| candidates.iter().position(|&key| key == focus))
+        {
+            Some(index) => candidates.get(index + 1).or_else(|| candidates.first()),
+            None => candidates.first(),
+        };
+
+        match next {
+            Some(&next) => self.set_focus(table, Some(next)),
+            None => SmallVec::new(),
+        }
+    }
+
+    /// Bring the focus state up to date with the table.
+    ///
+    /// If the focused window has been removed or is no longer viewable, the focus reverts to its
+    /// closest viewable ancestor. This should be called after the table has been modified.
+    pub fn update<T: Coordinate, const N: usize, const R: usize>(
+        &mut self,
+        table: &WindowTable<T, N, R>,
+    ) -> SmallVec<[FocusEvent; 2]> {
+        let focus = match self.focus() {
+            Some(focus) => focus,
+            None => return SmallVec::new(),
+        };
+
+        if table.is_viewable(focus) {
+            // The window may have been restacked, so refresh its ancestors.
+            self.chain = ancestor_chain(table, focus);
+            return SmallVec::new();
+        }
+
+        let chain = match self
+            .chain
+            .iter()
+            .rev()
+            .find(|&&ancestor| table.is_viewable(ancestor))
+        {
+            Some(&ancestor) => ancestor_chain(table, ancestor),
+            None => SmallVec::new(),
+        };
+
+        self.replace(chain)
+    }
+
+    /// Route a keyboard event to the focused window.
+    ///
+    /// Returns the path that the event should take, or `None` if no window is focused.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the focused window is blocked by a
+    /// [modal window](WindowTable::set_modal).
+    pub fn route_key_event<T: Coordinate, E, const N: usize, const R: usize>(
+        &self,
+        table: &WindowTable<T, N, R>,
+        event: E,
+    ) -> Result<Option<KeyRoute<E>>, ModalBlocked> {
+        let focus = match self.focus() {
+            Some(focus) => focus,
+            None => return Ok(None),
+        };
+        let path = table.ancestors(focus).collect::<SmallVec<_>>();
+
+        if path.is_empty() {
+            return Ok(None);
+        }
+
+        if let Some(modal) = table.blocking_modal(focus) {
+            return Err(ModalBlocked {
+                window: focus,
+                modal,
+            });
+        }
+
+        Ok(Some(KeyRoute { event, path }))
+    }
+
+    /// Replace the focus chain, returning the events generated.
+    fn replace(&mut self, chain: SmallVec<[WindowKey; 3]>) -> SmallVec<[FocusEvent; 2]> {
+        let old = self.focus();
+        let new = chain.last().copied();
+        self.chain = chain;
+
+        let mut events = SmallVec::new();
+        if old != new {
+            events.extend(old.map(FocusEvent::FocusOut));
+            events.extend(new.map(FocusEvent::FocusIn));
+        }
+
+        events
+    }
+}
+
+impl<T: Coordinate, const N: usize, const R: usize> WindowTable<T, N, R> {
+    /// Set whether a window can be given the focus by [traversal](FocusState::focus_next).
+    ///
+    /// Every window is focusable by default. This does not stop the window from being focused
+    /// with [`FocusState::set_focus`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn set_focusable(&mut self, key: WindowKey, focusable: bool) {
+        self.windows[key.0].focusable = focusable;
+    }
+
+    /// Tell if a window can be given the focus by traversal.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn focusable(&self, key: WindowKey) -> bool {
+        self.windows[key.0].focusable
+    }
+
+    /// Find the best window to move the focus to from a window, in a direction.
+    ///
+    /// Candidates are the viewable, [focusable](Self::set_focusable) windows that lie entirely
+    /// beyond the window's edge in that direction. Each is scored by the distance between the
+    /// facing edges, plus twice the gap between the windows along the other axis, minus how
+    /// much they overlap along the other axis. The candidate with the lowest score wins, with
+    /// ties going to the higher window. Returns `None` if there are no candidates.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn focus_in_direction(&self, from: WindowKey, direction: Direction) -> Option<WindowKey> {
+        let from_rect = self.windows[from.0].rect;
+        let mut best: Option<(f64, WindowKey)> = None;
+
+        for &key in self.order.iter().rev() {
+            if key == from || !self.windows[key.0].focusable || !self.is_viewable(key) {
+                continue;
+            }
+
+            let rect = self.windows[key.0].rect;
+            let (distance, (low, high), (other_low, other_high)) = match direction {
+                Direction::Left => (
+                    from_rect.left.to_f64() - rect.right.to_f64(),
+                    (from_rect.top, from_rect.bottom),
+                    (rect.top, rect.bottom),
+                ),
+                Direction::Right => (
+                    rect.left.to_f64() - from_rect.right.to_f64(),
+                    (from_rect.top, from_rect.bottom),
+                    (rect.top, rect.bottom),
+                ),
+                Direction::Up => (
+                    from_rect.top.to_f64() - rect.bottom.to_f64(),
+                    (from_rect.left, from_rect.right),
+                    (rect.left, rect.right),
+                ),
+                Direction::Down => (
+                    rect.top.to_f64() - from_rect.bottom.to_f64(),
+                    (from_rect.left, from_rect.right),
+                    (rect.left, rect.right),
+                ),
+            };
+            if distance < 0.0 {
+                continue;
+            }
+
+            // Positive when the windows overlap along the other axis, negative for the gap.
+            let overlap =
+                high.to_f64().min(other_high.to_f64()) - low.to_f64().max(other_low.to_f64());
+            let score = if overlap >= 0.0 {
+                distance - overlap
+            } else {
+                distance - 2.0 * overlap
+            };
+
+            if !matches!(best, Some((best, _)) if best <= score) {
+                best = Some((score, key));
+            }
+        }
+
+        best.map(|(_, key)| key)
+    }
+}
+
+/// Get a window, preceded by its ancestors.
+fn ancestor_chain<T: Coordinate, const N: usize, const R: usize>(
+    table: &WindowTable<T, N, R>,
+    key: WindowKey,
+) -> SmallVec<[WindowKey; 3]> {
+    let mut chain = table.ancestors(key).collect::<SmallVec<_>>();
+    chain.reverse();
+    chain
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Direction, FocusEvent, FocusPolicy, FocusState};
+    use crate::{Button, ButtonEvent, CursorState, ModalScope, Point, Rectangle, WindowTable};
+
+    #[test]
+    fn set_focus() {
+        let mut window_table = WindowTable::new();
+
+        let _root = window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let a = window_table.insert(Rectangle::new(10, 10, 40, 40)).unwrap();
+        let b = window_table.insert(Rectangle::new(50, 50, 90, 90)).unwrap();
+
+        let mut focus = FocusState::new();
+        assert_eq!(
+            &focus.set_focus(&window_table, Some(a))[..],
+            &[FocusEvent::FocusIn(a)]
+        );
+        assert_eq!(
+            &focus.set_focus(&window_table, Some(b))[..],
+            &[FocusEvent::FocusOut(a), FocusEvent::FocusIn(b)]
+        );
+        assert!(focus.set_focus(&window_table, Some(b)).is_empty());
+        assert_eq!(focus.focus(), Some(b));
+    }
+
+    #[test]
+    fn route_key_event() {
+        let mut window_table = WindowTable::new();
+
+        let root = window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let panel = window_table.insert(Rectangle::new(10, 10, 60, 60)).unwrap();
+        let button = window_table.insert(Rectangle::new(20, 20, 30, 30)).unwrap();
+
+        let mut focus = FocusState::new();
+        assert!(matches!(
+            focus.route_key_event(&window_table, 'a'),
+            Ok(None)
+        ));
+
+        focus.set_focus(&window_table, Some(button));
+        let route = focus.route_key_event(&window_table, 'a').unwrap().unwrap();
+        assert_eq!(*route.event(), 'a');
+        assert_eq!(route.target(), button);
+        assert!(route.capture().eq([root, panel, button]));
+        assert!(route.bubble().eq([button, panel, root]));
+    }
+
+    #[test]
+    fn revert_on_remove() {
+        let mut window_table = WindowTable::new();
+
+        let root = window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let panel = window_table.insert(Rectangle::new(10, 10, 60, 60)).unwrap();
+        let button = window_table.insert(Rectangle::new(20, 20, 30, 30)).unwrap();
+
+        let mut focus = FocusState::new();
+        focus.set_focus(&window_table, Some(button));
+
+        window_table.unmap(panel);
+        window_table.remove(button);
+        assert_eq!(
+            &focus.update(&window_table)[..],
+            &[FocusEvent::FocusOut(button), FocusEvent::FocusIn(root)]
+        );
+    }
+
+    #[test]
+    fn policy() {
+        let mut window_table = WindowTable::new();
+
+        let root = window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let panel = window_table.insert(Rectangle::new(10, 10, 60, 60)).unwrap();
+
+        let mut cursor = CursorState::new();
+        let mut focus = FocusState::new();
+        let events = cursor.update(&window_table, (20, 20));
+        assert!(focus.pointer_crossed(&window_table, &events).is_empty());
+
+        focus.set_policy(FocusPolicy::ClickToFocus);
+        let press = cursor
+            .button_down(&window_table, Button::Left)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            &focus.button_pressed(&window_table, &press)[..],
+            &[FocusEvent::FocusIn(panel)]
+        );
+        cursor.button_up(&window_table, Button::Left);
+
+        focus.set_policy(FocusPolicy::FollowsMouse);
+        focus.set_delay(2);
+        let events = cursor.update(&window_table, (80, 80));
+        assert!(focus.pointer_crossed(&window_table, &events).is_empty());
+        assert!(focus.tick(&window_table).is_empty());
+        assert_eq!(
+            &focus.tick(&window_table)[..],
+            &[FocusEvent::FocusOut(panel), FocusEvent::FocusIn(root)]
+        );
+        assert!(focus.tick(&window_table).is_empty());
+
+        focus.set_delay(0);
+        let events = cursor.update(&window_table, (20, 20));
+        assert_eq!(
+            &focus.pointer_crossed(&window_table, &events)[..],
+            &[FocusEvent::FocusOut(root), FocusEvent::FocusIn(panel)]
+        );
+    }
+
+    #[test]
+    fn modal_blocks_focus() {
+        let mut window_table = WindowTable::new();
+
+        window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let panel = window_table.insert(Rectangle::new(10, 10, 40, 40)).unwrap();
+        let dialog = window_table.insert(Rectangle::new(60, 60, 90, 90)).unwrap();
+        window_table.set_modal(dialog, ModalScope::Root);
+
+        let mut cursor = CursorState::new();
+        let mut focus = FocusState::new();
+        focus.set_policy(FocusPolicy::FollowsMouse);
+        let events = cursor.update(&window_table, (20, 20));
+        assert!(focus.pointer_crossed(&window_table, &events).is_empty());
+        let events = cursor.update(&window_table, (70, 70));
+        assert_eq!(
+            &focus.pointer_crossed(&window_table, &events)[..],
+            &[FocusEvent::FocusIn(dialog)]
+        );
+
+        // The focus does not follow the pointer out of the dialog.
+        focus.set_delay(1);
+        let events = cursor.update(&window_table, (20, 20));
+        assert!(focus.pointer_crossed(&window_table, &events).is_empty());
+        assert!(focus.tick(&window_table).is_empty());
+        assert_eq!(focus.focus(), Some(dialog));
+
+        // Nor does clicking on a blocked window focus it.
+        focus.set_policy(FocusPolicy::ClickToFocus);
+        let press = ButtonEvent {
+            window: panel,
+            button: Button::Left,
+            pressed: true,
+            position: Point::new(10, 10),
+        };
+        assert!(focus.button_pressed(&window_table, &press).is_empty());
+
+        window_table.unmap(dialog);
+        assert_eq!(
+            &focus.button_pressed(&window_table, &press)[..],
+            &[FocusEvent::FocusOut(dialog), FocusEvent::FocusIn(panel)]
+        );
+    }
+
+    #[test]
+    fn traversal() {
+        let mut window_table = WindowTable::new();
+
+        let root = window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let panel = window_table.insert(Rectangle::new(10, 10, 60, 60)).unwrap();
+        let button = window_table.insert(Rectangle::new(20, 20, 30, 30)).unwrap();
+        let other = window_table.insert(Rectangle::new(70, 70, 90, 90)).unwrap();
+
+        let mut focus = FocusState::new();
+        window_table.set_focusable(panel, false);
+        assert!(!window_table.focusable(panel));
+
+        assert_eq!(
+            &focus.focus_next(&window_table)[..],
+            &[FocusEvent::FocusIn(root)]
+        );
+        focus.focus_next(&window_table);
+        assert_eq!(focus.focus(), Some(button));
+        focus.focus_next(&window_table);
+        assert_eq!(focus.focus(), Some(other));
+        focus.focus_next(&window_table);
+        assert_eq!(focus.focus(), Some(root));
+        focus.focus_prev(&window_table);
+        assert_eq!(focus.focus(), Some(other));
+
+        // Unmapped windows are skipped.
+        window_table.unmap(button);
+        focus.focus_prev(&window_table);
+        assert_eq!(focus.focus(), Some(root));
+
+        focus.set_traversal_order(Some(alloc::vec![other, button, root]));
+        assert_eq!(focus.traversal_order(), Some(&[other, button, root][..]));
+        focus.focus_next(&window_table);
+        assert_eq!(focus.focus(), Some(other));
+    }
+
+    #[test]
+    fn focus_in_direction() {
+        let mut window_table = WindowTable::new();
+
+        window_table.insert(Rectangle::new(0, 0, 300, 300)).unwrap();
+        let center = window_table
+            .insert(Rectangle::new(100, 100, 200, 200))
+            .unwrap();
+        let right = window_table
+            .insert(Rectangle::new(250, 120, 290, 180))
+            .unwrap();
+        let near_corner = window_table
+            .insert(Rectangle::new(210, 10, 240, 40))
+            .unwrap();
+        let up = window_table
+            .insert(Rectangle::new(100, 10, 150, 60))
+            .unwrap();
+
+        // The window lined up to the right beats the closer one off to the side.
+        assert_eq!(
+            window_table.focus_in_direction(center, Direction::Right),
+            Some(right)
+        );
+        assert_eq!(
+            window_table.focus_in_direction(center, Direction::Up),
+            Some(up)
+        );
+        assert_eq!(
+            window_table.focus_in_direction(center, Direction::Left),
+            None
+        );
+        assert_eq!(
+            window_table.focus_in_direction(up, Direction::Right),
+            Some(near_corner)
+        );
+
+        window_table.set_focusable(right, false);
+        assert_eq!(
+            window_table.focus_in_direction(center, Direction::Right),
+            Some(near_corner)
+        );
+    }
+}
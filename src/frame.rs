@@ -0,0 +1,99 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later OR MPL-2.0
+// This file is a part of `windowless`.
+//
+// `windowless` is free software: you can redistribute it and/or modify it under the terms of
+// either:
+//
+// * GNU Lesser General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+// * Mozilla Public License as published by the Mozilla Foundation, version 2.
+//
+// `windowless` is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+// See the GNU Lesser General Public License or the Mozilla Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License and the Mozilla
+// Public License along with `windowless`. If not, see <https://www.gnu.org/licenses/> or
+// <https://www.mozilla.org/en-US/MPL/2.0/>.
+
+//! Scheduling frame callbacks.
+
+use crate::{Coordinate, WindowKey, WindowTable};
+use alloc::vec::Vec;
+
+impl<T: Coordinate, const N: usize, const R: usize> WindowTable<T, N, R> {
+    /// Ask for a window to be told when to draw its next frame.
+    ///
+    /// The request is answered by the next call to [`frame_done`](Self::frame_done) in which
+    /// the window can be seen. Asking again before then does nothing.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn request_frame(&mut self, key: WindowKey) {
+        assert!(self.contains_key(key), "window does not exist");
+        if !self.frame_requests.contains(&key) {
+            self.frame_requests.push(key);
+        }
+    }
+
+    /// Tell if a window is waiting to be told to draw its next frame.
+    pub fn has_frame_request(&self, key: WindowKey) -> bool {
+        self.frame_requests.contains(&key)
+    }
+
+    /// Finish a frame, returning the windows that should draw their next one.
+    ///
+    /// These are the windows that [asked for a frame](Self::request_frame) and have some part
+    /// of them visible, in the order they asked. Windows that are unmapped or entirely covered
+    /// keep waiting, so they don't draw frames that nobody will see. Requests from windows that
+    /// were removed are dropped.
+    pub fn frame_done(&mut self) -> Vec<WindowKey> {
+        let mut requests = core::mem::take(&mut self.frame_requests);
+        requests.retain(|&key| self.contains_key(key));
+
+        let mut done = Vec::new();
+        requests.retain(|&key| {
+            let visible = !self.visible_region(key).is_empty();
+            if visible {
+                done.push(key);
+            }
+            !visible
+        });
+
+        self.frame_requests = requests;
+        done
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Rectangle, WindowTable};
+
+    #[test]
+    fn frame_callbacks() {
+        let mut window_table = WindowTable::new();
+
+        window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let video = window_table.insert(Rectangle::new(0, 0, 50, 50)).unwrap();
+        let clock = window_table.insert(Rectangle::new(60, 60, 90, 90)).unwrap();
+        let cover = window_table.insert(Rectangle::new(0, 0, 50, 50)).unwrap();
+
+        window_table.request_frame(clock);
+        window_table.request_frame(video);
+        window_table.request_frame(clock);
+        assert_eq!(window_table.frame_done(), [clock]);
+
+        // The covered window is throttled until it can be seen.
+        assert!(window_table.has_frame_request(video));
+        assert!(window_table.frame_done().is_empty());
+        window_table.unmap(cover);
+        assert_eq!(window_table.frame_done(), [video]);
+        assert!(!window_table.has_frame_request(video));
+
+        window_table.request_frame(video);
+        window_table.remove(video);
+        assert!(window_table.frame_done().is_empty());
+        assert!(!window_table.has_frame_request(video));
+    }
+}
@@ -0,0 +1,290 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later OR MPL-2.0
+// This file is a part of `windowless`.
+//
+// `windowless` is free software: you can redistribute it and/or modify it under the terms of
+// either:
+//
+// * GNU Lesser General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+// * Mozilla Public License as published by the Mozilla Foundation, version 2.
+//
+// `windowless` is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+// See the GNU Lesser General Public License or the Mozilla Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License and the Mozilla
+// Public License along with `windowless`. If not, see <https://www.gnu.org/licenses/> or
+// <https://www.mozilla.org/en-US/MPL/2.0/>.
+
+//! Generating random window tables for fuzzing.
+
+use crate::{Coordinate, Gravity, Layer, Point, Rectangle, Size, WindowKey, WindowTable};
+use arbitrary::{Arbitrary, Result, Unstructured};
+
+impl<'a, T: Arbitrary<'a>> Arbitrary<'a> for Point<T> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Self {
+            x: u.arbitrary()?,
+            y: u.arbitrary()?,
+        })
+    }
+}
+
+impl<'a, T: Arbitrary<'a>> Arbitrary<'a> for Size<T> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Self {
+            width: u.arbitrary()?,
+            height: u.arbitrary()?,
+        })
+    }
+}
+
+impl<'a, T: Arbitrary<'a>> Arbitrary<'a> for Rectangle<T> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Self {
+            left: u.arbitrary()?,
+            top: u.arbitrary()?,
+            right: u.arbitrary()?,
+            bottom: u.arbitrary()?,
+        })
+    }
+}
+
+impl<'a> Arbitrary<'a> for Gravity {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        u.choose(&[
+            Gravity::NorthWest,
+            Gravity::North,
+            Gravity::NorthEast,
+            Gravity::West,
+            Gravity::Center,
+            Gravity::East,
+            Gravity::SouthWest,
+            Gravity::South,
+            Gravity::SouthEast,
+            Gravity::Static,
+            Gravity::Unmap,
+        ])
+        .copied()
+    }
+}
+
+impl<'a> Arbitrary<'a> for Layer {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        u.choose(&[
+            Layer::Background,
+            Layer::Normal,
+            Layer::Dock,
+            Layer::Overlay,
+        ])
+        .copied()
+    }
+}
+
+/// A random change to a window table.
+///
+/// Windows are named by their index in [`WindowTable::iter`], wrapped around to the number of
+/// windows in the table, so that any mutation can be applied to any table.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Mutation<T = i32> {
+    /// Insert a window.
+    Insert(Rectangle<T>),
+
+    /// Remove a window.
+    Remove(usize),
+
+    /// Give a window a new rectangle.
+    Resize(usize, Rectangle<T>),
+
+    /// Move a window.
+    Move(usize, Point<T>),
+
+    /// Raise a window.
+    Raise(usize),
+
+    /// Lower a window.
+    Lower(usize),
+
+    /// Map a window.
+    Map(usize),
+
+    /// Unmap a window.
+    Unmap(usize),
+
+    /// Set the gravity of a window.
+    SetGravity(usize, Gravity),
+
+    /// Give the root window a new rectangle.
+    ResizeRoot(Rectangle<T>),
+
+    /// Move a window into a layer.
+    SetLayer(usize, Layer),
+}
+
+impl<'a, T: Arbitrary<'a>> Arbitrary<'a> for Mutation<T> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(match u.int_in_range(0..=10)? {
+            0 => Mutation::Insert(u.arbitrary()?),
+            1 => Mutation::Remove(u.arbitrary()?),
+            2 => Mutation::Resize(u.arbitrary()?, u.arbitrary()?),
+            3 => Mutation::Move(u.arbitrary()?, u.arbitrary()?),
+            4 => Mutation::Raise(u.arbitrary()?),
+            5 => Mutation::Lower(u.arbitrary()?),
+            6 => Mutation::Map(u.arbitrary()?),
+            7 => Mutation::Unmap(u.arbitrary()?),
+            8 => Mutation::SetGravity(u.arbitrary()?, u.arbitrary()?),
+            9 => Mutation::ResizeRoot(u.arbitrary()?),
+            _ => Mutation::SetLayer(u.arbitrary()?, u.arbitrary()?),
+        })
+    }
+}
+
+impl<T: Coordinate> Mutation<T> {
+    /// Apply this change to a table.
+    ///
+    /// Changes that the table rejects, and changes to a table with no windows, are ignored.
+    pub fn apply(&self, table: &mut WindowTable<T>) {
+        let nth = |table: &WindowTable<T>, index: usize| -> Option<WindowKey> {
+            let len = table.len();
+            if len == 0 {
+                None
+            } else {
+                table.iter().nth(index % len).map(|(key, _)| key)
+            }
+        };
+
+        match *self {
+            Mutation::Insert(rect) => {
+                table.insert(rect).ok();
+            }
+            Mutation::Remove(index) => {
+                if let Some(key) = nth(table, index) {
+                    table.remove(key);
+                }
+            }
+            Mutation::Resize(index, rect) => {
+                if let Some(key) = nth(table, index) {
+                    table.resize_window(key, rect).ok();
+                }
+            }
+            Mutation::Move(index, position) => {
+                if let Some(key) = nth(table, index) {
+                    if Some(key) != table.root() {
+                        table.move_window(key, position).ok();
+                    }
+                }
+            }
+            Mutation::Raise(index) => {
+                if let Some(key) = nth(table, index) {
+                    table.raise(key);
+                }
+            }
+            Mutation::Lower(index) => {
+                if let Some(key) = nth(table, index) {
+                    table.lower(key);
+                }
+            }
+            Mutation::Map(index) => {
+                if let Some(key) = nth(table, index) {
+                    table.map(key);
+                }
+            }
+            Mutation::Unmap(index) => {
+                if let Some(key) = nth(table, index) {
+                    table.unmap(key);
+                }
+            }
+            Mutation::SetGravity(index, gravity) => {
+                if let Some(key) = nth(table, index) {
+                    table.set_gravity(key, gravity);
+                }
+            }
+            Mutation::ResizeRoot(rect) => {
+                if table.root().is_some() {
+                    table.resize_root(rect).ok();
+                }
+            }
+            Mutation::SetLayer(index, layer) => {
+                if let Some(key) = nth(table, index) {
+                    table.set_layer(key, layer);
+                }
+            }
+        }
+    }
+}
+
+/// Generates a table with a root window, built by applying a random sequence of
+/// [`Mutation`]s to it.
+impl<'a, T: Coordinate + Arbitrary<'a>> Arbitrary<'a> for WindowTable<T> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let mut table = WindowTable::new();
+
+        let root = u.arbitrary::<Rectangle<T>>()?.normalize();
+        let root = if root.is_empty() {
+            Rectangle::new(
+                T::ZERO,
+                T::ZERO,
+                T::from_i32(u.int_in_range(1..=4096)?),
+                T::from_i32(u.int_in_range(1..=4096)?),
+            )
+        } else {
+            root
+        };
+        table.insert(root).expect("root window is not empty");
+
+        while !u.is_empty() {
+            Mutation::arbitrary(u)?.apply(&mut table);
+        }
+
+        Ok(table)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Mutation;
+    use crate::WindowTable;
+    use alloc::vec::Vec;
+    use arbitrary::{Arbitrary, Unstructured};
+
+    /// Generate deterministic noise for the fuzzer to chew on.
+    fn noise(seed: u64, len: usize) -> Vec<u8> {
+        let mut state = seed;
+        (0..len)
+            .map(|_| {
+                state = state
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                (state >> 56) as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn random_tables() {
+        for seed in 0..64 {
+            let data = noise(seed, 2048);
+            let table = WindowTable::<i32>::arbitrary(&mut Unstructured::new(&data)).unwrap();
+            assert_eq!(table.validate(), Ok(()));
+
+            let table = WindowTable::<f32>::arbitrary(&mut Unstructured::new(&data)).unwrap();
+            assert_eq!(table.validate(), Ok(()));
+        }
+    }
+
+    #[test]
+    fn random_mutations() {
+        for seed in 0..64 {
+            let data = noise(seed, 2048);
+            let mut u = Unstructured::new(&data);
+            let mut table = WindowTable::<i32>::arbitrary(&mut u).unwrap();
+
+            let data = noise(!seed, 2048);
+            let mut u = Unstructured::new(&data);
+            while !u.is_empty() {
+                Mutation::arbitrary(&mut u).unwrap().apply(&mut table);
+                assert_eq!(table.validate(), Ok(()));
+            }
+        }
+    }
+}
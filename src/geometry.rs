@@ -0,0 +1,125 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later OR MPL-2.0
+// This file is a part of `windowless`.
+//
+// `windowless` is free software: you can redistribute it and/or modify it under the terms of
+// either:
+//
+// * GNU Lesser General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+// * Mozilla Public License as published by the Mozilla Foundation, version 2.
+//
+// `windowless` is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+// See the GNU Lesser General Public License or the Mozilla Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License and the Mozilla
+// Public License along with `windowless`. If not, see <https://www.gnu.org/licenses/> or
+// <https://www.mozilla.org/en-US/MPL/2.0/>.
+
+//! Points and sizes.
+
+use crate::Coordinate;
+
+/// A point.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Point<T = i32> {
+    /// The horizontal coordinate.
+    pub x: T,
+
+    /// The vertical coordinate.
+    pub y: T,
+}
+
+impl<T: Coordinate> Point<T> {
+    /// Creates a new point.
+    pub fn new(x: T, y: T) -> Self {
+        Self { x, y }
+    }
+
+    /// Move this point by the given offset, saturating at the bounds of the coordinate type.
+    pub fn translate(self, dx: T, dy: T) -> Self {
+        Self {
+            x: self.x.saturating_add(dx),
+            y: self.y.saturating_add(dy),
+        }
+    }
+}
+
+impl<T> From<(T, T)> for Point<T> {
+    fn from((x, y): (T, T)) -> Self {
+        Self { x, y }
+    }
+}
+
+impl<T> From<[T; 2]> for Point<T> {
+    fn from([x, y]: [T; 2]) -> Self {
+        Self { x, y }
+    }
+}
+
+impl<T> From<Point<T>> for (T, T) {
+    fn from(point: Point<T>) -> Self {
+        (point.x, point.y)
+    }
+}
+
+impl<T> From<Point<T>> for [T; 2] {
+    fn from(point: Point<T>) -> Self {
+        [point.x, point.y]
+    }
+}
+
+/// A size.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Size<T = i32> {
+    /// The width.
+    pub width: T,
+
+    /// The height.
+    pub height: T,
+}
+
+impl<T: Coordinate> Size<T> {
+    /// Creates a new size.
+    pub fn new(width: T, height: T) -> Self {
+        Self { width, height }
+    }
+}
+
+impl<T> From<(T, T)> for Size<T> {
+    fn from((width, height): (T, T)) -> Self {
+        Self { width, height }
+    }
+}
+
+impl<T> From<[T; 2]> for Size<T> {
+    fn from([width, height]: [T; 2]) -> Self {
+        Self { width, height }
+    }
+}
+
+impl<T> From<Size<T>> for (T, T) {
+    fn from(size: Size<T>) -> Self {
+        (size.width, size.height)
+    }
+}
+
+impl<T> From<Size<T>> for [T; 2] {
+    fn from(size: Size<T>) -> Self {
+        [size.width, size.height]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Point, Size};
+
+    #[test]
+    fn conversions() {
+        assert_eq!(Point::from((1, 2)), Point::new(1, 2));
+        assert_eq!(Point::from([1, 2]), Point::new(1, 2));
+        assert_eq!(<(i32, i32)>::from(Point::new(1, 2)), (1, 2));
+        assert_eq!(Size::from([3, 4]), Size::new(3, 4));
+        assert_eq!(<[i32; 2]>::from(Size::new(3, 4)), [3, 4]);
+    }
+}
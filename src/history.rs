@@ -0,0 +1,544 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later OR MPL-2.0
+// This file is a part of `windowless`.
+//
+// `windowless` is free software: you can redistribute it and/or modify it under the terms of
+// either:
+//
+// * GNU Lesser General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+// * Mozilla Public License as published by the Mozilla Foundation, version 2.
+//
+// `windowless` is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+// See the GNU Lesser General Public License or the Mozilla Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License and the Mozilla
+// Public License along with `windowless`. If not, see <https://www.gnu.org/licenses/> or
+// <https://www.mozilla.org/en-US/MPL/2.0/>.
+
+//! Undo and redo for changes to the window table.
+
+use crate::{
+    ConfigureError, Coordinate, InsertError, Point, Rectangle, Window, WindowKey, WindowTable,
+};
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+/// A window table that records changes so they can be undone and redone.
+///
+/// Windows that are restored by undoing their removal are given new keys by the table. The
+/// history keeps track of this, so the keys returned by [`History::insert`] can always be used
+/// with the history. Use [`History::resolve`] to get the key of a window in the table itself.
+#[derive(Debug, Default)]
+pub struct History<T: Coordinate = i32> {
+    /// The table being changed.
+    table: WindowTable<T>,
+
+    /// The changes that undo the most recent changes, most recent last.
+    undo: Vec<Edit<T>>,
+
+    /// The changes that redo the most recently undone changes, most recent last.
+    redo: Vec<Edit<T>>,
+
+    /// The current key of each window that has been restored.
+    live: BTreeMap<WindowKey, WindowKey>,
+
+    /// The original key of each window that has been restored.
+    original: BTreeMap<WindowKey, WindowKey>,
+}
+
+/// A change that can be applied to the table.
+///
+/// Keys are the original keys of the windows.
+#[derive(Debug)]
+enum Edit<T: Coordinate> {
+    /// Remove a window.
+    Remove(WindowKey),
+
+    /// Restore a removed window.
+    Restore(Saved<T>),
+
+    /// Replace the entire table.
+    Replace(Box<WindowTable<T>>),
+
+    /// Put windows back at the given rectangles and mapping states, without applying gravity.
+    Configure {
+        rects: Vec<(WindowKey, Rectangle<T>)>,
+        mapped: Vec<(WindowKey, bool)>,
+    },
+
+    /// Restack the windows into the given order.
+    Restack(Vec<WindowKey>),
+}
+
+/// A removed window.
+#[derive(Debug)]
+struct Saved<T: Coordinate> {
+    /// The original key of the window.
+    key: WindowKey,
+
+    /// The state of the window.
+    window: Window<T>,
+
+    /// The window that was stacked directly beneath it.
+    below: Option<WindowKey>,
+}
+
+impl<T: Coordinate> History<T> {
+    /// Start recording the changes made to a table.
+    pub fn new(table: WindowTable<T>) -> Self {
+        Self {
+            table,
+            undo: Vec::new(),
+            redo: Vec::new(),
+            live: BTreeMap::new(),
+            original: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the table.
+    pub fn table(&self) -> &WindowTable<T> {
+        &self.table
+    }
+
+    /// Stop recording changes, returning the table.
+    pub fn into_inner(self) -> WindowTable<T> {
+        self.table
+    }
+
+    /// Get the key in the table of a window known to the history.
+    pub fn resolve(&self, key: WindowKey) -> WindowKey {
+        self.live.get(&key).copied().unwrap_or(key)
+    }
+
+    /// Tell if there are changes to undo.
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    /// Tell if there are undone changes to redo.
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Undo the most recent change.
+    ///
+    /// Returns `false` if there was nothing to undo.
+    pub fn undo(&mut self) -> bool {
+        match self.undo.pop() {
+            Some(edit) => {
+                let inverse = self.apply(edit);
+                self.redo.push(inverse);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Redo the most recently undone change.
+    ///
+    /// Returns `false` if there was nothing to redo.
+    pub fn redo(&mut self) -> bool {
+        match self.redo.pop() {
+            Some(edit) => {
+                let inverse = self.apply(edit);
+                self.undo.push(inverse);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Insert a new window.
+    ///
+    /// See [`WindowTable::insert`].
+    pub fn insert(&mut self, rect: Rectangle<T>) -> Result<WindowKey, InsertError> {
+        let key = self.table.insert(rect)?;
+        self.record(Edit::Remove(key));
+        Ok(key)
+    }
+
+    /// Remove a window.
+    ///
+    /// See [`WindowTable::remove`].
+    pub fn remove(&mut self, key: WindowKey) -> Option<Rectangle<T>> {
+        let rect = self.table.windows.get(self.resolve(key).0)?.rect;
+        let inverse = self.apply(Edit::Remove(key));
+        self.record(inverse);
+        Some(rect)
+    }
+
+    /// Change the rectangle of a window.
+    ///
+    /// See [`WindowTable::resize_window`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn resize_window(
+        &mut self,
+        key: WindowKey,
+        rect: Rectangle<T>,
+    ) -> Result<Rectangle<T>, ConfigureError> {
+        let live = self.resolve(key);
+        let before = self.mapping(live);
+        let start = self.table.configure_events.len();
+
+        let rect = self.table.resize_window(live, rect)?;
+        let inverse = self.configured(start, before);
+        self.record(inverse);
+        Ok(rect)
+    }
+
+    /// Move a window so that its top-left corner is at the given position.
+    ///
+    /// See [`WindowTable::move_window`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn move_window(
+        &mut self,
+        key: WindowKey,
+        position: impl Into<Point<T>>,
+    ) -> Result<Rectangle<T>, ConfigureError> {
+        let rect = self.table.rect(self.resolve(key));
+        self.resize_window(
+            key,
+            Rectangle::from_origin_size(position.into(), rect.size()),
+        )
+    }
+
+    /// Raise a window, along with its descendants, to the top of the stack.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn raise(&mut self, key: WindowKey) {
+        let order = self.order();
+        self.table.raise(self.resolve(key));
+        self.record(Edit::Restack(order));
+    }
+
+    /// Lower a window, along with its descendants, to the bottom of the stack.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn lower(&mut self, key: WindowKey) {
+        let order = self.order();
+        self.table.lower(self.resolve(key));
+        self.record(Edit::Restack(order));
+    }
+
+    /// Get whether a window and each of its descendants are mapped.
+    fn mapping(&self, key: WindowKey) -> Vec<(WindowKey, bool)> {
+        self.table
+            .traverse(key, crate::Order::DepthFirst)
+            .map(|window| (window, self.table.is_mapped(window)))
+            .collect()
+    }
+
+    /// Build the change that puts back the windows configured since the configure event at
+    /// `start`, along with the mapping states in `before`.
+    fn configured(&self, start: usize, before: Vec<(WindowKey, bool)>) -> Edit<T> {
+        // Gravity may move any of the descendants, so restore every window with an event.
+        let mut rects: Vec<(WindowKey, Rectangle<T>)> = Vec::new();
+        for event in &self.table.configure_events[start..] {
+            let window = self.original(event.window);
+            if rects.iter().all(|&(key, _)| key != window) {
+                rects.push((window, event.old));
+            }
+        }
+
+        let mapped = before
+            .into_iter()
+            .filter(|&(window, mapped)| {
+                self.table.windows.contains_key(window.0) && self.table.is_mapped(window) != mapped
+            })
+            .map(|(window, mapped)| (self.original(window), mapped))
+            .collect();
+
+        Edit::Configure { rects, mapped }
+    }
+
+    /// Record the change that undoes a new change.
+    fn record(&mut self, inverse: Edit<T>) {
+        self.undo.push(inverse);
+        self.redo.clear();
+    }
+
+    /// Get the original key of a window in the table.
+    fn original(&self, key: WindowKey) -> WindowKey {
+        self.original.get(&key).copied().unwrap_or(key)
+    }
+
+    /// Get the stacking order of the table, using original keys.
+    fn order(&self) -> Vec<WindowKey> {
+        self.table
+            .stacking_order()
+            .into_iter()
+            .map(|key| self.original(key))
+            .collect()
+    }
+
+    /// Apply a change to the table, returning the change that undoes it.
+    fn apply(&mut self, edit: Edit<T>) -> Edit<T> {
+        match edit {
+            Edit::Remove(key) => {
+                let live = self.resolve(key);
+
+                if self.table.root == Some(live) {
+                    let before = self.table.clone();
+                    self.table.remove(live);
+                    return Edit::Replace(Box::new(before));
+                }
+
+                let order = self.table.stacking_order();
+                let below = order
+                    .iter()
+                    .position(|&window| window == live)
+                    .and_then(|i| i.checked_sub(1))
+                    .map(|i| self.original(order[i]));
+                let window = self.table.windows[live.0].clone();
+
+                self.table.remove(live);
+                Edit::Restore(Saved { key, window, below })
+            }
+
+            Edit::Restore(Saved { key, window, below }) => {
+                // The window fit before it was removed, so the overlap policy is not consulted.
+                let live = self.table.insert_unchecked(window.rect, None);
+                self.live.insert(key, live);
+                self.original.insert(live, key);
+
+                // Bring back the state of the window, keeping the links made by inserting it.
+                let current = &mut self.table.windows[live.0];
+                let links = (
+                    current.z,
+                    core::mem::take(&mut current.parents),
+                    core::mem::take(&mut current.children),
+                );
+                *current = window;
+                (current.z, current.parents, current.children) = links;
+
+                // Put it back where it was in the stacking order.
+                let mut order = self.table.stacking_order();
+                order.retain(|&window| window != live);
+                let position = match below.map(|below| self.resolve(below)) {
+                    Some(below) => order
+                        .iter()
+                        .position(|&window| window == below)
+                        .map_or(order.len(), |i| i + 1),
+                    None => 0,
+                };
+                order.insert(position, live);
+                self.table.relink(&order);
+
+                let window = &self.table.windows[live.0];
+                let visual = window.visual_rect();
+                if window.input_rect() != window.rect {
+                    self.table.input_margins.insert(live);
+                }
+                if visual != window.rect && self.table.is_viewable(live) {
+                    self.table.add_damage_rect(visual);
+                }
+
+                Edit::Remove(key)
+            }
+
+            Edit::Replace(mut table) => {
+                // Keep the same observers and damage sink.
+                table.observers = core::mem::take(&mut self.table.observers);
+                table.damage_sink = core::mem::take(&mut self.table.damage_sink);
+                let before = core::mem::replace(&mut self.table, *table);
+                if let Some(root) = self.table.root {
+                    let rect = self.table.windows[root.0].rect;
+                    self.table.add_damage_rect(rect);
+                }
+
+                Edit::Replace(Box::new(before))
+            }
+
+            Edit::Configure { rects, mapped } => {
+                let start = self.table.configure_events.len();
+                let before = mapped
+                    .iter()
+                    .map(|&(window, _)| self.resolve(window))
+                    .filter(|window| self.table.windows.contains_key(window.0))
+                    .map(|window| (window, self.table.is_mapped(window)))
+                    .collect();
+
+                let rects = rects
+                    .into_iter()
+                    .map(|(window, rect)| (self.resolve(window), rect))
+                    .filter(|(window, _)| self.table.windows.contains_key(window.0))
+                    .collect::<Vec<_>>();
+                let mapped = mapped
+                    .into_iter()
+                    .map(|(window, mapped)| (self.resolve(window), mapped))
+                    .filter(|(window, _)| self.table.windows.contains_key(window.0))
+                    .collect::<Vec<_>>();
+
+                let mut transaction = self.table.transaction();
+                for (window, rect) in rects {
+                    transaction.place(window, rect);
+                }
+                for (window, mapped) in mapped {
+                    if mapped {
+                        transaction.map(window);
+                    } else {
+                        transaction.unmap(window);
+                    }
+                }
+                transaction.commit();
+
+                self.configured(start, before)
+            }
+
+            Edit::Restack(order) => {
+                let before = self.order();
+                let old = self.table.snapshot();
+
+                let order = order
+                    .into_iter()
+                    .map(|key| self.resolve(key))
+                    .filter(|key| self.table.windows.contains_key(key.0))
+                    .collect::<Vec<_>>();
+                self.table.relink(&order);
+
+                let damage = old.diff(&self.table.snapshot()).damage;
+                self.table.add_damage(&damage);
+
+                Edit::Restack(before)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::History;
+    use crate::{Gravity, OverlapPolicy, Rectangle, WindowTable};
+
+    #[test]
+    fn undo_redo() {
+        let mut history = History::new(WindowTable::new());
+
+        let root = history.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let a = history.insert(Rectangle::new(0, 0, 50, 50)).unwrap();
+        let b = history.insert(Rectangle::new(25, 25, 75, 75)).unwrap();
+        history.raise(a);
+        history.move_window(b, (30, 30)).unwrap();
+        assert_eq!(history.table().window_at((40, 40)), Some(a));
+
+        assert!(history.undo());
+        assert_eq!(history.table().rect(b), Rectangle::new(25, 25, 75, 75));
+        assert!(history.undo());
+        assert_eq!(history.table().window_at((40, 40)), Some(b));
+
+        // Removing and restoring a window gives it a new key in the table.
+        history.table.set_opacity(b, 128);
+        history.remove(b);
+        assert!(history.undo());
+        let restored = history.resolve(b);
+        assert_ne!(restored, b);
+        assert_eq!(history.table().opacity(restored), 128);
+        assert_eq!(history.table().window_at((40, 40)), Some(restored));
+
+        assert!(history.redo());
+        assert!(!history.table().contains_key(restored));
+        assert!(history.undo());
+
+        // The redo stack is cleared by new changes.
+        history.lower(b);
+        assert!(!history.can_redo());
+        assert_eq!(history.table().window_at((40, 40)), Some(a));
+
+        history.remove(root);
+        assert!(history.table().is_empty());
+        assert!(history.undo());
+        assert_eq!(history.table().len(), 3);
+        assert_eq!(history.table().root(), Some(root));
+    }
+
+    #[test]
+    fn undo_gravity() {
+        let mut history = History::new(WindowTable::new());
+
+        history.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let a = history.insert(Rectangle::new(0, 0, 50, 50)).unwrap();
+        let b = history.insert(Rectangle::new(10, 10, 20, 20)).unwrap();
+        history.table.set_gravity(b, Gravity::Unmap);
+
+        history
+            .resize_window(a, Rectangle::new(0, 0, 60, 60))
+            .unwrap();
+        assert!(!history.table().is_mapped(b));
+
+        assert!(history.undo());
+        assert_eq!(history.table().rect(a), Rectangle::new(0, 0, 50, 50));
+        assert!(history.table().is_mapped(b));
+
+        assert!(history.redo());
+        assert_eq!(history.table().rect(a), Rectangle::new(0, 0, 60, 60));
+        assert!(!history.table().is_mapped(b));
+
+        assert!(history.undo());
+        while history.undo() {}
+        assert!(history.table().is_empty());
+    }
+
+    #[test]
+    fn undo_move_restores_children() {
+        let mut history = History::new(WindowTable::new());
+
+        history.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let a = history.insert(Rectangle::new(0, 0, 20, 20)).unwrap();
+        let b = history.insert(Rectangle::new(5, 5, 10, 10)).unwrap();
+        let c = history.insert(Rectangle::new(60, 60, 70, 70)).unwrap();
+
+        // `c` becomes a child of `a` at its new position.
+        history.move_window(a, (55, 55)).unwrap();
+        assert_eq!(history.table().rect(b), Rectangle::new(60, 60, 65, 65));
+        assert_eq!(history.table().parent(c), Some(a));
+
+        // Moving `a` back would drag `c` along with it, but undoing puts both children back.
+        assert!(history.undo());
+        assert_eq!(history.table().rect(a), Rectangle::new(0, 0, 20, 20));
+        assert_eq!(history.table().rect(b), Rectangle::new(5, 5, 10, 10));
+        assert_eq!(history.table().rect(c), Rectangle::new(60, 60, 70, 70));
+
+        assert!(history.redo());
+        assert_eq!(history.table().rect(a), Rectangle::new(55, 55, 75, 75));
+        assert_eq!(history.table().rect(b), Rectangle::new(60, 60, 65, 65));
+        assert_eq!(history.table().rect(c), Rectangle::new(60, 60, 70, 70));
+        assert_eq!(history.table().validate(), Ok(()));
+    }
+
+    #[test]
+    fn undo_remove_ignores_overlap_policy() {
+        let mut table = WindowTable::new();
+        table.set_overlap_policy(OverlapPolicy::Reject);
+        let mut history = History::new(table);
+
+        history.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let a = history.insert(Rectangle::new(0, 0, 20, 20)).unwrap();
+        let b = history.insert(Rectangle::new(50, 50, 70, 70)).unwrap();
+
+        // Moving a window is not checked against the policy, so `b` can partly overlap `a`.
+        history.move_window(b, (10, 10)).unwrap();
+        history.remove(b);
+
+        // Bringing it back must not be rejected for overlapping `a`.
+        assert!(history.undo());
+        let restored = history.resolve(b);
+        assert_eq!(
+            history.table().rect(restored),
+            Rectangle::new(10, 10, 30, 30)
+        );
+        assert_eq!(history.table().rect(a), Rectangle::new(0, 0, 20, 20));
+        assert_eq!(history.table().validate(), Ok(()));
+    }
+}
@@ -0,0 +1,277 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later OR MPL-2.0
+// This file is a part of `windowless`.
+//
+// `windowless` is free software: you can redistribute it and/or modify it under the terms of
+// either:
+//
+// * GNU Lesser General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+// * Mozilla Public License as published by the Mozilla Foundation, version 2.
+//
+// `windowless` is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+// See the GNU Lesser General Public License or the Mozilla Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License and the Mozilla
+// Public License along with `windowless`. If not, see <https://www.gnu.org/licenses/> or
+// <https://www.mozilla.org/en-US/MPL/2.0/>.
+
+//! A spatial index over window rectangles.
+
+use crate::{Coordinate, Point, Rectangle, WindowKey};
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use smallvec::SmallVec;
+
+/// The log2 of the width and height of a grid cell.
+const CELL_SHIFT: u32 = 6;
+
+/// Windows covering more than this many cells are kept in a separate list.
+const MAX_CELLS: i128 = 256;
+
+/// A uniform grid of the windows in the table.
+///
+/// Each cell lists the windows overlapping it, which makes point and rectangle queries
+/// proportional to the number of windows nearby rather than the size of the table.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct SpatialIndex {
+    /// The windows overlapping each cell.
+    ///
+    /// Cells are indexed with 64-bit integers, so that windows far apart on a huge canvas with
+    /// 64-bit coordinates don't end up in the same cells.
+    cells: BTreeMap<(i64, i64), SmallVec<[WindowKey; 4]>>,
+
+    /// Windows that are too large to be stored in the grid.
+    ///
+    /// These are returned by every query.
+    large: SmallVec<[WindowKey; 4]>,
+}
+
+/// The range of cells, inclusive, that a rectangle covers.
+#[derive(Debug, Copy, Clone)]
+struct CellRange {
+    left: i64,
+    top: i64,
+    right: i64,
+    bottom: i64,
+}
+
+impl CellRange {
+    /// Get the cells covered by a rectangle.
+    fn new<T: Coordinate>(rect: Rectangle<T>) -> Option<Self> {
+        if rect.right <= rect.left || rect.bottom <= rect.top {
+            return None;
+        }
+
+        // The right and bottom edges are exclusive.
+        Some(Self {
+            left: rect.left.floor_i64() >> CELL_SHIFT,
+            top: rect.top.floor_i64() >> CELL_SHIFT,
+            right: rect.right.ceil_i64().saturating_sub(1) >> CELL_SHIFT,
+            bottom: rect.bottom.ceil_i64().saturating_sub(1) >> CELL_SHIFT,
+        })
+    }
+
+    /// The number of cells in this range.
+    fn len(&self) -> i128 {
+        (i128::from(self.right) - i128::from(self.left) + 1)
+            * (i128::from(self.bottom) - i128::from(self.top) + 1)
+    }
+
+    /// Iterate over the cells in this range.
+    fn cells(self) -> impl Iterator<Item = (i64, i64)> {
+        (self.top..=self.bottom).flat_map(move |y| (self.left..=self.right).map(move |x| (x, y)))
+    }
+}
+
+impl SpatialIndex {
+    /// Add a window to the index.
+    pub(crate) fn insert<T: Coordinate>(&mut self, key: WindowKey, rect: Rectangle<T>) {
+        let range = match CellRange::new(rect) {
+            Some(range) => range,
+            None => return,
+        };
+
+        if range.len() > MAX_CELLS {
+            self.large.push(key);
+            return;
+        }
+
+        for cell in range.cells() {
+            self.cells.entry(cell).or_default().push(key);
+        }
+    }
+
+    /// Remove a window from the index.
+    ///
+    /// `rect` must be the rectangle that the window was inserted with.
+    pub(crate) fn remove<T: Coordinate>(&mut self, key: WindowKey, rect: Rectangle<T>) {
+        let range = match CellRange::new(rect) {
+            Some(range) => range,
+            None => return,
+        };
+
+        if range.len() > MAX_CELLS {
+            self.large.retain(|window| *window != key);
+            return;
+        }
+
+        for cell in range.cells() {
+            if let Some(windows) = self.cells.get_mut(&cell) {
+                windows.retain(|window| *window != key);
+                if windows.is_empty() {
+                    self.cells.remove(&cell);
+                }
+            }
+        }
+    }
+
+    /// Estimate the number of bytes allocated by the index.
+    pub(crate) fn heap_size(&self) -> usize {
+        let cell = core::mem::size_of::<((i64, i64), SmallVec<[WindowKey; 4]>)>();
+        let spilled = |keys: &SmallVec<[WindowKey; 4]>| {
+            if keys.spilled() {
+                keys.capacity() * core::mem::size_of::<WindowKey>()
+            } else {
+                0
+            }
+        };
+
+        self.cells.len() * cell
+            + self.cells.values().map(spilled).sum::<usize>()
+            + spilled(&self.large)
+    }
+
+    /// Empty the index.
+    pub(crate) fn clear(&mut self) {
+        self.cells.clear();
+        self.large.clear();
+    }
+
+    /// Get the windows that may contain a point.
+    pub(crate) fn query_point<T: Coordinate>(
+        &self,
+        Point { x, y }: Point<T>,
+    ) -> impl Iterator<Item = WindowKey> + '_ {
+        let cell = (x.floor_i64() >> CELL_SHIFT, y.floor_i64() >> CELL_SHIFT);
+
+        self.cells
+            .get(&cell)
+            .into_iter()
+            .flatten()
+            .chain(self.large.iter())
+            .copied()
+    }
+
+    /// Get the windows that may intersect a rectangle.
+    ///
+    /// Windows spanning several cells are only returned once.
+    pub(crate) fn query_rect<T: Coordinate>(&self, rect: Rectangle<T>) -> SmallVec<[WindowKey; 8]> {
+        let mut windows = SmallVec::<[WindowKey; 8]>::new();
+        self.visit_rect(rect, |key| windows.push(key));
+
+        windows.sort_unstable();
+        windows.dedup();
+        windows
+    }
+
+    /// Get the windows that may intersect a rectangle, reusing a buffer.
+    ///
+    /// The buffer is cleared first. Windows spanning several cells are only returned once.
+    pub(crate) fn query_rect_into<T: Coordinate>(
+        &self,
+        rect: Rectangle<T>,
+        windows: &mut Vec<WindowKey>,
+    ) {
+        windows.clear();
+        self.visit_rect(rect, |key| windows.push(key));
+
+        windows.sort_unstable();
+        windows.dedup();
+    }
+
+    /// Visit the windows in the cells covered by a rectangle.
+    ///
+    /// Windows spanning several cells are visited once per cell.
+    fn visit_rect<T: Coordinate>(&self, rect: Rectangle<T>, mut f: impl FnMut(WindowKey)) {
+        let range = match CellRange::new(rect) {
+            Some(range) => range,
+            None => return,
+        };
+
+        if range.len() > self.cells.len() as i128 {
+            // It's cheaper to look at every cell that exists.
+            for (&(x, y), keys) in &self.cells {
+                if x >= range.left && x <= range.right && y >= range.top && y <= range.bottom {
+                    keys.iter().copied().for_each(&mut f);
+                }
+            }
+        } else {
+            for cell in range.cells() {
+                if let Some(keys) = self.cells.get(&cell) {
+                    keys.iter().copied().for_each(&mut f);
+                }
+            }
+        }
+
+        self.large.iter().copied().for_each(f);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SpatialIndex;
+    use crate::{Point, Rectangle, WindowTable};
+
+    #[test]
+    fn query() {
+        let mut table = WindowTable::new();
+        table.insert(Rectangle::new(0, 0, 1000, 1000)).unwrap();
+        let a = table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let b = table.insert(Rectangle::new(200, 200, 300, 300)).unwrap();
+        let huge = table
+            .insert(Rectangle::new(-10000, -10000, 10000, 10000))
+            .unwrap();
+
+        let mut index = SpatialIndex::default();
+        index.insert(a, Rectangle::new(0, 0, 100, 100));
+        index.insert(b, Rectangle::new(200, 200, 300, 300));
+        index.insert(huge, Rectangle::new(-10000, -10000, 10000, 10000));
+
+        assert!(index.query_point(Point::new(10, 10)).eq([a, huge]));
+        assert_eq!(&index.query_rect(Rectangle::new(50, 50, 250, 250))[..], {
+            let mut keys = [a, b, huge];
+            keys.sort_unstable();
+            keys
+        });
+
+        index.remove(a, Rectangle::new(0, 0, 100, 100));
+        index.remove(huge, Rectangle::new(-10000, -10000, 10000, 10000));
+        assert_eq!(index.query_point(Point::new(10, 10)).count(), 0);
+    }
+
+    #[test]
+    fn huge_coordinates() {
+        let far = 1i64 << 40;
+        let mut table = WindowTable::<i64>::default();
+        table
+            .insert(Rectangle::new(-far * 4, -far * 4, far * 4, far * 4))
+            .unwrap();
+        let a = table
+            .insert(Rectangle::new(far, far, far + 100, far + 100))
+            .unwrap();
+        let b = table
+            .insert(Rectangle::new(far * 2, far, far * 2 + 100, far + 100))
+            .unwrap();
+
+        // Windows beyond the range of `i32` still get cells of their own.
+        let mut index = SpatialIndex::default();
+        index.insert(a, table.rect(a));
+        index.insert(b, table.rect(b));
+        assert!(index.query_point(Point::new(far + 10, far + 10)).eq([a]));
+        assert!(index
+            .query_point(Point::new(far * 2 + 10, far + 10))
+            .eq([b]));
+        assert_eq!(table.window_at((far * 2 + 10, far + 10)), Some(b));
+    }
+}
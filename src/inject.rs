@@ -0,0 +1,290 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later OR MPL-2.0
+// This file is a part of `windowless`.
+//
+// `windowless` is free software: you can redistribute it and/or modify it under the terms of
+// either:
+//
+// * GNU Lesser General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+// * Mozilla Public License as published by the Mozilla Foundation, version 2.
+//
+// `windowless` is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+// See the GNU Lesser General Public License or the Mozilla Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License and the Mozilla
+// Public License along with `windowless`. If not, see <https://www.gnu.org/licenses/> or
+// <https://www.mozilla.org/en-US/MPL/2.0/>.
+
+//! Injecting fabricated input events, for testing user interfaces.
+
+use crate::{
+    Button, ButtonEvent, Coordinate, CursorEvent, CursorState, FocusEvent, FocusState, KeyRoute,
+    ModalBlocked, Point, ScrollEvent, WindowKey, WindowTable,
+};
+use smallvec::SmallVec;
+
+/// The pointer and keyboard state that input events are routed through.
+///
+/// Real input is fed to the [`CursorState`] and [`FocusState`] directly. Fabricated input can
+/// be aimed at a window with [`inject_event`](Self::inject_event), which goes through the same
+/// routing.
+#[derive(Debug, Default)]
+pub struct InputState<T = i32> {
+    /// The state of the pointer.
+    cursor: CursorState<T>,
+
+    /// The state of the keyboard focus.
+    focus: FocusState,
+}
+
+/// A fabricated input event, aimed at a window by [`InputState::inject_event`].
+///
+/// Pointer positions are relative to the top-left corner of the window the event is aimed at.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum InputEvent<T = i32, E = ()> {
+    /// The pointer moved.
+    Motion(Point<T>),
+
+    /// A button was pressed or released.
+    Button {
+        /// The position of the pointer.
+        position: Point<T>,
+
+        /// The button.
+        button: Button,
+
+        /// Whether the button was pressed, rather than released.
+        pressed: bool,
+    },
+
+    /// The pointer scrolled.
+    Scroll {
+        /// The position of the pointer.
+        position: Point<T>,
+
+        /// The distance scrolled along each axis.
+        delta: Point<T>,
+    },
+
+    /// A key event, which is routed through the keyboard focus.
+    Key(E),
+}
+
+/// An injected event after it has been routed.
+#[derive(Debug, Clone)]
+pub enum RoutedEvent<T = i32, E = ()> {
+    /// The pointer moved over a window.
+    Motion {
+        /// The window that receives the event.
+        window: WindowKey,
+
+        /// The position of the pointer, relative to the window's top-left corner.
+        position: Point<T>,
+    },
+
+    /// A button event.
+    Button(ButtonEvent<T>),
+
+    /// A scroll event.
+    Scroll(ScrollEvent<T>),
+
+    /// A key event.
+    Key(KeyRoute<E>),
+}
+
+/// The events generated by injecting an input event.
+///
+/// Returned by [`InputState::inject_event`].
+#[derive(Debug, Clone)]
+pub struct Injected<T = i32, E = ()> {
+    /// The crossing events generated by moving the pointer to the event's position, or by
+    /// releasing an implicit grab.
+    pub crossing: SmallVec<[CursorEvent; 4]>,
+
+    /// The focus events generated by focusing the window that a key event is aimed at.
+    pub focus: SmallVec<[FocusEvent; 2]>,
+
+    /// The routed event, or `None` if no window receives it.
+    pub event: Option<RoutedEvent<T, E>>,
+}
+
+impl<T: Coordinate> InputState<T> {
+    /// Creates a new input state, with the pointer over no window and no window focused.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the state of the pointer.
+    pub fn cursor(&self) -> &CursorState<T> {
+        &self.cursor
+    }
+
+    /// Returns the state of the pointer, for feeding it real input.
+    pub fn cursor_mut(&mut self) -> &mut CursorState<T> {
+        &mut self.cursor
+    }
+
+    /// Returns the state of the keyboard focus.
+    pub fn focus(&self) -> &FocusState {
+        &self.focus
+    }
+
+    /// Returns the state of the keyboard focus, for feeding it real input.
+    pub fn focus_mut(&mut self) -> &mut FocusState {
+        &mut self.focus
+    }
+
+    /// Inject a fabricated event aimed at a window, routing it as if it came from real input.
+    ///
+    /// Pointer events first move the pointer to their position within the window, then are
+    /// routed from there, so grabs, event masks and windows stacked on top of the target all
+    /// apply. Key events first focus the window, then are routed to it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a key event or a button press is aimed at a window blocked by a
+    /// [modal window](WindowTable::set_modal).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn inject_event<E, const N: usize, const R: usize>(
+        &mut self,
+        table: &WindowTable<T, N, R>,
+        target: WindowKey,
+        event: InputEvent<T, E>,
+    ) -> Result<Injected<T, E>, ModalBlocked> {
+        let origin = table.rect(target).origin();
+        let absolute = |position: Point<T>| {
+            Point::new(
+                origin.x.saturating_add(position.x),
+                origin.y.saturating_add(position.y),
+            )
+        };
+
+        let mut injected = Injected {
+            crossing: SmallVec::new(),
+            focus: SmallVec::new(),
+            event: None,
+        };
+
+        match event {
+            InputEvent::Motion(position) => {
+                injected.crossing = self.cursor.update(table, absolute(position));
+                injected.event = self.cursor.target().map(|window| {
+                    let rect = table.rect(window);
+                    let position = self.cursor.position();
+                    RoutedEvent::Motion {
+                        window,
+                        position: Point::new(
+                            position.x.saturating_sub(rect.left),
+                            position.y.saturating_sub(rect.top),
+                        ),
+                    }
+                });
+            }
+            InputEvent::Button {
+                position,
+                button,
+                pressed,
+            } => {
+                injected.crossing = self.cursor.update(table, absolute(position));
+                let event = if pressed {
+                    self.cursor.button_down(table, button)?
+                } else {
+                    let (event, crossing) = self.cursor.button_up(table, button);
+                    injected.crossing.extend(crossing);
+                    event
+                };
+                injected.event = event.map(RoutedEvent::Button);
+            }
+            InputEvent::Scroll { position, delta } => {
+                let position = absolute(position);
+                injected.crossing = self.cursor.update(table, position);
+                injected.event = table.route_scroll(position, delta).map(RoutedEvent::Scroll);
+            }
+            InputEvent::Key(event) => {
+                if self.focus.focus() != Some(target) {
+                    injected.focus = self.focus.set_focus(table, Some(target));
+                }
+                injected.event = self
+                    .focus
+                    .route_key_event(table, event)?
+                    .map(RoutedEvent::Key);
+            }
+        }
+
+        Ok(injected)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{InputEvent, InputState, RoutedEvent};
+    use crate::{Button, CrossingDetail, CursorEvent, FocusEvent, Point, Rectangle, WindowTable};
+
+    #[test]
+    fn inject_event() {
+        let mut window_table = WindowTable::new();
+
+        let root = window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let button = window_table.insert(Rectangle::new(10, 10, 30, 30)).unwrap();
+
+        let mut input = InputState::new();
+        let injected = input
+            .inject_event(
+                &window_table,
+                button,
+                InputEvent::<_, ()>::Button {
+                    position: Point::new(5, 5),
+                    button: Button::Left,
+                    pressed: true,
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            &injected.crossing[..],
+            &[
+                CursorEvent::Enter(root, CrossingDetail::NonlinearVirtual),
+                CursorEvent::Enter(button, CrossingDetail::Nonlinear)
+            ]
+        );
+        match injected.event {
+            Some(RoutedEvent::Button(event)) => {
+                assert_eq!(event.window, button);
+                assert_eq!(event.position, Point::new(5, 5));
+                assert!(event.pressed);
+            }
+            event => panic!("unexpected event: {:?}", event),
+        }
+        assert_eq!(input.cursor().grab(), Some(button));
+
+        // Motion in another window still goes to the grabbing window.
+        let injected = input
+            .inject_event(
+                &window_table,
+                root,
+                InputEvent::<_, ()>::Motion(Point::new(50, 50)),
+            )
+            .unwrap();
+        assert!(injected.crossing.is_empty());
+        assert!(matches!(
+            injected.event,
+            Some(RoutedEvent::Motion { window, position })
+                if window == button && position == Point::new(40, 40)
+        ));
+
+        let injected = input
+            .inject_event(&window_table, button, InputEvent::Key('a'))
+            .unwrap();
+        assert_eq!(&injected.focus[..], &[FocusEvent::FocusIn(button)]);
+        match injected.event {
+            Some(RoutedEvent::Key(route)) => {
+                assert_eq!(*route.event(), 'a');
+                assert_eq!(route.path(), [button, root]);
+            }
+            event => panic!("unexpected event: {:?}", event),
+        }
+    }
+}
@@ -0,0 +1,315 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later OR MPL-2.0
+// This file is a part of `windowless`.
+//
+// `windowless` is free software: you can redistribute it and/or modify it under the terms of
+// either:
+//
+// * GNU Lesser General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+// * Mozilla Public License as published by the Mozilla Foundation, version 2.
+//
+// `windowless` is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+// See the GNU Lesser General Public License or the Mozilla Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License and the Mozilla
+// Public License along with `windowless`. If not, see <https://www.gnu.org/licenses/> or
+// <https://www.mozilla.org/en-US/MPL/2.0/>.
+
+//! Moving and resizing windows with the pointer.
+
+use crate::{
+    coord, ConfigureError, Coordinate, HitRegion, Point, Rectangle, WindowKey, WindowTable,
+};
+
+/// An interactive move or resize of a window, driven by the pointer.
+///
+/// Start one with [`WindowTable::begin_move`] or [`WindowTable::begin_resize`], then feed the
+/// position of the pointer to [`update`](Self::update) as it moves. Dropping the session ends
+/// it, leaving the window where it is, while [`cancel`](Self::cancel) puts the window back.
+#[derive(Debug, Clone)]
+pub struct MoveResize<T = i32> {
+    /// The window being moved or resized.
+    window: WindowKey,
+
+    /// The position of the pointer when the session began.
+    start: Point<T>,
+
+    /// The rectangle of the window when the session began.
+    rect: Rectangle<T>,
+
+    /// Whether the pointer drags the left, top, right and bottom edges.
+    edges: [bool; 4],
+
+    /// The distance within which edges snap to the edges around them.
+    snap: Option<T>,
+}
+
+impl<T: Coordinate> MoveResize<T> {
+    /// Returns the window being moved or resized.
+    pub fn window(&self) -> WindowKey {
+        self.window
+    }
+
+    /// Tell if this session moves the window, rather than resizing it.
+    pub fn is_move(&self) -> bool {
+        self.edges == [true; 4]
+    }
+
+    /// Snap the window to the edges around it when within a distance of them.
+    ///
+    /// See [`WindowTable::snap_rect`] for how edges are snapped. Snapping is off by default.
+    pub fn set_snap_threshold(&mut self, threshold: Option<T>) {
+        self.snap = threshold;
+    }
+
+    /// Follow the pointer to a new position, applying the new rectangle to the window.
+    ///
+    /// The dragged edges move along with the pointer and are [snapped](Self::set_snap_threshold)
+    /// to the edges around them. They are then kept within the root window, and the window's
+    /// [size hints](WindowTable::set_size_hints) are applied, keeping the edges that aren't
+    /// dragged in place. The window is damaged like it is by
+    /// [`resize_window`](WindowTable::resize_window). Returns the new rectangle of the window.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window no longer exists.
+    pub fn update<const N: usize, const R: usize>(
+        &mut self,
+        table: &mut WindowTable<T, N, R>,
+        position: impl Into<Point<T>>,
+    ) -> Result<Rectangle<T>, ConfigureError> {
+        let position = position.into();
+        let dx = position.x.saturating_sub(self.start.x);
+        let dy = position.y.saturating_sub(self.start.y);
+        let [left, top, right, bottom] = self.edges;
+
+        let mut rect = self.rect;
+        let shift = |edge: &mut T, dragged: bool, delta: T| {
+            if dragged {
+                *edge = edge.saturating_add(delta);
+            }
+        };
+        shift(&mut rect.left, left, dx);
+        shift(&mut rect.top, top, dy);
+        shift(&mut rect.right, right, dx);
+        shift(&mut rect.bottom, bottom, dy);
+
+        if let Some(threshold) = self.snap {
+            rect = table.snap_rect(self.window, rect, threshold);
+        }
+
+        if let Some(root) = table.root().filter(|&root| root != self.window) {
+            let root = table.rect(root);
+            if self.is_move() {
+                let (dx, dy) = (
+                    push_inside(rect.left, rect.right, root.left, root.right),
+                    push_inside(rect.top, rect.bottom, root.top, root.bottom),
+                );
+                rect = rect.translate(dx, dy);
+            } else {
+                if left {
+                    rect.left = coord::max(rect.left, root.left);
+                }
+                if top {
+                    rect.top = coord::max(rect.top, root.top);
+                }
+                if right {
+                    rect.right = coord::min(rect.right, root.right);
+                }
+                if bottom {
+                    rect.bottom = coord::min(rect.bottom, root.bottom);
+                }
+            }
+        }
+
+        // Don't let the dragged edges cross over the edges that are kept in place.
+        let one = T::from_i32(1);
+        if left && !right {
+            rect.left = coord::min(rect.left, rect.right.saturating_sub(one));
+        } else if right && !left {
+            rect.right = coord::max(rect.right, rect.left.saturating_add(one));
+        }
+        if top && !bottom {
+            rect.top = coord::min(rect.top, rect.bottom.saturating_sub(one));
+        } else if bottom && !top {
+            rect.bottom = coord::max(rect.bottom, rect.top.saturating_add(one));
+        }
+
+        // Apply the size hints here rather than in `resize_window`, so that the edges that are
+        // dragged are the ones that give way.
+        let size = table.size_hints(self.window).constrain(rect.size());
+        if left && !right {
+            rect.left = rect.right.saturating_sub(size.width);
+        } else {
+            rect.right = rect.left.saturating_add(size.width);
+        }
+        if top && !bottom {
+            rect.top = rect.bottom.saturating_sub(size.height);
+        } else {
+            rect.bottom = rect.top.saturating_add(size.height);
+        }
+
+        table.resize_window(self.window, rect)
+    }
+
+    /// Cancel the session, putting the window back where it was when the session began.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window no longer exists.
+    pub fn cancel<const N: usize, const R: usize>(
+        self,
+        table: &mut WindowTable<T, N, R>,
+    ) -> Result<Rectangle<T>, ConfigureError> {
+        table.resize_window(self.window, self.rect)
+    }
+}
+
+impl<T: Coordinate, const N: usize, const R: usize> WindowTable<T, N, R> {
+    /// Begin moving a window with the pointer, from the pointer's current position.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn begin_move(&self, key: WindowKey, position: impl Into<Point<T>>) -> MoveResize<T> {
+        MoveResize {
+            window: key,
+            start: position.into(),
+            rect: self.windows[key.0].rect,
+            edges: [true; 4],
+            snap: None,
+        }
+    }
+
+    /// Begin resizing a window with the pointer, by dragging an edge or a corner.
+    ///
+    /// The region is usually found with [`hit_test_frame`](Self::hit_test_frame). Returns
+    /// `None` if the region is not an edge or a corner.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn begin_resize(
+        &self,
+        key: WindowKey,
+        region: HitRegion,
+        position: impl Into<Point<T>>,
+    ) -> Option<MoveResize<T>> {
+        let edges = match region {
+            HitRegion::EdgeLeft => [true, false, false, false],
+            HitRegion::EdgeTop => [false, true, false, false],
+            HitRegion::EdgeRight => [false, false, true, false],
+            HitRegion::EdgeBottom => [false, false, false, true],
+            HitRegion::CornerNW => [true, true, false, false],
+            HitRegion::CornerNE => [false, true, true, false],
+            HitRegion::CornerSW => [true, false, false, true],
+            HitRegion::CornerSE => [false, false, true, true],
+            HitRegion::Outside | HitRegion::Interior | HitRegion::TitleBar => return None,
+        };
+
+        Some(MoveResize {
+            edges,
+            ..self.begin_move(key, position)
+        })
+    }
+}
+
+/// Find how far to move a span to put it inside of another, if it fits.
+fn push_inside<T: Coordinate>(low: T, high: T, outer_low: T, outer_high: T) -> T {
+    if low < outer_low {
+        outer_low.saturating_sub(low)
+    } else if high > outer_high {
+        coord::max(
+            outer_high.saturating_sub(high),
+            outer_low.saturating_sub(low),
+        )
+    } else {
+        T::ZERO
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{HitRegion, Rectangle, Size, SizeHints, WindowTable};
+
+    #[test]
+    fn move_window() {
+        let mut window_table = WindowTable::new();
+
+        window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let a = window_table.insert(Rectangle::new(10, 10, 30, 30)).unwrap();
+        let b = window_table.insert(Rectangle::new(50, 10, 70, 30)).unwrap();
+
+        let mut session = window_table.begin_move(b, (55, 15));
+        assert!(session.is_move());
+        assert_eq!(
+            session.update(&mut window_table, (60, 25)).unwrap(),
+            Rectangle::new(55, 20, 75, 40)
+        );
+        assert!(!window_table.take_damage().is_empty());
+
+        // The window is kept on the root.
+        assert_eq!(
+            session.update(&mut window_table, (90, -10)).unwrap(),
+            Rectangle::new(80, 0, 100, 20)
+        );
+
+        session.set_snap_threshold(Some(5));
+        assert_eq!(
+            session.update(&mut window_table, (38, 16)).unwrap(),
+            Rectangle::new(30, 10, 50, 30)
+        );
+        assert_eq!(window_table.rect(a), Rectangle::new(10, 10, 30, 30));
+
+        session.cancel(&mut window_table).unwrap();
+        assert_eq!(window_table.rect(b), Rectangle::new(50, 10, 70, 30));
+    }
+
+    #[test]
+    fn resize_window() {
+        let mut window_table = WindowTable::new();
+
+        window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let a = window_table.insert(Rectangle::new(40, 40, 60, 60)).unwrap();
+        window_table.set_size_hints(
+            a,
+            SizeHints {
+                min_size: Some(Size::new(10, 10)),
+                ..SizeHints::default()
+            },
+        );
+
+        assert!(window_table
+            .begin_resize(a, HitRegion::Interior, (50, 50))
+            .is_none());
+
+        let mut session = window_table
+            .begin_resize(a, HitRegion::CornerNW, (40, 40))
+            .unwrap();
+        assert!(!session.is_move());
+        assert_eq!(
+            session.update(&mut window_table, (30, 35)).unwrap(),
+            Rectangle::new(30, 35, 60, 60)
+        );
+
+        // The size hints hold the dragged edges back, and the root bounds them.
+        assert_eq!(
+            session.update(&mut window_table, (80, 80)).unwrap(),
+            Rectangle::new(50, 50, 60, 60)
+        );
+        assert_eq!(
+            session.update(&mut window_table, (-20, 10)).unwrap(),
+            Rectangle::new(0, 10, 60, 60)
+        );
+
+        let mut session = window_table
+            .begin_resize(a, HitRegion::EdgeRight, (60, 30))
+            .unwrap();
+        session.set_snap_threshold(Some(5));
+        assert_eq!(
+            session.update(&mut window_table, (97, 70)).unwrap(),
+            Rectangle::new(0, 10, 100, 60)
+        );
+    }
+}
@@ -0,0 +1,329 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later OR MPL-2.0
+// This file is a part of `windowless`.
+//
+// `windowless` is free software: you can redistribute it and/or modify it under the terms of
+// either:
+//
+// * GNU Lesser General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+// * Mozilla Public License as published by the Mozilla Foundation, version 2.
+//
+// `windowless` is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+// See the GNU Lesser General Public License or the Mozilla Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License and the Mozilla
+// Public License along with `windowless`. If not, see <https://www.gnu.org/licenses/> or
+// <https://www.mozilla.org/en-US/MPL/2.0/>.
+
+//! Tiling windows with split containers.
+
+use crate::{ConfigureError, Coordinate, InsertError, Rectangle, Region, WindowKey, WindowTable};
+use alloc::vec::Vec;
+
+/// The direction that a split lays out its children in.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Axis {
+    /// Side by side, from left to right.
+    Horizontal,
+
+    /// Stacked, from top to bottom.
+    Vertical,
+}
+
+/// An entry in a split.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Tile {
+    /// A window, which is given the whole of its space.
+    Window(WindowKey),
+
+    /// A nested split, which divides its space between its own children.
+    Split(Split),
+}
+
+impl From<WindowKey> for Tile {
+    fn from(key: WindowKey) -> Self {
+        Tile::Window(key)
+    }
+}
+
+impl From<Split> for Tile {
+    fn from(split: Split) -> Self {
+        Tile::Split(split)
+    }
+}
+
+/// A container that divides a rectangle between its children along an axis.
+///
+/// Each child gets a share of the space in proportion to its weight. Splits nest, so any tiled
+/// layout can be built out of them, and [`apply`](Self::apply) moves and resizes the windows in
+/// a table to match.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Split {
+    /// The direction the children are laid out in.
+    axis: Axis,
+
+    /// The children, along with their weights.
+    children: Vec<(Tile, f64)>,
+}
+
+impl Split {
+    /// Creates an empty split.
+    pub fn new(axis: Axis) -> Self {
+        Self {
+            axis,
+            children: Vec::new(),
+        }
+    }
+
+    /// Returns the direction the children are laid out in.
+    pub fn axis(&self) -> Axis {
+        self.axis
+    }
+
+    /// Returns the children, along with their weights.
+    pub fn children(&self) -> &[(Tile, f64)] {
+        &self.children
+    }
+
+    /// Add a child after the others, returning the split.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the weight is not a positive, finite number.
+    pub fn with(mut self, tile: impl Into<Tile>, weight: f64) -> Self {
+        self.push(tile, weight);
+        self
+    }
+
+    /// Add a child after the others.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the weight is not a positive, finite number.
+    pub fn push(&mut self, tile: impl Into<Tile>, weight: f64) {
+        assert!(
+            weight > 0.0 && weight.is_finite(),
+            "weight must be positive and finite"
+        );
+        self.children.push((tile.into(), weight));
+    }
+
+    /// Remove a window from this split or any split nested in it.
+    ///
+    /// Nested splits left empty are removed as well. Returns `false` if the window was not found.
+    pub fn remove_window(&mut self, key: WindowKey) -> bool {
+        let mut found = false;
+        self.children.retain_mut(|(tile, _)| match tile {
+            Tile::Window(window) if *window == key => {
+                found = true;
+                false
+            }
+            Tile::Window(_) => true,
+            Tile::Split(split) => {
+                found |= split.remove_window(key);
+                !split.children.is_empty()
+            }
+        });
+        found
+    }
+
+    /// Compute the rectangle of every window in the split, when laid out in a rectangle.
+    pub fn compute<T: Coordinate>(&self, rect: Rectangle<T>) -> Vec<(WindowKey, Rectangle<T>)> {
+        let mut windows = Vec::new();
+        self.compute_into(rect, &mut windows);
+        windows
+    }
+
+    /// Move and resize the windows in the split to lay them out in a rectangle.
+    ///
+    /// Every window is changed in a single [transaction](WindowTable::transaction). Returns the
+    /// region of the screen that was damaged.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a window would be given an empty rectangle, in which case no windows
+    /// are changed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if one of the windows does not exist.
+    pub fn apply<T: Coordinate, const N: usize, const R: usize>(
+        &self,
+        table: &mut WindowTable<T, N, R>,
+        rect: Rectangle<T>,
+    ) -> Result<Region<T, R>, ConfigureError> {
+        let mut transaction = table.transaction();
+        for (key, rect) in self.compute(rect) {
+            transaction.resize_window(key, rect)?;
+        }
+        Ok(transaction.commit())
+    }
+
+    /// Insert a new window into the table as the last child of this split, then lay out the
+    /// split in a rectangle.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the window could not be inserted, or if the split is too small to
+    /// give every window a non-empty rectangle. Nothing is changed in either case.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the weight is not a positive, finite number, or if one of the windows already
+    /// in the split does not exist.
+    pub fn insert_window<T: Coordinate, const N: usize, const R: usize>(
+        &mut self,
+        table: &mut WindowTable<T, N, R>,
+        rect: Rectangle<T>,
+        weight: f64,
+    ) -> Result<WindowKey, InsertError> {
+        assert!(
+            weight > 0.0 && weight.is_finite(),
+            "weight must be positive and finite"
+        );
+
+        // Work out the space the new window will get before it exists.
+        let weights = self
+            .children
+            .iter()
+            .map(|&(_, weight)| weight)
+            .chain(Some(weight))
+            .collect::<Vec<_>>();
+        let slot = *slots(self.axis, rect, &weights).last().unwrap();
+
+        let key = table.insert(slot)?;
+        self.push(key, weight);
+        if self.apply(table, rect).is_err() {
+            self.children.pop();
+            table.remove(key);
+            return Err(InsertError::EmptyRectangle);
+        }
+
+        Ok(key)
+    }
+
+    /// Compute the rectangles of the windows in the split, adding them to a list.
+    fn compute_into<T: Coordinate>(
+        &self,
+        rect: Rectangle<T>,
+        windows: &mut Vec<(WindowKey, Rectangle<T>)>,
+    ) {
+        let weights = self
+            .children
+            .iter()
+            .map(|&(_, weight)| weight)
+            .collect::<Vec<_>>();
+
+        for ((tile, _), slot) in self.children.iter().zip(slots(self.axis, rect, &weights)) {
+            match tile {
+                Tile::Window(key) => windows.push((*key, slot)),
+                Tile::Split(split) => split.compute_into(slot, windows),
+            }
+        }
+    }
+}
+
+/// Divide a rectangle along an axis in proportion to some weights.
+///
+/// The edges between the pieces are rounded to coordinates, and the pieces cover the rectangle
+/// exactly.
+fn slots<T: Coordinate>(axis: Axis, rect: Rectangle<T>, weights: &[f64]) -> Vec<Rectangle<T>> {
+    let (start, end) = match axis {
+        Axis::Horizontal => (rect.left, rect.right),
+        Axis::Vertical => (rect.top, rect.bottom),
+    };
+    let (start_f, length) = (start.to_f64(), end.to_f64() - start.to_f64());
+    let total = weights.iter().sum::<f64>();
+
+    let mut slots = Vec::with_capacity(weights.len());
+    let mut low = start;
+    let mut sum = 0.0;
+
+    for (i, weight) in weights.iter().enumerate() {
+        sum += weight;
+        let high = if i + 1 == weights.len() {
+            end
+        } else {
+            T::from_f64(start_f + length * sum / total)
+        };
+
+        slots.push(match axis {
+            Axis::Horizontal => Rectangle::new(low, rect.top, high, rect.bottom),
+            Axis::Vertical => Rectangle::new(rect.left, low, rect.right, high),
+        });
+        low = high;
+    }
+
+    slots
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Axis, Split};
+    use crate::{InsertError, Rectangle, WindowTable};
+
+    #[test]
+    fn compute() {
+        let mut window_table = WindowTable::new();
+
+        let root = window_table.insert(Rectangle::new(0, 0, 90, 60)).unwrap();
+        let a = window_table.insert(Rectangle::new(0, 0, 10, 10)).unwrap();
+        let b = window_table.insert(Rectangle::new(0, 0, 10, 10)).unwrap();
+        let c = window_table.insert(Rectangle::new(0, 0, 10, 10)).unwrap();
+
+        let mut layout = Split::new(Axis::Horizontal)
+            .with(a, 2.0)
+            .with(Split::new(Axis::Vertical).with(b, 1.0).with(c, 1.0), 1.0);
+        let bounds = window_table.rect(root);
+        assert_eq!(
+            layout.compute(bounds),
+            [
+                (a, Rectangle::new(0, 0, 60, 60)),
+                (b, Rectangle::new(60, 0, 90, 30)),
+                (c, Rectangle::new(60, 30, 90, 60)),
+            ]
+        );
+
+        layout.apply(&mut window_table, bounds).unwrap();
+        assert_eq!(window_table.rect(b), Rectangle::new(60, 0, 90, 30));
+        assert_eq!(window_table.window_at((70, 40)), Some(c));
+
+        // Removing the last window in the nested split removes the split.
+        assert!(layout.remove_window(b));
+        assert!(layout.remove_window(c));
+        assert!(!layout.remove_window(c));
+        assert_eq!(layout.children().len(), 1);
+
+        let d = layout
+            .insert_window(&mut window_table, bounds, 1.0)
+            .unwrap();
+        assert_eq!(window_table.rect(a), Rectangle::new(0, 0, 60, 60));
+        assert_eq!(window_table.rect(d), Rectangle::new(60, 0, 90, 60));
+
+        // There is no room for a window this small.
+        let len = window_table.len();
+        assert!(matches!(
+            layout.insert_window(&mut window_table, bounds, 0.001),
+            Err(InsertError::EmptyRectangle)
+        ));
+        assert_eq!(window_table.len(), len);
+        assert_eq!(layout.children().len(), 2);
+    }
+
+    #[test]
+    fn rounding() {
+        let mut window_table = WindowTable::new();
+        let root = window_table.insert(Rectangle::new(0, 0, 10, 10)).unwrap();
+
+        let layout = (0..3).fold(Split::new(Axis::Vertical), |layout, _| {
+            layout.with(root, 1.0)
+        });
+        let rects = layout.compute(Rectangle::new(0, 0, 10, 10));
+        assert!(rects.iter().map(|(_, rect)| (rect.top, rect.bottom)).eq([
+            (0, 3),
+            (3, 7),
+            (7, 10)
+        ]));
+    }
+}
@@ -18,17 +18,118 @@
 
 //! A table for creating virtual windows.
 
-#![forbid(unsafe_code)]
+#![cfg_attr(not(feature = "ffi"), forbid(unsafe_code))]
+#![cfg_attr(feature = "ffi", deny(unsafe_code))]
 #![no_std]
 
 extern crate alloc;
 
-use alloc::vec;
+#[cfg(feature = "accessibility")]
+mod accessibility;
+mod animation;
+mod background;
+mod coord;
+mod cursor;
+mod damage;
+mod decoration;
+#[cfg(feature = "dot")]
+mod dot;
+mod drag;
+mod dump;
+mod event;
+mod extents;
+#[cfg(feature = "ffi")]
+#[allow(unsafe_code)]
+pub mod ffi;
+#[cfg(feature = "fixed")]
+mod fixed;
+mod focus;
+mod frame;
+#[cfg(feature = "arbitrary")]
+mod fuzz;
+mod geometry;
+mod history;
+mod index;
+mod inject;
+mod interactive;
+#[cfg(feature = "layout")]
+pub mod layout;
+mod modal;
+mod observer;
+#[cfg(feature = "rayon")]
+mod parallel;
+mod property;
+mod query;
+#[cfg(feature = "record")]
+mod record;
+mod region;
+mod request;
+mod snap;
+mod snapshot;
+mod state;
+mod stats;
+mod subtree;
+mod transaction;
+mod transform;
+mod transient;
+mod validate;
+mod visibility;
+mod workspace;
+
+#[cfg(feature = "accessibility")]
+pub use accessibility::{AccessNode, AccessTree};
+pub use coord::Coordinate;
+pub use cursor::{
+    Button, ButtonEvent, CrossingDetail, CursorEvent, CursorShape, CursorState, GrabError,
+    PointerId, PointerQuery, PointerState,
+};
+pub use damage::DamageSink;
+pub use decoration::{Decoration, Frame, HitRegion};
+pub use drag::{DragEvent, DragState};
+pub use event::{EventMask, ScrollEvent};
+pub use extents::Extents;
+#[cfg(feature = "fixed")]
+pub use fixed::{Fixed, WideFixed};
+pub use focus::{Direction, FocusEvent, FocusPolicy, FocusState, KeyRoute};
+#[cfg(feature = "arbitrary")]
+pub use fuzz::Mutation;
+pub use geometry::{Point, Size};
+pub use history::History;
+pub use inject::{Injected, InputEvent, InputState, RoutedEvent};
+pub use interactive::MoveResize;
+pub use modal::{ModalBlocked, ModalScope};
+pub use observer::{ObserverKey, WindowObserver};
+pub use property::{Atom, Property, PropertyEvent, PropertyState};
+pub use query::QueryScratch;
+#[cfg(feature = "record")]
+pub use record::{Call, Recorder};
+pub use region::{Region, Span};
+pub use request::ConfigureRequest;
+pub use snapshot::{Diff, Snapshot};
+pub use state::MaximizeAxis;
+pub use stats::Stats;
+pub use transaction::{ConfigureCause, ConfigureEvent, Transaction};
+pub use transform::Transform;
+pub use validate::{ValidationError, Violation};
+pub use visibility::{VisibilityEvent, VisibilityState, VisibilityTracker};
+pub use workspace::WorkspaceId;
+
+use animation::Animation;
+use damage::Sink;
+use index::SpatialIndex;
+use observer::Observers;
+use property::Atoms;
+use state::Placement;
+
+use alloc::boxed::Box;
+use alloc::collections::{BTreeMap, BTreeSet, VecDeque};
+use alloc::string::String;
+use alloc::vec::Vec;
 use core::cmp;
 
-use slotmap::{new_key_type, SlotMap};
+use slotmap::{new_key_type, Key as _, KeyData, SecondaryMap, SlotMap};
 use smallvec::SmallVec;
-use tinyvec::{ArrayVec, TinyVec};
+use tinyvec::ArrayVec;
 
 /// The key type for windows.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -38,46 +139,440 @@ new_key_type! {
     struct Key;
 }
 
+impl WindowKey {
+    /// Convert this key into a `u64`, for passing it across an FFI boundary or a wire protocol.
+    ///
+    /// The value is stable across versions of this crate, and round-trips through
+    /// [`from_ffi`](Self::from_ffi). It pairs the slot of the window with a generation that is
+    /// bumped whenever the slot is reused, so the key of a removed window does not refer to a
+    /// new window that takes its slot, unless the slot is reused more than 2<sup>31</sup>
+    /// times.
+    pub fn as_ffi(self) -> u64 {
+        self.0.data().as_ffi()
+    }
+
+    /// Convert a value returned by [`as_ffi`](Self::as_ffi) back into a key.
+    ///
+    /// Any value may be passed in. Values that were not returned by `as_ffi` give keys that
+    /// don't refer to any window, or that refer to an arbitrary one.
+    pub fn from_ffi(value: u64) -> Self {
+        Self(KeyData::from_ffi(value).into())
+    }
+}
+
 /// The table of windows
-#[derive(Debug, Default)]
-pub struct WindowTable {
+///
+/// The table is generic over the [`Coordinate`] type used for window rectangles, and over the
+/// number of parents and children each window can link to before the links are moved to the
+/// heap. The default of three suits deep trees; wide, flat trees where windows have many
+/// children are faster with more, at the cost of larger windows. `R` is the number of
+/// rectangles each [`Region`] the table keeps, such as the damage and window shapes, stores
+/// before moving to the heap.
+#[derive(Debug, Clone, Default)]
+pub struct WindowTable<T: Coordinate = i32, const N: usize = 3, const R: usize = 4> {
     /// The windows.
-    windows: SlotMap<Key, Window>,
+    windows: SlotMap<Key, Window<T, N, R>>,
 
     /// The root window.
     root: Option<WindowKey>,
+
+    /// The spatial index over the window rectangles.
+    index: SpatialIndex,
+
+    /// The position in the stacking order to give the next inserted window.
+    next_z: usize,
+
+    /// The windows, from the bottom of the stacking order to the top.
+    ///
+    /// This is kept up to date by `insert` and `relink`, so that walking the windows in
+    /// stacking order is a linear scan rather than a sort.
+    order: Vec<WindowKey>,
+
+    /// The region damaged since the last call to `take_damage`.
+    damage: Region<T, R>,
+
+    /// The regions returned by the most recent calls to `take_damage`, newest first.
+    damage_history: VecDeque<Region<T, R>>,
+
+    /// The number of regions kept in `damage_history`.
+    damage_history_len: usize,
+
+    /// The newly visible parts of each window, relative to the window's origin.
+    exposures: SecondaryMap<Key, Region<T, R>>,
+
+    /// The regions to restore from the saved contents of hidden save-under windows.
+    save_unders: Vec<(WindowKey, Region<T, R>)>,
+
+    /// The changes to the rectangles and stacking positions of windows.
+    configure_events: Vec<ConfigureEvent<T>>,
+
+    /// The observers notified of changes to the table.
+    observers: Observers<T>,
+
+    /// The sink that damage is pushed to as it happens.
+    damage_sink: Sink<T>,
+
+    /// The interned property names.
+    atoms: Atoms,
+
+    /// The changes to the properties of windows.
+    property_events: Vec<PropertyEvent>,
+
+    /// How the root window is rotated and flipped on the output.
+    transform: Transform,
+
+    /// The buffers reused by every call to `link`.
+    scratch: QueryScratch<T>,
+
+    /// The workspace being shown.
+    active_workspace: WorkspaceId,
+
+    /// The windows being animated.
+    animations: Vec<Animation<T>>,
+
+    /// The windows waiting to be told to draw their next frame.
+    frame_requests: Vec<WindowKey>,
+
+    /// The window that each transient window is kept above.
+    transients: BTreeMap<WindowKey, WindowKey>,
+
+    /// The window kept beneath every other window above the root.
+    background: Option<WindowKey>,
+
+    /// The windows that accept input in a margin around their rectangles.
+    input_margins: BTreeSet<WindowKey>,
+
+    /// What happens when a new window overlaps the windows already in the table.
+    overlap_policy: OverlapPolicy,
+
+    /// The configure requests waiting to be applied, oldest first.
+    configure_requests: Vec<ConfigureRequest<T>>,
+
+    /// The serial to give the next configure request.
+    next_serial: u32,
 }
 
 /// The window.
-#[derive(Debug)]
-struct Window {
+#[derive(Debug, Clone)]
+struct Window<T: Coordinate, const N: usize = 3, const R: usize = 4> {
+    /// The rarely used state of the window.
+    ///
+    /// This is `None` until some of it is set, which keeps the windows small.
+    attributes: Option<Box<Attributes<T>>>,
+
     /// The rectangle (LTRB) of the window.
-    rect: Rectangle,
+    rect: Rectangle<T>,
+
+    /// The position of the window in the stacking order.
+    ///
+    /// Windows with a higher position are stacked above windows with a lower position.
+    z: usize,
 
     /// The parents of the window.
-    parents: SmallVec<[WindowKey; 3]>,
+    parents: SmallVec<[WindowKey; N]>,
 
     /// The children of the window.
-    children: SmallVec<[WindowKey; 3]>,
+    children: SmallVec<[WindowKey; N]>,
+
+    /// The region of the window that accepts input, relative to the window's origin.
+    ///
+    /// If this is `None`, the entire window accepts input.
+    input_region: Option<Region<T, R>>,
+
+    /// Whether this window is ignored by hit testing.
+    input_transparent: bool,
+
+    /// The kinds of input events that this window accepts.
+    event_mask: EventMask,
+
+    /// Whether things can be dropped onto this window.
+    accepts_drops: bool,
+
+    /// Whether this window can be given the keyboard focus by traversal.
+    focusable: bool,
+
+    /// Whether the retained contents of this window can be reused without redrawing.
+    contents_valid: bool,
+
+    /// Which windows this window blocks input to.
+    modal: ModalScope,
+
+    /// The layer of the stacking order that this window is kept in.
+    layer: Layer,
+
+    /// Whether this window is mapped.
+    mapped: bool,
+
+    /// The width of the border around the inside edge of the window.
+    border_width: u16,
+
+    /// How the window moves when its primary parent is resized.
+    gravity: Gravity,
+
+    /// Whether the contents beneath this window are saved while it is mapped.
+    save_under: bool,
+
+    /// The opacity of the window, from 0 (transparent) to 255 (opaque).
+    opacity: u8,
+
+    /// The shape of the window, relative to the window's origin.
+    ///
+    /// If this is `None`, the window is shaped like its rectangle.
+    shape: Option<Region<T, R>>,
+}
+
+/// The state of a window that is rarely set, and never needed when walking the table.
+#[derive(Debug, Clone, Default)]
+struct Attributes<T: Coordinate> {
+    /// The name of the window, used to identify it while debugging.
+    name: Option<String>,
+
+    /// The properties of the window.
+    properties: BTreeMap<Atom, Property>,
+
+    /// The number of physical pixels per logical unit.
+    ///
+    /// If this is `None`, the scale factor is inherited from the primary parent.
+    scale_factor: Option<f64>,
+
+    /// The constraints on the size of the window.
+    size_hints: SizeHints<T>,
+
+    /// The workspaces the window belongs to, or empty if it is on every workspace.
+    workspaces: SmallVec<[WorkspaceId; 1]>,
+
+    /// Whether the window was unmapped by switching away from its workspaces.
+    hidden_by_workspace: bool,
+
+    /// How the edges of the window are anchored to its primary parent.
+    anchors: Option<Anchors<T>>,
+
+    /// The fractions of its primary parent that the window's rectangle is made of.
+    relative: Option<RelativeRect>,
+
+    /// Where the window was before it was minimized.
+    minimized: Option<Placement<T>>,
+
+    /// Where the window was before it was made fullscreen.
+    fullscreen: Option<Placement<T>>,
+
+    /// The axis the window is maximized along, and its rectangle before it was maximized.
+    maximized: Option<(MaximizeAxis, Rectangle<T>)>,
+
+    /// The decorations of the window, if it is a frame.
+    decoration: Option<Decoration<T>>,
+
+    /// The shape of the cursor over the window.
+    cursor: Option<CursorShape>,
+
+    /// The position of the window within its layer.
+    z_index: i32,
+
+    /// How far the window is painted beyond its rectangle.
+    visual_extents: Option<Extents<T>>,
+
+    /// How far the window accepts input beyond its rectangle.
+    input_margin: Option<Extents<T>>,
+}
+
+impl<T: Coordinate, const N: usize, const R: usize> Window<T, N, R> {
+    /// Get the rarely used state of this window, if any of it has been set.
+    fn attributes(&self) -> Option<&Attributes<T>> {
+        self.attributes.as_deref()
+    }
+
+    /// Get the rarely used state of this window, creating it if it has not been set.
+    fn attributes_mut(&mut self) -> &mut Attributes<T> {
+        self.attributes.get_or_insert_with(Default::default)
+    }
+
+    /// Get the rectangle that this window is painted in, including its visual extents.
+    fn visual_rect(&self) -> Rectangle<T> {
+        match self
+            .attributes()
+            .and_then(|attributes| attributes.visual_extents)
+        {
+            Some(extents) => extents.outset(self.rect),
+            None => self.rect,
+        }
+    }
+
+    /// Get the rectangle that this window accepts input in, including its input margin.
+    fn input_rect(&self) -> Rectangle<T> {
+        match self
+            .attributes()
+            .and_then(|attributes| attributes.input_margin)
+        {
+            Some(margin) => margin.outset(self.rect),
+            None => self.rect,
+        }
+    }
+
+    /// Tell if this window accepts input at the given position.
+    fn accepts_input(&self, point: Point<T>) -> bool {
+        if self.input_transparent {
+            return false;
+        }
+
+        // The input margin accepts input regardless of the shape and input region.
+        if !self.rect.contains(point) {
+            return self.input_rect().contains(point);
+        }
+        if !self.contains_point(point) {
+            return false;
+        }
+
+        match &self.input_region {
+            Some(region) => region.contains(self.to_local(point)),
+            None => true,
+        }
+    }
+
+    /// Tell if the shape of this window contains a point.
+    fn contains_point(&self, point: Point<T>) -> bool {
+        self.rect.contains(point)
+            && match &self.shape {
+                Some(shape) => shape.contains(self.to_local(point)),
+                None => true,
+            }
+    }
+
+    /// Convert a point to be relative to the window's origin.
+    fn to_local(&self, point: Point<T>) -> Point<T> {
+        Point::new(
+            point.x.saturating_sub(self.rect.left),
+            point.y.saturating_sub(self.rect.top),
+        )
+    }
+
+    /// Get the area covered by this window.
+    fn bounding_region(&self) -> Region<T, R> {
+        match &self.shape {
+            Some(shape) => {
+                let mut region = shape.clone();
+                region.translate(self.rect.left, self.rect.top);
+                region.intersect_rect(self.rect);
+                region
+            }
+            None => core::iter::once(self.rect).collect(),
+        }
+    }
 }
 
-impl WindowTable {
+impl<T: Coordinate> WindowTable<T> {
     /// Creates a new window table.
+    ///
+    /// Use [`Default::default`] to create a table with a different inline capacity for links or
+    /// regions.
     pub fn new() -> Self {
         Self::default()
     }
+}
+
+impl<T: Coordinate, const N: usize, const R: usize> WindowTable<T, N, R> {
+    /// Returns the number of windows in the table.
+    pub fn len(&self) -> usize {
+        self.windows.len()
+    }
+
+    /// Tell if the table has no windows.
+    pub fn is_empty(&self) -> bool {
+        self.windows.is_empty()
+    }
+
+    /// Tell if a window exists in the table.
+    pub fn contains_key(&self, key: WindowKey) -> bool {
+        self.windows.contains_key(key.0)
+    }
+
+    /// Returns the number of primary parents between a window and the root window.
+    ///
+    /// The root window has a depth of zero. Returns `None` if the window does not exist.
+    pub fn depth(&self, key: WindowKey) -> Option<usize> {
+        self.ancestors(key).count().checked_sub(1)
+    }
+
+    /// Returns the number of levels in the tree of windows.
+    ///
+    /// This is one more than the largest [`depth`](Self::depth) of any window, or zero if the
+    /// table is empty.
+    pub fn tree_depth(&self) -> usize {
+        // Primary parents always come before their children in the stacking order.
+        let mut depths = SecondaryMap::<Key, usize>::with_capacity(self.windows.len());
+        let mut deepest = 0;
+
+        for key in self.stacking_order() {
+            let depth = match self.parent(key) {
+                Some(parent) => depths[parent.0] + 1,
+                None => 1,
+            };
+            depths.insert(key.0, depth);
+            deepest = cmp::max(deepest, depth);
+        }
+
+        deepest
+    }
 
     /// Iterate over the windows.
-    pub fn iter(&self) -> impl ExactSizeIterator<Item = (WindowKey, Rectangle)> + '_ {
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = (WindowKey, Rectangle<T>)> + '_ {
         self.windows
             .iter()
             .map(|(key, window)| (WindowKey(key), window.rect))
     }
 
+    /// Iterate over the viewable windows in the order they should be painted.
+    ///
+    /// The windows come in stacking order, so every window comes after all of the windows it is
+    /// stacked on top of, and the root window comes first.
+    pub fn paint_order(&self) -> impl ExactSizeIterator<Item = (WindowKey, Rectangle<T>)> + '_ {
+        // A window is viewable if it is mapped and its primary parent is viewable. Parents come
+        // before their children in the stacking order, so this can be worked out in one pass.
+        let mut viewable = SecondaryMap::<Key, bool>::with_capacity(self.windows.len());
+        let mut order = Vec::with_capacity(self.windows.len());
+
+        for &key in &self.order {
+            let window = &self.windows[key.0];
+            let shown = window.mapped
+                && match window.parents.first() {
+                    Some(parent) => viewable[parent.0],
+                    None => self.root == Some(key),
+                };
+
+            viewable.insert(key.0, shown);
+            if shown {
+                order.push((key, window.rect));
+            }
+        }
+
+        order.into_iter()
+    }
+
     /// Empties the window table.
     pub fn clear(&mut self) {
+        if !self.observers.is_empty() {
+            for (key, window) in &self.windows {
+                self.observers
+                    .notify(|observer| observer.on_remove(WindowKey(key), window.rect));
+            }
+        }
+
         self.windows.clear();
+        self.index.clear();
         self.root = None;
+        self.next_z = 0;
+        self.order.clear();
+        self.animations.clear();
+        self.frame_requests.clear();
+        self.transients.clear();
+        self.background = None;
+        self.input_margins.clear();
+        self.configure_requests.clear();
+        self.damage = Region::default();
+        self.damage_history.clear();
+        self.exposures.clear();
+        self.save_unders.clear();
+        self.configure_events.clear();
+        self.property_events.clear();
     }
 
     /// Returns the root window.
@@ -86,310 +581,4048 @@ impl WindowTable {
     }
 
     /// Insert a new window.
-    pub fn insert(&mut self, rect: Rectangle) -> Result<WindowKey, InsertError> {
-        use alloc::vec;
+    ///
+    /// The rectangle is [normalized](Rectangle::normalize) first. Empty rectangles are rejected.
+    pub fn insert(&mut self, rect: Rectangle<T>) -> Result<WindowKey, InsertError> {
+        self.insert_stacked(rect, None)
+    }
+
+    /// Insert a new window directly above a sibling and its descendants.
+    ///
+    /// See [`insert`](Self::insert) for how the rectangle is checked.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the sibling does not exist.
+    pub fn insert_above(
+        &mut self,
+        rect: Rectangle<T>,
+        sibling: WindowKey,
+    ) -> Result<WindowKey, InsertError> {
+        assert!(self.contains_key(sibling), "window does not exist");
+        self.insert_stacked(rect, Some((sibling, true)))
+    }
+
+    /// Insert a new window directly below a sibling.
+    ///
+    /// See [`insert`](Self::insert) for how the rectangle is checked. Nothing can be inserted
+    /// below the root window.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the sibling does not exist.
+    pub fn insert_below(
+        &mut self,
+        rect: Rectangle<T>,
+        sibling: WindowKey,
+    ) -> Result<WindowKey, InsertError> {
+        assert!(self.contains_key(sibling), "window does not exist");
+        if self.root == Some(sibling) {
+            return Err(InsertError::BelowRoot);
+        }
+
+        self.insert_stacked(rect, Some((sibling, false)))
+    }
+
+    /// Insert a new window whose rectangle is a fraction of another window's rectangle.
+    ///
+    /// The window is placed by resolving the fractions against `parent`, and is resolved again
+    /// against its primary parent whenever that parent is moved or resized. See
+    /// [`set_relative`](Self::set_relative) for more information.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the parent does not exist.
+    pub fn insert_relative(
+        &mut self,
+        parent: WindowKey,
+        relative: RelativeRect,
+    ) -> Result<WindowKey, InsertError> {
+        let key = self.insert(relative.resolve(self.rect(parent)))?;
+        self.windows[key.0].attributes_mut().relative = Some(relative);
+        Ok(key)
+    }
+
+    /// Insert a new window, either on top of the stack or next to a sibling.
+    ///
+    /// The sibling is paired with whether the window goes above it.
+    fn insert_stacked(
+        &mut self,
+        rect: Rectangle<T>,
+        sibling: Option<(WindowKey, bool)>,
+    ) -> Result<WindowKey, InsertError> {
+        let rect = rect.normalize();
+        if rect.is_empty() {
+            return Err(InsertError::EmptyRectangle);
+        }
+
+        // We should be able to fit this window in the root window.
+        let rect = match self.root {
+            Some(root) => {
+                let root = self.windows[root.0].rect;
+                if !rect.intersects(root) {
+                    return Err(InsertError::OutsideRoot);
+                }
+                self.apply_overlap_policy(rect, root, sibling)?
+            }
+            None => rect,
+        };
 
+        Ok(self.insert_unchecked(rect, sibling))
+    }
+
+    /// Insert a new window without checking its rectangle against the root window or the
+    /// overlap policy.
+    ///
+    /// This is used to bring back windows that were already in the table, such as when undoing
+    /// a removal.
+    pub(crate) fn insert_unchecked(
+        &mut self,
+        rect: Rectangle<T>,
+        sibling: Option<(WindowKey, bool)>,
+    ) -> WindowKey {
         let key = {
             let inner = self.windows.insert(Window {
+                attributes: None,
                 rect,
+                z: self.next_z,
                 parents: SmallVec::new(),
                 children: SmallVec::new(),
+                input_region: None,
+                input_transparent: false,
+                event_mask: EventMask::default(),
+                accepts_drops: false,
+                focusable: true,
+                contents_valid: false,
+                modal: ModalScope::default(),
+                layer: Layer::default(),
+                mapped: true,
+                border_width: 0,
+                gravity: Gravity::default(),
+                save_under: false,
+                opacity: u8::MAX,
+                shape: None,
             });
 
             WindowKey(inner)
         };
+        self.next_z += 1;
+        self.order.push(key);
+        self.index.insert(key, rect);
+        self.add_damage_rect(rect);
+        self.observers
+            .notify(|observer| observer.on_insert(key, rect));
 
         // If there is no root window, set this window as the root.
-        let root = match self.root {
-            Some(root) => root,
-            None => {
-                self.root = Some(key);
-                return Ok(key);
-            }
-        };
-
-        // We should be able to fit this window in the root window.
-        if rect.intersects(self.windows[root.0].rect) {
-            return Err(InsertError::OutsideRoot);
+        if self.root.is_none() {
+            self.root = Some(key);
+            return key;
         }
-        let root = match self.root {
-            Some(root) => root,
-            None => {
-                self.root = Some(key);
-                return Ok(key);
-            }
-        };
 
-        // Get the intersections.
-        let (parents, _) = self.intersections(&[root], rect);
+        match sibling {
+            // Windows in higher layers or z-indices stay above the new window.
+            None if {
+                let top = self.order[self.order.len() - 2];
+                (self.windows[top.0].layer, self.z_index(top)) > (Layer::Normal, 0)
+            } =>
+            {
+                let order = self.stacking_order();
+                self.relink(&order);
+            }
+            None => self.link(key),
+            Some((sibling, above)) => {
+                let mut order = self.stacking_order();
+                order.pop();
 
-        // Set children.
-        for parent in &parents {
-            self.windows[parent.0].children.push(key);
+                let position = if above {
+                    let subtree = self.subtree_in(&order, sibling);
+                    let last = *subtree.last().unwrap();
+                    order.iter().position(|&window| window == last).unwrap() + 1
+                } else {
+                    order.iter().position(|&window| window == sibling).unwrap()
+                };
+                order.insert(position, key);
+                self.relink(&order);
+            }
         }
 
-        // The current rectangles and windows we're testing.
-        let mut current_rects = tinyvec::tiny_vec![[Rectangle; 4] => rect];
-        let mut current_windows: SmallVec<[WindowKey; 3]> = smallvec::smallvec![root];
+        // The entire window needs to be drawn.
+        let mut exposed = self.visible_region(key);
+        exposed.relative_to(rect.left, rect.top);
+        self.expose(key, &exposed);
+
+        key
+    }
 
-        while !current_rects.is_empty() {
-            // Take the rectangles.
-            let mut taken_rects = core::mem::take(&mut current_rects);
-            let mut taken_windows = core::mem::take(&mut current_windows);
+    /// Set what happens when a new window overlaps the windows already in the table.
+    ///
+    /// The policy applies to windows inserted afterwards. The default is
+    /// [`OverlapPolicy::AllowOverlap`].
+    pub fn set_overlap_policy(&mut self, policy: OverlapPolicy) {
+        self.overlap_policy = policy;
+    }
 
-            // Drain the rectangles.
-            while !taken_rects.is_empty() {
-                let rect = taken_rects.swap_remove(0);
+    /// Returns what happens when a new window overlaps the windows already in the table.
+    pub fn overlap_policy(&self) -> OverlapPolicy {
+        self.overlap_policy
+    }
 
-                // Find the intersection of the rectangle with the windows.
-                for window in &taken_windows {
-                    let window_rect = self.windows[window.0].rect;
-                    let (intersection, remainder) = match rect.intersection(window_rect) {
-                        Some(x) => x,
-                        None => continue,
-                    };
+    /// Check the rectangle of a new window against the overlap policy, returning the rectangle
+    /// to give it.
+    fn apply_overlap_policy(
+        &self,
+        rect: Rectangle<T>,
+        root: Rectangle<T>,
+        sibling: Option<(WindowKey, bool)>,
+    ) -> Result<Rectangle<T>, InsertError> {
+        match self.overlap_policy {
+            OverlapPolicy::AllowOverlap => Ok(rect),
+            OverlapPolicy::Reject => {
+                if !root.contains_rect(rect) {
+                    return Err(InsertError::OutsideRoot);
+                }
 
-                    // This window's children should be carried over to the next iteration.
+                let nested = self.index.query_rect(rect).into_iter().all(|key| {
+                    let other = self.windows[key.0].rect;
+                    !other.intersects(rect)
+                        || other.contains_rect(rect)
+                        || rect.contains_rect(other)
+                });
+                if nested {
+                    Ok(rect)
+                } else {
+                    Err(InsertError::Overlaps)
                 }
             }
+            OverlapPolicy::ClipToParent => {
+                // Find where the window will go in the stacking order.
+                let position = match sibling {
+                    None => self.order.iter().position(|&key| {
+                        (self.windows[key.0].layer, self.z_index(key)) > (Layer::Normal, 0)
+                    }),
+                    Some((sibling, true)) => {
+                        let subtree = self.subtree_in(&self.order, sibling);
+                        let last = *subtree.last().unwrap();
+                        self.order
+                            .iter()
+                            .position(|&key| key == last)
+                            .map(|i| i + 1)
+                    }
+                    Some((sibling, false)) => self.order.iter().position(|&key| key == sibling),
+                };
+                let below = position
+                    .and_then(|i| self.order.get(i))
+                    .map_or(usize::MAX, |key| self.windows[key.0].z);
+
+                // Total up how much of the window lies on each of the windows beneath it, like
+                // when linking it.
+                let mut parents = SmallVec::<[(WindowKey, T); N]>::new();
+                self.split_onto(
+                    rect,
+                    &mut QueryScratch::default(),
+                    |key| self.windows[key.0].z < below,
+                    |parent, piece| match parents.iter_mut().find(|(key, _)| *key == parent) {
+                        Some((_, area)) => *area = area.saturating_add(piece.saturating_area()),
+                        None => parents.push((parent, piece.saturating_area())),
+                    },
+                );
+                parents.sort_by(|(_, a), (_, b)| b.partial_cmp(a).unwrap_or(cmp::Ordering::Equal));
+
+                let parent = parents
+                    .first()
+                    .map_or(root, |&(parent, _)| self.windows[parent.0].rect);
+                Ok(rect.intersection(parent).unwrap_or(rect))
+            }
         }
+    }
 
-        Ok(key)
+    /// Register an observer to be notified of changes to the table.
+    ///
+    /// Observers are not copied when the table is cloned.
+    pub fn add_observer(
+        &mut self,
+        observer: impl WindowObserver<T> + Send + 'static,
+    ) -> ObserverKey {
+        self.observers.insert(Box::new(observer))
     }
 
-    /// Get the windows that this rectangle intersects, at this root.
-    fn intersections(
-        &self,
-        roots: &[WindowKey],
-        rect: Rectangle,
-    ) -> (SmallVec<[WindowKey; 3]>, bool) {
-        let mut windows = SmallVec::new();
-        let mut rectangles = tinyvec::tiny_vec![[Rectangle; 4] => rect];
-        let mut leftovers = true;
-
-        while !rectangles.is_empty() {
-            let rect = rectangles.swap_remove(0);
-
-            let intersect = roots.iter().find_map(|root| {
-                let window = self.windows[root.0].rect;
-                rect.intersection(window).map(|(i, r)| (i, r, root))
-            });
+    /// Unregister an observer, returning it.
+    pub fn remove_observer(
+        &mut self,
+        key: ObserverKey,
+    ) -> Option<Box<dyn WindowObserver<T> + Send>> {
+        self.observers.remove(key)
+    }
 
-            if let Some((intersection, remainder, matched_root)) = intersect {
-                // Re-run this code on the new root's children.
-                let (mut child_intersections, leftovers) =
-                    self.intersections(&self.windows[matched_root.0].children, intersection);
+    /// Take an immutable copy of the windows in the table.
+    ///
+    /// Snapshots can be compared with [`Snapshot::diff`] to find out what changed between them.
+    pub fn snapshot(&self) -> Snapshot<T> {
+        Snapshot::new(self)
+    }
 
-                // Append the results to our list.
-                windows.append(&mut child_intersections);
+    /// Check that the table is internally consistent.
+    ///
+    /// This verifies that every link between windows points at a window that exists and goes
+    /// both ways, that every window overlaps and is stacked above its parents, that every window
+    /// other than the root has a parent, and that the spatial index knows about every window.
+    /// It is meant for debugging; a table that is only changed through its public API should
+    /// always pass.
+    ///
+    /// # Errors
+    ///
+    /// Returns every broken invariant that was found.
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        validate::validate(self)
+    }
 
-                // If there are leftover rectangles, this root intersects.
-                if leftovers {
-                    windows.push(*matched_root);
-                }
+    /// Start a batch of changes to the table.
+    ///
+    /// See [`Transaction`] for more information.
+    pub fn transaction(&mut self) -> Transaction<'_, T, N, R> {
+        Transaction::new(self)
+    }
 
-                // Try again for remainder.
-                rectangles.append(&mut tinyvec::TinyVec::Inline(remainder));
-            } else {
-                // There are leftover rectangles here.
-                leftovers = true;
-            }
+    /// Take the region that has been damaged since the last call to this function.
+    ///
+    /// Inserting, removing, moving, resizing, mapping, unmapping and restacking windows all
+    /// damage the parts of the screen that need to be repainted.
+    ///
+    /// Each call ends a frame. If a [damage history](Self::set_damage_history_len) is kept, the
+    /// region is remembered for [`damage_since`](Self::damage_since).
+    pub fn take_damage(&mut self) -> Region<T, R> {
+        let damage = core::mem::take(&mut self.damage);
+        if self.damage_history_len > 0 {
+            self.damage_history.truncate(self.damage_history_len - 1);
+            self.damage_history.push_front(damage.clone());
         }
 
-        // Remove duplicates.
-        windows.sort_unstable();
-        windows.dedup();
-
-        (windows, leftovers)
+        damage
     }
-}
 
-/// Error with inserting a window.
-#[derive(Debug)]
-pub enum InsertError {
-    /// This window falls outside of the bounds of the root window.
-    OutsideRoot,
-}
+    /// Set the number of frames of damage to remember for [`damage_since`](Self::damage_since).
+    ///
+    /// This should be at least the number of buffers being rendered to, minus one. No history
+    /// is kept by default.
+    pub fn set_damage_history_len(&mut self, len: usize) {
+        self.damage_history_len = len;
+        self.damage_history.truncate(len);
+    }
 
-/// The current cursor state.
-#[derive(Debug)]
-pub struct CursorState {
-    /// Last known cursor position.
-    position: (i32, i32),
+    /// Returns the number of frames of damage that are remembered.
+    pub fn damage_history_len(&self) -> usize {
+        self.damage_history_len
+    }
 
-    /// Windows currently under the cursor.
-    windows: SmallVec<[WindowKey; 3]>,
-}
+    /// Get the region to repaint in a buffer whose contents are from `age` frames ago.
+    ///
+    /// This follows the semantics of `EGL_EXT_buffer_age`. A buffer of age 1 holds the last
+    /// frame, so only the damage since then needs to be repainted; older buffers also need the
+    /// damage from the frames in between. An age of zero means the contents are unknown, so
+    /// the whole root window is returned, as it is when the age is older than the history. The
+    /// damage is not taken.
+    pub fn damage_since(&self, age: usize) -> Region<T, R> {
+        let missed = match age.checked_sub(1) {
+            Some(missed) if missed <= self.damage_history.len() => missed,
+            _ => {
+                return match self.root {
+                    Some(root) => core::iter::once(self.windows[root.0].rect).collect(),
+                    None => Region::default(),
+                }
+            }
+        };
 
-/// A rectangle.
-#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
-pub struct Rectangle {
-    /// The left coordinate.
-    pub left: i32,
+        let mut damage = self.damage.clone();
+        for frame in self.damage_history.iter().take(missed) {
+            damage.union(frame);
+        }
 
-    /// The top coordinate.
-    pub top: i32,
+        damage
+    }
 
-    /// The right coordinate.
-    pub right: i32,
+    /// Take the region that has been damaged since the last call to this function, in output
+    /// coordinates.
+    ///
+    /// This is [`take_damage`](Self::take_damage) with the [`Transform`] of the table applied.
+    pub fn take_output_damage(&mut self) -> Region<T, R> {
+        let damage = self.take_damage();
+        match self.root {
+            Some(root) => self
+                .transform
+                .apply_region(self.windows[root.0].rect, &damage),
+            None => damage,
+        }
+    }
 
-    /// The bottom coordinate.
-    pub bottom: i32,
-}
+    /// Set how the root window is rotated and flipped on the output.
+    ///
+    /// Output coordinates share the top-left corner of the root window. If the transform swaps
+    /// the axes, the output is as wide as the root window is tall. The entire root window is
+    /// damaged when the transform changes.
+    pub fn set_transform(&mut self, transform: Transform) {
+        if self.transform == transform {
+            return;
+        }
 
-impl Rectangle {
-    /// Creates a new rectangle.
-    pub fn new(left: i32, top: i32, right: i32, bottom: i32) -> Self {
-        Self {
-            left,
-            top,
-            right,
-            bottom,
+        self.transform = transform;
+        if let Some(root) = self.root {
+            let rect = self.windows[root.0].rect;
+            self.add_damage_rect(rect);
         }
     }
 
-    /// Returns the width of the rectangle.
-    pub fn width(&self) -> i32 {
-        (self.right - self.left).abs()
+    /// Returns how the root window is rotated and flipped on the output.
+    pub fn transform(&self) -> Transform {
+        self.transform
     }
 
-    /// Returns the height of the rectangle.
-    pub fn height(&self) -> i32 {
-        (self.bottom - self.top).abs()
+    /// Convert a point into output coordinates.
+    pub fn to_output(&self, point: impl Into<Point<T>>) -> Point<T> {
+        match self.root {
+            Some(root) => self
+                .transform
+                .apply(self.windows[root.0].rect, point.into()),
+            None => point.into(),
+        }
     }
 
-    fn area(&self) -> i32 {
-        self.width() * self.height()
+    /// Convert a point from output coordinates.
+    pub fn from_output(&self, point: impl Into<Point<T>>) -> Point<T> {
+        match self.root {
+            Some(root) => self
+                .transform
+                .invert(self.windows[root.0].rect, point.into()),
+            None => point.into(),
+        }
     }
 
-    /// Tell if two windows intersect.
-    fn intersects(&self, other: Self) -> bool {
-        self.left < other.right
-            && self.right > other.left
-            && self.top < other.bottom
-            && self.bottom > other.top
+    /// Returns the rectangle of a window in output coordinates.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn output_rect(&self, key: WindowKey) -> Rectangle<T> {
+        let rect = self.windows[key.0].rect;
+        let root = self.windows[self.root.unwrap().0].rect;
+        self.transform.apply_rect(root, rect)
     }
 
-    /// Intersect this rectangle with another.
+    /// Take the newly visible parts of each window since the last call to this function.
+    ///
+    /// When a window is removed, unmapped, moved or restacked, the windows that it covered become
+    /// visible and need to be redrawn. Each region is relative to its window's top-left corner.
+    pub fn take_exposures(&mut self) -> Vec<(WindowKey, Region<T, R>)> {
+        let exposures = core::mem::take(&mut self.exposures);
+
+        exposures
+            .into_iter()
+            .filter(|(key, _)| self.windows.contains_key(*key))
+            .map(|(key, region)| (WindowKey(key), region))
+            .collect()
+    }
+
+    /// Mark the retained contents of a window as up to date, after drawing it.
+    ///
+    /// The table marks the contents as invalid again whenever part of the window is
+    /// [exposed](Self::take_exposures) or the window is resized. Windows start out with invalid
+    /// contents.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn mark_contents_valid(&mut self, key: WindowKey) {
+        self.windows[key.0].contents_valid = true;
+    }
+
+    /// Mark the retained contents of a window as needing to be redrawn.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn invalidate_contents(&mut self, key: WindowKey) {
+        self.windows[key.0].contents_valid = false;
+    }
+
+    /// Tell if the retained contents of a window can be reused without redrawing.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn contents_valid(&self, key: WindowKey) -> bool {
+        self.windows[key.0].contents_valid
+    }
+
+    /// Returns the viewable windows whose contents need to be redrawn, in paint order.
+    pub fn needs_redraw(&self) -> Vec<WindowKey> {
+        self.paint_order()
+            .map(|(key, _)| key)
+            .filter(|key| !self.windows[key.0].contents_valid)
+            .collect()
+    }
+
+    /// Take the changes to the rectangles and stacking positions of windows since the last call
+    /// to this function.
+    ///
+    /// An event is generated for each window that is moved, resized or restacked, whether
+    /// directly or along with another window. The events may refer to windows that no longer
+    /// exist.
+    pub fn take_configure_events(&mut self) -> Vec<ConfigureEvent<T>> {
+        core::mem::take(&mut self.configure_events)
+    }
+
+    /// Take the regions to restore from the contents saved beneath save-under windows.
+    ///
+    /// When a save-under window is hidden, the windows beneath it are not sent exposures for
+    /// the area it covered. Instead, the window and the area, in the same coordinates as the
+    /// window rectangles, are reported here. The window may no longer exist.
+    pub fn take_save_unders(&mut self) -> Vec<(WindowKey, Region<T, R>)> {
+        core::mem::take(&mut self.save_unders)
+    }
+
+    /// Set whether the contents beneath a window are saved while it is mapped.
+    ///
+    /// This is useful for short-lived windows, like menus and tooltips. See
+    /// [`take_save_unders`](Self::take_save_unders).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn set_save_under(&mut self, key: WindowKey, save_under: bool) {
+        self.windows[key.0].save_under = save_under;
+    }
+
+    /// Tell if the contents beneath a window are saved while it is mapped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn save_under(&self, key: WindowKey) -> bool {
+        self.windows[key.0].save_under
+    }
+
+    /// Set the opacity of a window, from 0 (transparent) to 255 (opaque).
+    ///
+    /// Windows are opaque by default. Windows that aren't opaque don't hide the windows beneath
+    /// them.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn set_opacity(&mut self, key: WindowKey, opacity: u8) {
+        let window = &mut self.windows[key.0];
+        if window.opacity == opacity {
+            return;
+        }
+
+        window.opacity = opacity;
+        if self.is_viewable(key) {
+            let rect = self.windows[key.0].visual_rect();
+            self.add_damage_rect(rect);
+        }
+    }
+
+    /// Returns the opacity of a window.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn opacity(&self, key: WindowKey) -> u8 {
+        self.windows[key.0].opacity
+    }
+
+    /// Returns the opacity of a window combined with the opacities of its ancestors.
+    ///
+    /// Returns zero if the window does not exist.
+    pub fn effective_opacity(&self, key: WindowKey) -> u8 {
+        if !self.windows.contains_key(key.0) {
+            return 0;
+        }
+
+        self.ancestors(key).fold(u8::MAX, |opacity, ancestor| {
+            let own = u32::from(self.windows[ancestor.0].opacity);
+            (u32::from(opacity) * own / 255) as u8
+        })
+    }
+
+    /// Tell if a window is viewable and its effective opacity is above a threshold.
+    pub fn is_effectively_visible(&self, key: WindowKey, threshold: u8) -> bool {
+        self.is_viewable(key) && self.effective_opacity(key) > threshold
+    }
+
+    /// Tell if a window hides the windows beneath it.
+    fn occludes(&self, key: WindowKey) -> bool {
+        self.is_viewable(key) && self.effective_opacity(key) == u8::MAX
+    }
+
+    /// Get the part of a window that is not covered by the windows stacked above it.
+    ///
+    /// The region is in the same coordinates as the window rectangles, and is clipped to the
+    /// root window. Windows that aren't opaque don't cover the windows beneath them. Returns an
+    /// empty region if the window is not viewable.
+    pub fn visible_region(&self, key: WindowKey) -> Region<T, R> {
+        if !self.is_viewable(key) {
+            return Region::default();
+        }
+
+        let window = &self.windows[key.0];
+        let mut region = window.bounding_region();
+        if let Some(root) = self.root {
+            region.intersect_rect(self.windows[root.0].rect);
+        }
+
+        for other in self.index.query_rect(window.rect) {
+            if self.windows[other.0].z > window.z && self.occludes(other) {
+                region.subtract(&self.windows[other.0].bounding_region());
+            }
+        }
+
+        region
+    }
+
+    /// Get the part of the root window that no other viewable window covers.
+    ///
+    /// This is where the background shows through. Unlike the root window's
+    /// [visible region](Self::visible_region), windows that aren't opaque still count as
+    /// covering the root. Returns an empty region if the table is empty.
+    pub fn uncovered_region(&self) -> Region<T, R> {
+        let root = match self.root {
+            Some(root) => root,
+            None => return Region::default(),
+        };
+
+        let mut region: Region<T, R> = core::iter::once(self.windows[root.0].rect).collect();
+        for (key, _) in self.paint_order().skip(1) {
+            region.subtract(&self.windows[key.0].bounding_region());
+        }
+
+        region
+    }
+
+    /// Visit the viewable windows in paint order, along with the parts of them that are visible.
+    ///
+    /// Each window is handed the rectangles of its [visible region](Self::visible_region), in
+    /// the same coordinates as the window rectangles. Windows that are entirely covered are
+    /// skipped. Painting each window's rectangles in the order they are visited draws the whole
+    /// screen without overdraw from opaque windows. The rectangles include the window's
+    /// [visual extents](Self::set_visual_extents), which are painted over the windows beneath.
+    pub fn composite(&self, mut f: impl FnMut(WindowKey, &[Rectangle<T>])) {
+        let root = match self.root {
+            Some(root) => self.windows[root.0].rect,
+            None => return,
+        };
+
+        // Sweep from the top down, keeping track of what the windows above have covered.
+        let order = self.paint_order().collect::<Vec<_>>();
+        let mut covered = Region::default();
+        let mut visible = Vec::with_capacity(order.len());
+
+        for &(key, _) in order.iter().rev() {
+            let window = &self.windows[key.0];
+            let bounds = window.bounding_region();
+
+            // Visual extents are painted, but don't hide the windows beneath.
+            let mut region = bounds.clone();
+            if window.visual_rect() != window.rect {
+                region.union_rect(window.visual_rect());
+            }
+            region.intersect_rect(root);
+            region.subtract(&covered);
+            visible.push((key, region));
+
+            if self.occludes(key) {
+                covered.union(&bounds);
+            }
+        }
+
+        for (key, region) in visible.iter().rev() {
+            if !region.is_empty() {
+                f(*key, region.rects());
+            }
+        }
+    }
+
+    /// Split the visible region of a window into horizontal runs of pixels.
+    ///
+    /// See [`Region::spans`] for how the spans are ordered and rounded.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn visible_spans(&self, key: WindowKey) -> Vec<Span> {
+        assert!(self.contains_key(key), "window does not exist");
+        self.visible_region(key)
+            .spans()
+            .into_iter()
+            .map(|(y, x_start, x_end)| Span {
+                y,
+                x_start,
+                x_end,
+                window: key,
+            })
+            .collect()
+    }
+
+    /// Split the damaged region into horizontal runs of pixels, each labelled with the window
+    /// that is visible there.
+    ///
+    /// This uses the damage accumulated since the last call to
+    /// [`take_damage`](Self::take_damage), without taking it. Spans are sorted by row, then
+    /// from left to right. Damaged areas that no window covers are left out.
+    pub fn damage_spans(&self) -> Vec<Span> {
+        let mut spans = Vec::new();
+        self.composite(|key, rects| {
+            let mut region = rects.iter().copied().collect::<Region<T, R>>();
+            region.intersect(&self.damage);
+            spans.extend(region.spans().into_iter().map(|(y, x_start, x_end)| Span {
+                y,
+                x_start,
+                x_end,
+                window: key,
+            }));
+        });
+
+        spans.sort_unstable_by_key(|span| (span.y, span.x_start));
+        spans
+    }
+
+    /// Record part of a window, relative to its origin, as exposed.
+    fn expose(&mut self, key: WindowKey, region: &Region<T, R>) {
+        if region.is_empty() {
+            return;
+        }
+
+        if let Some(window) = self.windows.get_mut(key.0) {
+            window.contents_valid = false;
+        }
+
+        match self.exposures.get_mut(key.0) {
+            Some(exposed) => exposed.union(region),
+            None => {
+                self.exposures.insert(key.0, region.clone());
+            }
+        }
+    }
+
+    /// Returns the rectangle of a window.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn rect(&self, key: WindowKey) -> Rectangle<T> {
+        self.windows[key.0].rect
+    }
+
+    /// Returns the content area of a window, inside of its border.
+    ///
+    /// The window's rectangle includes its border, and is used for hit testing and occlusion.
+    /// If the window's primary parent shrinks it below the width of its border, the content
+    /// area is empty.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn inner_rect(&self, key: WindowKey) -> Rectangle<T> {
+        let window = &self.windows[key.0];
+        let border = T::from_i32(window.border_width.into());
+        let rect = window.rect;
+
+        let left = coord::min(rect.left.saturating_add(border), rect.right);
+        let top = coord::min(rect.top.saturating_add(border), rect.bottom);
+        Rectangle {
+            left,
+            top,
+            right: coord::max(rect.right.saturating_sub(border), left),
+            bottom: coord::max(rect.bottom.saturating_sub(border), top),
+        }
+    }
+
+    /// Set the width of the border of a window.
+    ///
+    /// Like in X11, the border lies outside of the content area returned by
+    /// [`inner_rect`](Self::inner_rect). The content stays where it is, while the window's
+    /// rectangle grows or shrinks around it. The root window never has a border, so this does
+    /// nothing for it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn set_border_width(&mut self, key: WindowKey, width: u16) {
+        let window = &mut self.windows[key.0];
+        if window.border_width == width || self.root == Some(key) {
+            return;
+        }
+
+        let grow = T::from_i32(i32::from(width) - i32::from(window.border_width));
+        let rect = window.rect;
+        window.border_width = width;
+        let mut transaction = self.transaction();
+        transaction.place(
+            key,
+            Rectangle {
+                left: rect.left.saturating_sub(grow),
+                top: rect.top.saturating_sub(grow),
+                right: rect.right.saturating_add(grow),
+                bottom: rect.bottom.saturating_add(grow),
+            },
+        );
+        transaction.commit();
+    }
+
+    /// Returns the width of the border of a window.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn border_width(&self, key: WindowKey) -> u16 {
+        self.windows[key.0].border_width
+    }
+
+    /// Set how a window moves when its primary parent is moved or resized.
+    ///
+    /// The default gravity is [`Gravity::NorthWest`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn set_gravity(&mut self, key: WindowKey, gravity: Gravity) {
+        self.windows[key.0].gravity = gravity;
+    }
+
+    /// Returns how a window moves when its primary parent is moved or resized.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn gravity(&self, key: WindowKey) -> Gravity {
+        self.windows[key.0].gravity
+    }
+
+    /// Anchor the edges of a window to the edges of its primary parent.
+    ///
+    /// When the primary parent is moved or resized, the window is given the rectangle that
+    /// [`Anchors::resolve`] computes for the parent's new rectangle, instead of being moved
+    /// according to its [`Gravity`]. Passing `None` goes back to using the gravity, which is
+    /// the default. The window is not moved until its parent next changes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn set_anchors(&mut self, key: WindowKey, anchors: Option<Anchors<T>>) {
+        let window = &mut self.windows[key.0];
+        if anchors.is_some() || window.attributes.is_some() {
+            window.attributes_mut().anchors = anchors;
+        }
+    }
+
+    /// Make the rectangle of a window a fraction of the rectangle of its primary parent.
+    ///
+    /// When the primary parent is moved or resized, the window is given the rectangle that
+    /// [`RelativeRect::resolve`] computes for the parent's new rectangle. This takes precedence
+    /// over the window's [anchors](Self::set_anchors) and [`Gravity`]. Passing `None` makes the
+    /// window keep its rectangle again, which is the default. The window is not moved until
+    /// its parent next changes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn set_relative(&mut self, key: WindowKey, relative: Option<RelativeRect>) {
+        let window = &mut self.windows[key.0];
+        if relative.is_some() || window.attributes.is_some() {
+            window.attributes_mut().relative = relative;
+        }
+    }
+
+    /// Returns the fractions of its primary parent that a window's rectangle is made of.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn relative(&self, key: WindowKey) -> Option<RelativeRect> {
+        self.windows[key.0]
+            .attributes()
+            .and_then(|attributes| attributes.relative)
+    }
+
+    /// Returns how the edges of a window are anchored to its primary parent.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn anchors(&self, key: WindowKey) -> Option<Anchors<T>> {
+        self.windows[key.0]
+            .attributes()
+            .and_then(|attributes| attributes.anchors)
+    }
+
+    /// Set the constraints on the size of a window.
+    ///
+    /// These are enforced the next time the window is resized.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn set_size_hints(&mut self, key: WindowKey, hints: SizeHints<T>) {
+        self.windows[key.0].attributes_mut().size_hints = hints;
+    }
+
+    /// Returns the constraints on the size of a window.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn size_hints(&self, key: WindowKey) -> SizeHints<T> {
+        self.windows[key.0]
+            .attributes()
+            .map_or_else(SizeHints::default, |attributes| attributes.size_hints)
+    }
+
+    /// Change the rectangle of a window.
+    ///
+    /// The rectangle is [normalized](Rectangle::normalize), and empty rectangles are rejected.
+    /// The size of the rectangle is constrained by the window's [`SizeHints`], keeping its
+    /// top-left corner in place. The window is restacked on top of the windows beneath its new
+    /// rectangle, and its children are moved according to their [`Gravity`]. Returns the new
+    /// rectangle of the window.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn resize_window(
+        &mut self,
+        key: WindowKey,
+        rect: Rectangle<T>,
+    ) -> Result<Rectangle<T>, ConfigureError> {
+        let mut transaction = self.transaction();
+        let rect = transaction.configure(key, rect)?;
+        transaction.commit();
+
+        Ok(rect)
+    }
+
+    /// Change the rectangle of the root window.
+    ///
+    /// The root's children are moved according to their [`Gravity`]. Any window that no longer
+    /// overlaps the root afterwards is moved back across the edge of the root it fell past,
+    /// until its own edge on that side lines up with it, taking its children with it. A window
+    /// that fits ends up entirely on the root. Returns the windows that had to be moved back.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the table has no root window.
+    pub fn resize_root(&mut self, rect: Rectangle<T>) -> Result<Vec<WindowKey>, ConfigureError> {
+        assert!(self.root.is_some(), "table has no root window");
+
+        let rect = rect.normalize();
+        if rect.is_empty() {
+            return Err(ConfigureError::EmptyRectangle);
+        }
+
+        Ok(transaction::resize_root(self, rect))
+    }
+
+    /// Check that a window can be given this rectangle, applying its size hints.
+    fn check_configure(
+        &self,
+        key: WindowKey,
+        rect: Rectangle<T>,
+    ) -> Result<Rectangle<T>, ConfigureError> {
+        let window = self.windows.get(key.0).expect("window does not exist");
+
+        let root = self.root.unwrap();
+        if key == root {
+            return Err(ConfigureError::RootWindow);
+        }
+
+        let rect = rect.normalize();
+        if rect.is_empty() {
+            return Err(ConfigureError::EmptyRectangle);
+        }
+
+        let size = window
+            .attributes()
+            .map_or_else(SizeHints::default, |attributes| attributes.size_hints)
+            .constrain(rect.size());
+        let rect = Rectangle::from_origin_size(rect.origin(), size);
+
+        // The size hints may have shrunk the window down to nothing, or left no room for the
+        // content inside of its border.
+        let border = T::from_i32(i32::from(window.border_width) * 2);
+        if rect.is_empty() || rect.width() <= border || rect.height() <= border {
+            return Err(ConfigureError::EmptyRectangle);
+        }
+
+        if !rect.intersects(self.windows[root.0].rect) {
+            return Err(ConfigureError::OutsideRoot);
+        }
+
+        Ok(rect)
+    }
+
+    /// Move a window so that its top-left corner is at the given position.
+    ///
+    /// Returns the new rectangle of the window.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn move_window(
+        &mut self,
+        key: WindowKey,
+        position: impl Into<Point<T>>,
+    ) -> Result<Rectangle<T>, ConfigureError> {
+        let rect = self.rect(key);
+        self.resize_window(
+            key,
+            Rectangle::from_origin_size(position.into(), rect.size()),
+        )
+    }
+
+    /// Raise a window, along with its descendants, to the top of the stack.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn raise(&mut self, key: WindowKey) {
+        let mut transaction = self.transaction();
+        transaction.raise(key);
+        transaction.commit();
+    }
+
+    /// Lower a window, along with its descendants, to the bottom of the stack.
+    ///
+    /// The window remains above the root window.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn lower(&mut self, key: WindowKey) {
+        let mut transaction = self.transaction();
+        transaction.lower(key);
+        transaction.commit();
+    }
+
+    /// Restack one of the children of a window, like X11's `CirculateWindow`.
+    ///
+    /// With [`CirculateDirection::RaiseLowest`], the lowest mapped child that is overlapped by
+    /// one of its siblings is raised above its siblings. With
+    /// [`CirculateDirection::LowerHighest`], the highest mapped child that overlaps one of its
+    /// siblings is lowered beneath them. The child's descendants move with it. Returns the
+    /// child that was restacked, or `None` if no child is overlapping another.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn circulate(
+        &mut self,
+        parent: WindowKey,
+        direction: CirculateDirection,
+    ) -> Option<WindowKey> {
+        let child = self.circulate_target(parent, direction);
+        let mut transaction = self.transaction();
+        transaction.circulate(parent, direction);
+        transaction.commit();
+        child
+    }
+
+    /// Move a window into a layer of the stacking order.
+    ///
+    /// Windows in higher layers are always stacked above windows in lower layers, no matter how
+    /// they are raised or lowered; raising and lowering only moves a window within its layer.
+    /// The window keeps its place relative to the other windows in its new layer. The root
+    /// window always stays at the bottom, whatever its layer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn set_layer(&mut self, key: WindowKey, layer: Layer) {
+        let mut transaction = self.transaction();
+        transaction.set_layer(key, layer);
+        transaction.commit();
+    }
+
+    /// Returns the layer of the stacking order that a window is kept in.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn layer(&self, key: WindowKey) -> Layer {
+        self.windows[key.0].layer
+    }
+
+    /// Give a window an explicit position within its layer.
+    ///
+    /// Within a layer, windows with a higher z-index are always stacked above windows with a
+    /// lower one, and raising and lowering only moves a window among the windows with the same
+    /// z-index. Windows keep their relative order when their z-indices are equal, so the
+    /// default of zero for every window leaves the stacking order entirely up to raising and
+    /// lowering.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn set_z_index(&mut self, key: WindowKey, z_index: i32) {
+        let mut transaction = self.transaction();
+        transaction.set_z_index(key, z_index);
+        transaction.commit();
+    }
+
+    /// Returns the z-index of a window within its layer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn z_index(&self, key: WindowKey) -> i32 {
+        self.windows[key.0]
+            .attributes()
+            .map_or(0, |attributes| attributes.z_index)
+    }
+
+    /// Get the child that circulating the children of a window would restack.
+    fn circulate_target(
+        &self,
+        parent: WindowKey,
+        direction: CirculateDirection,
+    ) -> Option<WindowKey> {
+        let children = self
+            .children(parent)
+            .filter(|&child| self.windows[child.0].mapped)
+            .collect::<SmallVec<[_; 8]>>();
+        let overlaps =
+            |a: WindowKey, b: WindowKey| self.windows[a.0].rect.intersects(self.windows[b.0].rect);
+
+        match direction {
+            CirculateDirection::RaiseLowest => {
+                children.iter().enumerate().find_map(|(i, &child)| {
+                    children[i + 1..]
+                        .iter()
+                        .any(|&above| overlaps(child, above))
+                        .then_some(child)
+                })
+            }
+            CirculateDirection::LowerHighest => {
+                children.iter().enumerate().rev().find_map(|(i, &child)| {
+                    children[..i]
+                        .iter()
+                        .any(|&below| overlaps(child, below))
+                        .then_some(child)
+                })
+            }
+        }
+    }
+
+    /// Remove a window from the table.
+    ///
+    /// Windows stacked on top of the removed window are re-parented to the windows beneath it.
+    /// Removing the root window removes every window in the table. Returns the rectangle of the
+    /// removed window, or `None` if the window did not exist.
+    pub fn remove(&mut self, key: WindowKey) -> Option<Rectangle<T>> {
+        let rect = self.windows.get(key.0)?.rect;
+        let mut transaction = self.transaction();
+        transaction.remove(key);
+        transaction.commit();
+
+        Some(rect)
+    }
+
+    /// Remove every window that the predicate returns `false` for.
+    ///
+    /// This is equivalent to removing each of the windows in a single [`Transaction`], and is much
+    /// faster than removing them one at a time. If the root window is removed, every window in
+    /// the table is removed. Returns the region of the screen exposed by removing the windows.
+    pub fn retain(&mut self, mut f: impl FnMut(WindowKey, Rectangle<T>) -> bool) -> Region<T, R> {
+        let removed = self
+            .windows
+            .iter()
+            .map(|(key, window)| (WindowKey(key), window.rect))
+            .filter(|&(key, rect)| !f(key, rect))
+            .map(|(key, _)| key)
+            .collect::<Vec<_>>();
+
+        let mut transaction = self.transaction();
+        for key in removed {
+            transaction.remove(key);
+        }
+        transaction.commit()
+    }
+
+    /// Map a window, making it visible.
+    ///
+    /// Windows are mapped when they are inserted.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn map(&mut self, key: WindowKey) {
+        let mut transaction = self.transaction();
+        transaction.map(key);
+        transaction.commit();
+    }
+
+    /// Unmap a window, hiding it and its descendants.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn unmap(&mut self, key: WindowKey) {
+        let mut transaction = self.transaction();
+        transaction.unmap(key);
+        transaction.commit();
+    }
+
+    /// Tell if a window is mapped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn is_mapped(&self, key: WindowKey) -> bool {
+        self.windows[key.0].mapped
+    }
+
+    /// Tell if a window is viewable.
+    ///
+    /// A window is viewable if it and all of its ancestors are mapped. Returns `false` if the
+    /// window does not exist.
+    pub fn is_viewable(&self, key: WindowKey) -> bool {
+        self.windows.contains_key(key.0)
+            && self
+                .ancestors(key)
+                .all(|ancestor| self.windows[ancestor.0].mapped)
+    }
+
+    /// Returns the primary parent of a window.
+    ///
+    /// A window may be stacked on top of several windows. Its primary parent is the one that
+    /// most of the window lies on. Returns `None` for the root window and for windows that do
+    /// not exist.
+    pub fn parent(&self, key: WindowKey) -> Option<WindowKey> {
+        self.windows
+            .get(key.0)
+            .and_then(|window| window.parents.first().copied())
+    }
+
+    /// Set the region of a window that accepts input.
+    ///
+    /// The region is relative to the window's top-left corner. Parts of the region that fall
+    /// outside of the window are ignored. By default, the entire window accepts input; use
+    /// [`reset_input_region`](Self::reset_input_region) to go back to that.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn set_input_region(&mut self, key: WindowKey, region: Region<T, R>) {
+        self.windows[key.0].input_region = Some(region);
+    }
+
+    /// Make the entire window accept input again, undoing
+    /// [`set_input_region`](Self::set_input_region).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn reset_input_region(&mut self, key: WindowKey) {
+        self.windows[key.0].input_region = None;
+    }
+
+    /// Returns the input region of a window, relative to the window's top-left corner.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn input_region(&self, key: WindowKey) -> Option<&Region<T, R>> {
+        self.windows[key.0].input_region.as_ref()
+    }
+
+    /// Set the shape of a window.
+    ///
+    /// The shape is relative to the window's top-left corner, and is clipped to the window's
+    /// rectangle. It replaces the rectangle for hit testing and for hiding the windows beneath.
+    /// Passing `None` makes the window shaped like its rectangle, which is the default.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn set_shape(&mut self, key: WindowKey, shape: Option<Region<T, R>>) {
+        self.windows[key.0].shape = shape;
+
+        if self.is_viewable(key) {
+            let rect = self.windows[key.0].visual_rect();
+            self.add_damage_rect(rect);
+        }
+    }
+
+    /// Returns the shape of a window, relative to the window's top-left corner.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn shape(&self, key: WindowKey) -> Option<&Region<T, R>> {
+        self.windows[key.0].shape.as_ref()
+    }
+
+    /// Set whether a window is transparent to input.
+    ///
+    /// Input-transparent windows are skipped by hit testing, so input falls through to the
+    /// windows beneath them. They still occupy space for the purposes of occlusion and painting.
+    /// The window's children are not affected.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn set_input_transparent(&mut self, key: WindowKey, transparent: bool) {
+        self.windows[key.0].input_transparent = transparent;
+    }
+
+    /// Tell if a window is transparent to input.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn is_input_transparent(&self, key: WindowKey) -> bool {
+        self.windows[key.0].input_transparent
+    }
+
+    /// Give a window a name.
+    ///
+    /// Names are shown in the debug output of the table, and windows can be looked up by name
+    /// with [`find_by_name`](Self::find_by_name). They don't have to be unique.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn set_name(&mut self, key: WindowKey, name: &str) {
+        let attributes = self.windows[key.0].attributes_mut();
+        match &mut attributes.name {
+            Some(old) => {
+                old.clear();
+                old.push_str(name);
+            }
+            None => attributes.name = Some(name.into()),
+        }
+    }
+
+    /// Remove the name of a window.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn clear_name(&mut self, key: WindowKey) {
+        if let Some(attributes) = &mut self.windows[key.0].attributes {
+            attributes.name = None;
+        }
+    }
+
+    /// Returns the name of a window.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn name(&self, key: WindowKey) -> Option<&str> {
+        self.windows[key.0]
+            .attributes()
+            .and_then(|attributes| attributes.name.as_deref())
+    }
+
+    /// Get the atom for a property name, creating it if it doesn't exist.
+    ///
+    /// Interning the same name twice returns the same atom. Atoms stay valid when the table is
+    /// cleared.
+    pub fn intern_atom(&mut self, name: &str) -> Atom {
+        self.atoms.intern(name)
+    }
+
+    /// Get the atom for a property name, if it has been interned.
+    pub fn atom(&self, name: &str) -> Option<Atom> {
+        self.atoms.get(name)
+    }
+
+    /// Returns the name of an atom, or `None` if it was not created by this table.
+    pub fn atom_name(&self, atom: Atom) -> Option<&str> {
+        self.atoms.name(atom)
+    }
+
+    /// Set a property of a window, returning its old value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn set_property(
+        &mut self,
+        key: WindowKey,
+        atom: Atom,
+        value: Property,
+    ) -> Option<Property> {
+        let old = self.windows[key.0]
+            .attributes_mut()
+            .properties
+            .insert(atom, value);
+        self.property_events.push(PropertyEvent {
+            window: key,
+            atom,
+            state: PropertyState::NewValue,
+        });
+        old
+    }
+
+    /// Delete a property of a window, returning its value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn delete_property(&mut self, key: WindowKey, atom: Atom) -> Option<Property> {
+        let old = self.windows[key.0]
+            .attributes
+            .as_mut()
+            .and_then(|attributes| attributes.properties.remove(&atom));
+        if old.is_some() {
+            self.property_events.push(PropertyEvent {
+                window: key,
+                atom,
+                state: PropertyState::Deleted,
+            });
+        }
+        old
+    }
+
+    /// Returns a property of a window.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn property(&self, key: WindowKey, atom: Atom) -> Option<&Property> {
+        self.windows[key.0]
+            .attributes()
+            .and_then(|attributes| attributes.properties.get(&atom))
+    }
+
+    /// Iterate over the properties of a window.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn properties(&self, key: WindowKey) -> impl Iterator<Item = (Atom, &Property)> + '_ {
+        self.windows[key.0]
+            .attributes()
+            .into_iter()
+            .flat_map(|attributes| attributes.properties.iter())
+            .map(|(&atom, value)| (atom, value))
+    }
+
+    /// Take the changes to the properties of windows since the last call to this function.
+    ///
+    /// The events may refer to windows that no longer exist.
+    pub fn take_property_events(&mut self) -> Vec<PropertyEvent> {
+        core::mem::take(&mut self.property_events)
+    }
+
+    /// Find a window by its name.
+    ///
+    /// If several windows have this name, the topmost one is returned.
+    pub fn find_by_name(&self, name: &str) -> Option<WindowKey> {
+        self.order.iter().rev().copied().find(|key| {
+            self.windows[key.0]
+                .attributes()
+                .and_then(|attributes| attributes.name.as_deref())
+                == Some(name)
+        })
+    }
+
+    /// Set the scale factor of a window.
+    ///
+    /// The scale factor is the number of physical pixels per unit of the logical coordinates
+    /// used by the table. Passing `None` makes the window inherit the scale factor of its primary
+    /// parent, which is the default. A root window without a scale factor has a scale of 1.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist, or if the scale factor is not a positive, finite
+    /// number.
+    pub fn set_scale_factor(&mut self, key: WindowKey, scale_factor: Option<f64>) {
+        if let Some(scale_factor) = scale_factor {
+            assert!(
+                scale_factor > 0.0 && scale_factor.is_finite(),
+                "scale factor must be positive and finite"
+            );
+        }
+
+        self.windows[key.0].attributes_mut().scale_factor = scale_factor;
+    }
+
+    /// Returns the scale factor of a window, inherited from its ancestors if it has none.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn scale_factor(&self, key: WindowKey) -> f64 {
+        assert!(self.contains_key(key), "window does not exist");
+        self.ancestors(key)
+            .find_map(|ancestor| {
+                self.windows[ancestor.0]
+                    .attributes()
+                    .and_then(|attributes| attributes.scale_factor)
+            })
+            .unwrap_or(1.0)
+    }
+
+    /// Convert a point from logical coordinates into physical pixels, using the scale factor of
+    /// a window.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn to_physical(&self, key: WindowKey, point: impl Into<Point<T>>) -> Point<T> {
+        let scale = self.scale_factor(key);
+        let Point { x, y } = point.into();
+        Point::new(
+            T::from_f64(x.to_f64() * scale),
+            T::from_f64(y.to_f64() * scale),
+        )
+    }
+
+    /// Convert a point from physical pixels into logical coordinates, using the scale factor of
+    /// a window.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn to_logical(&self, key: WindowKey, point: impl Into<Point<T>>) -> Point<T> {
+        let scale = self.scale_factor(key);
+        let Point { x, y } = point.into();
+        Point::new(
+            T::from_f64(x.to_f64() / scale),
+            T::from_f64(y.to_f64() / scale),
+        )
+    }
+
+    /// Returns the rectangle of a window in physical pixels.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn physical_rect(&self, key: WindowKey) -> Rectangle<T> {
+        let rect = self.windows[key.0].rect;
+        let top_left = self.to_physical(key, rect.origin());
+        let bottom_right = self.to_physical(key, (rect.right, rect.bottom));
+        Rectangle::new(top_left.x, top_left.y, bottom_right.x, bottom_right.y)
+    }
+
+    /// Iterate over a window and its ancestors, following primary parents up to the root.
+    ///
+    /// The iterator is empty if the window does not exist.
+    pub fn ancestors(&self, key: WindowKey) -> Ancestors<'_, T, N, R> {
+        Ancestors {
+            table: self,
+            next: Some(key).filter(|key| self.windows.contains_key(key.0)),
+        }
+    }
+
+    /// Iterate over the children of a window, from the bottom of the stack up.
+    ///
+    /// These are the windows whose primary parent is this window. The iterator is empty if the
+    /// window does not exist.
+    pub fn children(&self, key: WindowKey) -> impl Iterator<Item = WindowKey> + '_ {
+        self.windows
+            .get(key.0)
+            .into_iter()
+            .flat_map(|window| window.children.iter().copied())
+            .filter(move |&child| self.parent(child) == Some(key))
+    }
+
+    /// Traverse a window and its descendants in the given order.
+    ///
+    /// The iterator is empty if the window does not exist.
+    pub fn traverse(&self, key: WindowKey, order: Order) -> Traverse<'_, T, N, R> {
+        let mut pending = VecDeque::new();
+        if self.windows.contains_key(key.0) {
+            pending.push_back(key);
+        }
+
+        Traverse {
+            table: self,
+            order,
+            pending,
+        }
+    }
+
+    /// Returns the windows in the order they are stacked, from the bottom up.
+    ///
+    /// Every window comes after all of its parents.
+    fn stacking_order(&self) -> Vec<WindowKey> {
+        self.order.clone()
+    }
+
+    /// Get a window and its descendants, in the given stacking order.
+    ///
+    /// A window's descendants are the windows whose primary parent is the window or one of its
+    /// descendants.
+    fn subtree_in(&self, order: &[WindowKey], key: WindowKey) -> Vec<WindowKey> {
+        let mut subtree = SecondaryMap::<Key, ()>::new();
+        subtree.insert(key.0, ());
+
+        order
+            .iter()
+            .copied()
+            .filter(|&window| {
+                let inside = window == key
+                    || matches!(
+                        self.parent(window),
+                        Some(parent) if subtree.contains_key(parent.0)
+                    );
+                if inside {
+                    subtree.insert(window.0, ());
+                }
+                inside
+            })
+            .collect()
+    }
+
+    /// Rebuild the links between windows, stacking them in the given order.
+    ///
+    /// The first window in the order must be the root window. Windows in the order that no
+    /// longer exist are skipped.
+    fn relink(&mut self, order: &[WindowKey]) {
+        for window in self.windows.values_mut() {
+            window.parents.clear();
+            window.children.clear();
+        }
+
+        let mut sorted = core::mem::take(&mut self.order);
+        sorted.clear();
+        sorted.extend(
+            order
+                .iter()
+                .copied()
+                .filter(|key| self.windows.contains_key(key.0)),
+        );
+        self.sort_layers(&mut sorted);
+
+        for (z, key) in sorted.iter().enumerate() {
+            self.windows[key.0].z = z;
+        }
+        self.next_z = sorted.len();
+        self.order = sorted;
+
+        for &key in order.iter().skip(1) {
+            if self.windows.contains_key(key.0) {
+                self.link(key);
+            }
+        }
+    }
+
+    /// Sort a stacking order into layers and z-indices, keeping the order of the windows that
+    /// share both.
+    ///
+    /// The root window stays at the bottom, with the background window directly above it.
+    /// Windows that no longer exist are moved to the bottom too, to be skipped when relinking.
+    /// Transient windows are then moved above their owners.
+    fn sort_layers(&self, order: &mut Vec<WindowKey>) {
+        order.sort_by_key(|&key| {
+            (
+                Some(key) != self.root,
+                Some(key) != self.background,
+                self.windows
+                    .get(key.0)
+                    .map(|window| (window.layer, self.z_index(key))),
+            )
+        });
+
+        if !self.transients.is_empty() {
+            self.stack_transients(order);
+        }
+    }
+
+    /// Link a window to the windows beneath it.
+    fn link(&mut self, key: WindowKey) {
+        let Window { rect, z, .. } = self.windows[key.0];
+
+        // Split the window onto the windows beneath it, totalling up how much of the window lies
+        // on each of them. The worklist of pieces is kept in buffers shared between calls.
+        let mut scratch = core::mem::take(&mut self.scratch);
+        let mut parents = SmallVec::<[(WindowKey, T); N]>::new();
+        self.split_onto(
+            rect,
+            &mut scratch,
+            |key| self.windows[key.0].z < z,
+            |parent, piece| match parents.iter_mut().find(|(key, _)| *key == parent) {
+                Some((_, area)) => *area = area.saturating_add(piece.saturating_area()),
+                None => parents.push((parent, piece.saturating_area())),
+            },
+        );
+        self.scratch = scratch;
+
+        // The parent that the window mostly lies on is the primary parent.
+        parents.sort_by(|(_, a), (_, b)| b.partial_cmp(a).unwrap_or(cmp::Ordering::Equal));
+
+        // Set children.
+        for (parent, _) in &parents {
+            self.windows[parent.0].children.push(key);
+        }
+        self.windows[key.0].parents = parents.into_iter().map(|(parent, _)| parent).collect();
+    }
+
+    /// Get the topmost window that accepts input at a position given in physical pixels.
+    ///
+    /// The position is converted into logical coordinates using the scale factor of the root
+    /// window.
+    pub fn window_at_physical(&self, position: impl Into<Point<T>>) -> Option<WindowKey> {
+        let root = self.root?;
+        self.window_at(self.to_logical(root, position))
+    }
+
+    /// Get the topmost window that accepts input at a position given in output coordinates.
+    pub fn window_at_output(&self, position: impl Into<Point<T>>) -> Option<WindowKey> {
+        self.window_at(self.from_output(position))
+    }
+
+    /// Get the topmost window that accepts input at the given position.
+    pub fn window_at(&self, position: impl Into<Point<T>>) -> Option<WindowKey> {
+        let position = position.into();
+        self.index
+            .query_point(position)
+            .chain(self.input_margins.iter().copied())
+            .filter(|key| self.windows[key.0].accepts_input(position) && self.is_viewable(*key))
+            .max_by_key(|key| self.windows[key.0].z)
+    }
+
+    /// Get the window whose rectangle is closest to a point, out of those accepted by `filter`.
+    ///
+    /// The distance is zero for windows that contain the point, so the topmost of those wins.
+    /// The right and bottom edges are exclusive, so a window whose edge lies on the point loses
+    /// to one that contains it. Otherwise, ties also go to the higher window. Returns `None` if
+    /// no windows are accepted.
+    pub fn nearest_window(
+        &self,
+        point: impl Into<Point<T>>,
+        filter: impl Fn(WindowKey) -> bool,
+    ) -> Option<WindowKey> {
+        let Point { x, y } = point.into();
+        let gap = |point: T, low: T, high: T| {
+            if point < low {
+                coord::distance(point, low).to_f64()
+            } else if point >= high {
+                coord::distance(point, high).to_f64()
+            } else {
+                0.0
+            }
+        };
+
+        let mut best: Option<((f64, bool), WindowKey)> = None;
+        for &key in self.order.iter().rev() {
+            if !filter(key) {
+                continue;
+            }
+
+            // Compare squared distances, since there's no square root without `std`.
+            let rect = self.windows[key.0].rect;
+            let (dx, dy) = (gap(x, rect.left, rect.right), gap(y, rect.top, rect.bottom));
+            let distance = (dx * dx + dy * dy, !rect.contains((x, y)));
+
+            if !matches!(best, Some((best, _)) if best <= distance) {
+                best = Some((distance, key));
+            }
+        }
+
+        best.map(|(_, key)| key)
+    }
+
+    /// Get the path of windows leading to the topmost window that accepts input at this position.
+    ///
+    /// The path begins with the root window and ends with the window that was hit.
+    fn hit_path(&self, position: Point<T>) -> SmallVec<[WindowKey; 3]> {
+        let mut path = match self.window_at(position) {
+            Some(hit) => self.ancestors(hit).collect::<SmallVec<_>>(),
+            None => SmallVec::new(),
+        };
+
+        path.reverse();
+        path
+    }
+
+    /// Split a rectangle into the pieces that lie on the windows accepted by `filter`.
+    ///
+    /// Windows are considered from the top down, and each piece is handed to `f` along with the
+    /// topmost window it lies on. The pieces that don't lie on any window are left in
+    /// `scratch.rects`. This works through an explicit worklist of pieces rather than
+    /// recursing, so deep stacks of windows don't use up the call stack.
+    fn split_onto(
+        &self,
+        rect: Rectangle<T>,
+        scratch: &mut QueryScratch<T>,
+        filter: impl Fn(WindowKey) -> bool,
+        mut f: impl FnMut(WindowKey, Rectangle<T>),
+    ) {
+        // Find the windows, from the top down.
+        self.index.query_rect_into(rect, &mut scratch.windows);
+        scratch.windows.retain(|&key| filter(key));
+        scratch
+            .windows
+            .sort_unstable_by_key(|key| cmp::Reverse(self.windows[key.0].z));
+
+        scratch.rects.clear();
+        scratch.rects.push(rect);
+
+        for &candidate in &scratch.windows {
+            if scratch.rects.is_empty() {
+                break;
+            }
+
+            let window = self.windows[candidate.0].rect;
+            scratch.uncovered.clear();
+
+            for &rect in &scratch.rects {
+                match rect.split(window) {
+                    Some((intersection, remainder)) => {
+                        // This piece lies on the candidate; the rest may lie on lower windows.
+                        f(candidate, intersection);
+                        scratch.uncovered.extend(remainder);
+                    }
+                    None => scratch.uncovered.push(rect),
+                }
+            }
+
+            core::mem::swap(&mut scratch.rects, &mut scratch.uncovered);
+        }
+    }
+
+    /// Split a rectangle into the pieces that lie on each viewable window.
+    ///
+    /// Windows are considered from the top of the stack down, and each piece of the rectangle
+    /// is handed to `f` once, along with the topmost viewable window that it lies on. Window
+    /// shapes and opacity are not taken into account. Parts of the rectangle that don't lie on
+    /// any window are skipped.
+    ///
+    /// The scratch space is reused between calls, so this does not allocate once it has grown
+    /// large enough.
+    pub fn for_each_intersection(
+        &self,
+        rect: Rectangle<T>,
+        scratch: &mut QueryScratch<T>,
+        f: impl FnMut(WindowKey, Rectangle<T>),
+    ) {
+        self.split_onto(rect, scratch, |key| self.is_viewable(key), f);
+    }
+
+    /// Visit the windows that overlap a rectangle, from the top of the stack down.
+    ///
+    /// The scratch space is reused between calls, so this does not allocate once it has grown
+    /// large enough.
+    pub fn for_each_window_in(
+        &self,
+        rect: Rectangle<T>,
+        scratch: &mut QueryScratch<T>,
+        f: impl FnMut(WindowKey),
+    ) {
+        self.index.query_rect_into(rect, &mut scratch.windows);
+        scratch
+            .windows
+            .retain(|key| self.windows[key.0].rect.intersects(rect));
+        scratch
+            .windows
+            .sort_unstable_by_key(|key| cmp::Reverse(self.windows[key.0].z));
+        scratch.windows.iter().copied().for_each(f);
+    }
+
+    /// Get the windows that overlap a rectangle, from the top of the stack down.
+    ///
+    /// This is useful for rubber-band selection. Use
+    /// [`for_each_window_in`](Self::for_each_window_in) to avoid allocating.
+    pub fn windows_in_rect(&self, rect: Rectangle<T>) -> Vec<WindowKey> {
+        let mut windows = Vec::new();
+        self.for_each_window_in(rect, &mut QueryScratch::default(), |key| windows.push(key));
+        windows
+    }
+
+    /// Get the viewable windows that can be seen somewhere in a rectangle, from the top of the
+    /// stack down.
+    ///
+    /// Windows that are entirely covered by other viewable windows within the rectangle are
+    /// left out, which makes this useful for repainting part of the screen. Like
+    /// [`for_each_intersection`](Self::for_each_intersection), window shapes and opacity are not
+    /// taken into account.
+    pub fn visible_windows_in_rect(&self, rect: Rectangle<T>) -> Vec<WindowKey> {
+        let mut windows = Vec::new();
+        self.for_each_intersection(rect, &mut QueryScratch::default(), |key, _| {
+            if !windows.contains(&key) {
+                windows.push(key);
+            }
+        });
+        windows
+    }
+
+    /// Get the pieces that a window was split into when it was linked to the windows beneath it.
+    ///
+    /// Each piece is returned along with the parent that it lies on, so it can be drawn with
+    /// that parent's clip. The pieces are split the same way as when the window was linked, so
+    /// every parent of the window has at least one piece. Parts of the window that don't lie on
+    /// any other window are skipped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn fragments(
+        &self,
+        key: WindowKey,
+    ) -> impl ExactSizeIterator<Item = (WindowKey, Rectangle<T>)> {
+        assert!(self.contains_key(key), "window does not exist");
+        let Window { rect, z, .. } = self.windows[key.0];
+
+        let mut fragments = Vec::new();
+        self.split_onto(
+            rect,
+            &mut QueryScratch::default(),
+            |key| self.windows[key.0].z < z,
+            |parent, piece| fragments.push((parent, piece)),
+        );
+        fragments.into_iter()
+    }
+}
+
+/// An iterator over a window and its ancestors.
+///
+/// Returned by [`WindowTable::ancestors`].
+#[derive(Debug, Clone)]
+pub struct Ancestors<'a, T: Coordinate = i32, const N: usize = 3, const R: usize = 4> {
+    /// The table being traversed.
+    table: &'a WindowTable<T, N, R>,
+
+    /// The next window to yield.
+    next: Option<WindowKey>,
+}
+
+impl<T: Coordinate, const N: usize, const R: usize> Iterator for Ancestors<'_, T, N, R> {
+    type Item = WindowKey;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let key = self.next?;
+        self.next = self.table.parent(key);
+        Some(key)
+    }
+}
+
+impl<T: Coordinate, const N: usize, const R: usize> core::iter::FusedIterator
+    for Ancestors<'_, T, N, R>
+{
+}
+
+/// A coarse layer of the stacking order.
+///
+/// Windows in higher layers are always stacked above windows in lower layers. Set with
+/// [`WindowTable::set_layer`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum Layer {
+    /// Beneath every other window, such as desktop icons.
+    Background,
+
+    /// Ordinary windows.
+    #[default]
+    Normal,
+
+    /// Above ordinary windows, such as panels and docks.
+    Dock,
+
+    /// Above everything else, such as notifications and on-screen displays.
+    Overlay,
+}
+
+/// What happens when a new window overlaps the windows already in the table.
+///
+/// Set with [`WindowTable::set_overlap_policy`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub enum OverlapPolicy {
+    /// Windows must nest.
+    ///
+    /// A new window must lie entirely inside the root window, and must either lie entirely
+    /// inside or entirely contain each window it overlaps. Otherwise it is rejected with
+    /// [`InsertError::Overlaps`] or [`InsertError::OutsideRoot`].
+    Reject,
+
+    /// A new window is clipped to the window it mostly lies on, which becomes its primary
+    /// parent.
+    ClipToParent,
+
+    /// A new window may overlap any windows, and is stacked on top of each window beneath it.
+    #[default]
+    AllowOverlap,
+}
+
+/// How a window moves when its parent is moved or resized.
+///
+/// Each variant names the point of the parent that the window stays in place relative to.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub enum Gravity {
+    /// The top-left corner.
+    #[default]
+    NorthWest,
+
+    /// The middle of the top edge.
+    North,
+
+    /// The top-right corner.
+    NorthEast,
+
+    /// The middle of the left edge.
+    West,
+
+    /// The center.
+    Center,
+
+    /// The middle of the right edge.
+    East,
+
+    /// The bottom-left corner.
+    SouthWest,
+
+    /// The middle of the bottom edge.
+    South,
+
+    /// The bottom-right corner.
+    SouthEast,
+
+    /// The window does not move.
+    Static,
+
+    /// The window is unmapped instead of being moved.
+    Unmap,
+}
+
+impl Gravity {
+    /// Get how far a child moves when its parent changes from `old` to `new`.
+    ///
+    /// Returns `None` if the child should be unmapped instead.
+    fn offset<T: Coordinate>(self, old: Rectangle<T>, new: Rectangle<T>) -> Option<(T, T)> {
+        let left = new.left.saturating_sub(old.left);
+        let top = new.top.saturating_sub(old.top);
+        let right = new.right.saturating_sub(old.right);
+        let bottom = new.bottom.saturating_sub(old.bottom);
+        let center_x = coord::midpoint(new.left, new.right)
+            .saturating_sub(coord::midpoint(old.left, old.right));
+        let center_y = coord::midpoint(new.top, new.bottom)
+            .saturating_sub(coord::midpoint(old.top, old.bottom));
+
+        Some(match self {
+            Gravity::NorthWest => (left, top),
+            Gravity::North => (center_x, top),
+            Gravity::NorthEast => (right, top),
+            Gravity::West => (left, center_y),
+            Gravity::Center => (center_x, center_y),
+            Gravity::East => (right, center_y),
+            Gravity::SouthWest => (left, bottom),
+            Gravity::South => (center_x, bottom),
+            Gravity::SouthEast => (right, bottom),
+            Gravity::Static => (T::ZERO, T::ZERO),
+            Gravity::Unmap => return None,
+        })
+    }
+}
+
+/// How the edges of a window follow the edges of its primary parent.
+///
+/// Edge anchors are distances inward from the matching edge of the parent, so
+/// `right: Some(8)` keeps the window's right edge 8 units left of the parent's right edge.
+/// Along each axis:
+///
+/// * If both edges are anchored, the window stretches between them and the size is ignored.
+/// * If one edge is anchored, the window keeps that edge in place and takes the given size, or
+///   keeps its current size if there is none.
+/// * If neither edge is anchored, the window keeps its position along that axis, following the
+///   parent's top-left corner, and takes the given size, or keeps its current size.
+///
+/// Set with [`WindowTable::set_anchors`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Anchors<T = i32> {
+    /// The distance from the parent's left edge to the window's left edge.
+    pub left: Option<T>,
+
+    /// The distance from the parent's top edge to the window's top edge.
+    pub top: Option<T>,
+
+    /// The distance from the window's right edge to the parent's right edge.
+    pub right: Option<T>,
+
+    /// The distance from the window's bottom edge to the parent's bottom edge.
+    pub bottom: Option<T>,
+
+    /// The width of the window.
+    pub width: Option<T>,
+
+    /// The height of the window.
+    pub height: Option<T>,
+}
+
+impl<T: Coordinate> Anchors<T> {
+    /// Compute the rectangle of a window from the rectangle of its parent.
+    ///
+    /// `rect` is the window's current rectangle, used along the axes and for the sizes that
+    /// aren't anchored.
+    pub fn resolve(&self, parent: Rectangle<T>, rect: Rectangle<T>) -> Rectangle<T> {
+        // Lay out one axis.
+        let axis = |start: Option<T>,
+                    end: Option<T>,
+                    length: Option<T>,
+                    (low, high): (T, T),
+                    (current_low, current_high): (T, T)| {
+            let length = length.unwrap_or_else(|| current_high.saturating_sub(current_low));
+            match (start, end) {
+                (Some(start), Some(end)) => (low.saturating_add(start), high.saturating_sub(end)),
+                (Some(start), None) => {
+                    let low = low.saturating_add(start);
+                    (low, low.saturating_add(length))
+                }
+                (None, Some(end)) => {
+                    let high = high.saturating_sub(end);
+                    (high.saturating_sub(length), high)
+                }
+                (None, None) => (current_low, current_low.saturating_add(length)),
+            }
+        };
+
+        let (left, right) = axis(
+            self.left,
+            self.right,
+            self.width,
+            (parent.left, parent.right),
+            (rect.left, rect.right),
+        );
+        let (top, bottom) = axis(
+            self.top,
+            self.bottom,
+            self.height,
+            (parent.top, parent.bottom),
+            (rect.top, rect.bottom),
+        );
+
+        Rectangle::new(left, top, right, bottom)
+    }
+}
+
+/// A rectangle expressed as fractions of another rectangle.
+///
+/// Each edge is a fraction of the way across the other rectangle, so `left: 0.25` and
+/// `right: 0.75` cover the middle half horizontally. Fractions outside of `0.0..=1.0` reach
+/// outside of the other rectangle.
+///
+/// Used by [`WindowTable::insert_relative`] and [`WindowTable::set_relative`].
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct RelativeRect {
+    /// How far across the parent the left edge is.
+    pub left: f64,
+
+    /// How far down the parent the top edge is.
+    pub top: f64,
+
+    /// How far across the parent the right edge is.
+    pub right: f64,
+
+    /// How far down the parent the bottom edge is.
+    pub bottom: f64,
+}
+
+impl RelativeRect {
+    /// Creates a new relative rectangle from its edges.
+    pub fn new(left: f64, top: f64, right: f64, bottom: f64) -> Self {
+        Self {
+            left,
+            top,
+            right,
+            bottom,
+        }
+    }
+
+    /// Compute the rectangle that these fractions make of another rectangle.
+    pub fn resolve<T: Coordinate>(&self, parent: Rectangle<T>) -> Rectangle<T> {
+        let lerp = |low: T, high: T, fraction: f64| {
+            let low = low.to_f64();
+            T::from_f64(low + (high.to_f64() - low) * fraction)
+        };
+
+        Rectangle::new(
+            lerp(parent.left, parent.right, self.left),
+            lerp(parent.top, parent.bottom, self.top),
+            lerp(parent.left, parent.right, self.right),
+            lerp(parent.top, parent.bottom, self.bottom),
+        )
+    }
+}
+
+/// Constraints on the size of a window.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub struct SizeHints<T = i32> {
+    /// The minimum width and height.
+    pub min_size: Option<Size<T>>,
+
+    /// The maximum width and height.
+    pub max_size: Option<Size<T>>,
+
+    /// The minimum aspect ratio, as a width to height ratio.
+    pub min_aspect: Option<(T, T)>,
+
+    /// The maximum aspect ratio, as a width to height ratio.
+    pub max_aspect: Option<(T, T)>,
+}
+
+impl<T: Coordinate> SizeHints<T> {
+    /// Constrain a size to these hints.
+    ///
+    /// Aspect ratios are enforced by shrinking one of the dimensions, but never below one unit,
+    /// after which the minimum size takes priority.
+    pub fn constrain(&self, size: Size<T>) -> Size<T> {
+        let Size {
+            mut width,
+            mut height,
+        } = size;
+        let one = T::from_i32(1);
+
+        if let Some(max) = self.max_size {
+            width = coord::min(width, max.width);
+            height = coord::min(height, max.height);
+        }
+
+        if let Some(min) = self.min_size {
+            width = coord::max(width, min.width);
+            height = coord::max(height, min.height);
+        }
+
+        // Compare ratios by cross-multiplying in the wider type, to avoid rounding.
+        if let Some((x, y)) = self.min_aspect {
+            if x > T::ZERO && y > T::ZERO && width.widen() * y.widen() < x.widen() * height.widen()
+            {
+                height = coord::max(
+                    T::narrow(width.widen() * y.widen() / x.widen()),
+                    coord::min(height, one),
+                );
+            }
+        }
+
+        if let Some((x, y)) = self.max_aspect {
+            if x > T::ZERO && y > T::ZERO && width.widen() * y.widen() > x.widen() * height.widen()
+            {
+                width = coord::max(
+                    T::narrow(height.widen() * x.widen() / y.widen()),
+                    coord::min(width, one),
+                );
+            }
+        }
+
+        if let Some(min) = self.min_size {
+            width = coord::max(width, min.width);
+            height = coord::max(height, min.height);
+        }
+
+        Size::new(width, height)
+    }
+}
+
+/// Which child [`WindowTable::circulate`] restacks.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum CirculateDirection {
+    /// Raise the lowest child that is overlapped by a sibling.
+    RaiseLowest,
+
+    /// Lower the highest child that overlaps a sibling.
+    LowerHighest,
+}
+
+/// The order to traverse a tree of windows in.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Order {
+    /// Visit each window's descendants before its next sibling.
+    DepthFirst,
+
+    /// Visit every window at one depth before the windows at the next depth.
+    BreadthFirst,
+}
+
+/// An iterator over a window and its descendants.
+///
+/// Returned by [`WindowTable::traverse`]. Each window comes before its descendants, and siblings
+/// are visited from the bottom of the stack up.
+#[derive(Debug, Clone)]
+pub struct Traverse<'a, T: Coordinate = i32, const N: usize = 3, const R: usize = 4> {
+    /// The table being traversed.
+    table: &'a WindowTable<T, N, R>,
+
+    /// The order to traverse in.
+    order: Order,
+
+    /// The windows that have yet to be visited.
+    ///
+    /// This is used as a stack for depth-first traversal and a queue for breadth-first traversal.
+    pending: VecDeque<WindowKey>,
+}
+
+impl<T: Coordinate, const N: usize, const R: usize> Iterator for Traverse<'_, T, N, R> {
+    type Item = WindowKey;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let key = self.pending.pop_front()?;
+
+        match self.order {
+            Order::DepthFirst => {
+                let count = self.pending.len();
+                self.pending.extend(self.table.children(key));
+                self.pending.rotate_left(count);
+            }
+            Order::BreadthFirst => self.pending.extend(self.table.children(key)),
+        }
+
+        Some(key)
+    }
+}
+
+impl<T: Coordinate, const N: usize, const R: usize> core::iter::FusedIterator
+    for Traverse<'_, T, N, R>
+{
+}
+
+/// Error with changing the rectangle of a window.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ConfigureError {
+    /// The new rectangle falls outside of the bounds of the root window.
+    OutsideRoot,
+
+    /// The rectangle of the root window cannot be changed this way.
+    ///
+    /// Use [`WindowTable::resize_root`] instead.
+    RootWindow,
+
+    /// The new rectangle has no area.
+    EmptyRectangle,
+
+    /// No configure request with this serial is pending for the window.
+    ///
+    /// See [`WindowTable::apply_configure`].
+    UnknownRequest,
+}
+
+/// Error with inserting a window.
+#[derive(Debug)]
+pub enum InsertError {
+    /// This window falls outside of the bounds of the root window.
+    OutsideRoot,
+
+    /// The rectangle of this window has no area.
+    EmptyRectangle,
+
+    /// Windows cannot be inserted below the root window.
+    BelowRoot,
+
+    /// This window partly overlaps another window, which the [`OverlapPolicy`] forbids.
+    Overlaps,
+}
+
+/// A rectangle.
+///
+/// The coordinates are `i32` by default, but may be any [`Coordinate`] type.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Rectangle<T = i32> {
+    /// The left coordinate.
+    pub left: T,
+
+    /// The top coordinate.
+    pub top: T,
+
+    /// The right coordinate.
+    pub right: T,
+
+    /// The bottom coordinate.
+    pub bottom: T,
+}
+
+impl<T: Coordinate> Rectangle<T> {
+    /// Creates a new rectangle.
+    pub fn new(left: T, top: T, right: T, bottom: T) -> Self {
+        Self {
+            left,
+            top,
+            right,
+            bottom,
+        }
+    }
+
+    /// Creates a new rectangle from its top-left corner and its size.
+    ///
+    /// The right and bottom edges saturate at the bounds of the coordinate type.
+    pub fn from_origin_size(origin: Point<T>, size: Size<T>) -> Self {
+        Self {
+            left: origin.x,
+            top: origin.y,
+            right: origin.x.saturating_add(size.width),
+            bottom: origin.y.saturating_add(size.height),
+        }
+    }
+
+    /// Returns the top-left corner of the rectangle.
+    pub fn origin(&self) -> Point<T> {
+        Point::new(self.left, self.top)
+    }
+
+    /// Returns the size of the rectangle.
+    pub fn size(&self) -> Size<T> {
+        Size::new(self.saturating_width(), self.saturating_height())
+    }
+
+    /// Returns the width of the rectangle.
+    ///
+    /// The width saturates if it doesn't fit in the coordinate type.
+    pub fn width(&self) -> T {
+        self.saturating_width()
+    }
+
+    /// Returns the height of the rectangle.
+    ///
+    /// The height saturates if it doesn't fit in the coordinate type.
+    pub fn height(&self) -> T {
+        self.saturating_height()
+    }
+
+    /// Returns the width of the rectangle, or `None` if it doesn't fit in the coordinate type.
+    pub fn checked_width(&self) -> Option<T> {
+        coord::checked_distance(self.left, self.right)
+    }
+
+    /// Returns the height of the rectangle, or `None` if it doesn't fit in the coordinate type.
+    pub fn checked_height(&self) -> Option<T> {
+        coord::checked_distance(self.top, self.bottom)
+    }
+
+    /// Returns the width of the rectangle, saturating at the bounds of the coordinate type.
+    pub fn saturating_width(&self) -> T {
+        coord::distance(self.left, self.right)
+    }
+
+    /// Returns the height of the rectangle, saturating at the bounds of the coordinate type.
+    pub fn saturating_height(&self) -> T {
+        coord::distance(self.top, self.bottom)
+    }
+
+    /// Returns the area of the rectangle, or `None` if it doesn't fit in the coordinate type.
+    pub fn checked_area(&self) -> Option<T> {
+        self.checked_width()?.checked_mul(self.checked_height()?)
+    }
+
+    /// Returns the area of the rectangle, saturating at the bounds of the coordinate type.
+    pub fn saturating_area(&self) -> T {
+        self.saturating_width()
+            .saturating_mul(self.saturating_height())
+    }
+
+    /// Returns the area of the rectangle in the [wider type](Coordinate::Wide).
+    ///
+    /// The width and height saturate at the bounds of the coordinate type, but their product
+    /// always fits in the wider type. This is useful for large rectangles, such as those on a
+    /// huge canvas with `i64` coordinates.
+    pub fn wide_area(&self) -> T::Wide {
+        self.saturating_width().widen() * self.saturating_height().widen()
+    }
+
+    /// Tell if the rectangle contains no points.
+    ///
+    /// This is the case if it has no area, or if its right or bottom edge comes before its left
+    /// or top edge.
+    pub fn is_empty(&self) -> bool {
+        // Written this way so that NaN coordinates are empty, too.
+        !(self.left < self.right && self.top < self.bottom)
+    }
+
+    /// Returns this rectangle with its edges swapped, if needed, so that the left edge comes
+    /// before the right edge and the top edge comes before the bottom edge.
+    pub fn normalize(self) -> Self {
+        Self {
+            left: coord::min(self.left, self.right),
+            top: coord::min(self.top, self.bottom),
+            right: coord::max(self.left, self.right),
+            bottom: coord::max(self.top, self.bottom),
+        }
+    }
+
+    /// Move this rectangle by the given offset.
+    ///
+    /// The offset is clamped so the rectangle stays within the bounds of the coordinate type
+    /// without changing its size.
+    pub fn translate(self, dx: T, dy: T) -> Self {
+        let dx = coord::clamp_offset(self.left, self.right, dx);
+        let dy = coord::clamp_offset(self.top, self.bottom, dy);
+
+        Self {
+            left: self.left + dx,
+            top: self.top + dy,
+            right: self.right + dx,
+            bottom: self.bottom + dy,
+        }
+    }
+
+    /// Grow this rectangle by `dx` on the left and right and `dy` on the top and bottom.
+    ///
+    /// Negative amounts shrink the rectangle. A rectangle shrunk past its size collapses to its
+    /// center.
+    pub fn inflate(self, dx: T, dy: T) -> Self {
+        let mut rect = Self {
+            left: self.left.saturating_sub(dx),
+            top: self.top.saturating_sub(dy),
+            right: self.right.saturating_add(dx),
+            bottom: self.bottom.saturating_add(dy),
+        };
+
+        if rect.left > rect.right {
+            rect.left = coord::midpoint(self.left, self.right);
+            rect.right = rect.left;
+        }
+
+        if rect.top > rect.bottom {
+            rect.top = coord::midpoint(self.top, self.bottom);
+            rect.bottom = rect.top;
+        }
+
+        rect
+    }
+
+    /// Tell if this rectangle contains a point.
+    ///
+    /// The left and top edges are inside the rectangle, while the right and bottom edges are not.
+    pub fn contains(&self, point: impl Into<Point<T>>) -> bool {
+        let Point { x, y } = point.into();
+        x >= self.left && x < self.right && y >= self.top && y < self.bottom
+    }
+
+    /// Tell if this rectangle entirely contains another rectangle.
+    pub fn contains_rect(&self, other: Self) -> bool {
+        other.left >= self.left
+            && other.right <= self.right
+            && other.top >= self.top
+            && other.bottom <= self.bottom
+    }
+
+    /// Tell if two rectangles intersect.
+    ///
+    /// Rectangles that only share an edge do not intersect.
+    pub fn intersects(&self, other: Self) -> bool {
+        self.left < other.right
+            && self.right > other.left
+            && self.top < other.bottom
+            && self.bottom > other.top
+    }
+
+    /// Returns the overlap between two rectangles, or `None` if they don't intersect.
+    pub fn intersection(&self, other: Self) -> Option<Self> {
+        if !self.intersects(other) {
+            return None;
+        }
+
+        Some(Self {
+            left: coord::max(self.left, other.left),
+            top: coord::max(self.top, other.top),
+            right: coord::min(self.right, other.right),
+            bottom: coord::min(self.bottom, other.bottom),
+        })
+    }
+
+    /// Returns the smallest rectangle containing both rectangles.
+    pub fn union(&self, other: Self) -> Self {
+        Self {
+            left: coord::min(self.left, other.left),
+            top: coord::min(self.top, other.top),
+            right: coord::max(self.right, other.right),
+            bottom: coord::max(self.bottom, other.bottom),
+        }
+    }
+
+    /// Split this rectangle against another.
     ///
     /// Returns the intersection and the remainder of the `Self` rectangle.
-    fn intersection(mut self, other: Self) -> Option<(Self, ArrayVec<[Self; 4]>)> {
+    fn split(mut self, other: Self) -> Option<(Self, ArrayVec<[Self; 4]>)> {
         // See if the rectangles intersect.
-        if self.intersects(other) {
+        if !self.intersects(other) {
             return None;
         }
 
-        let mut remainder = ArrayVec::new();
+        let mut remainder = ArrayVec::new();
+
+        // See if the bottom edge of this rectangle intersects the top edge of the other.
+        if self.bottom > other.top {
+            let new_top = coord::max(self.top, other.top);
+
+            // Push the remainder of the top edge if there is any.
+            if new_top != self.top {
+                remainder.push(Rectangle {
+                    bottom: new_top,
+                    ..self
+                });
+            }
+
+            self.top = new_top;
+        }
+
+        // See if the top edge of this rectangle intersects the bottom edge of the other.
+        if self.top < other.bottom {
+            let new_bottom = coord::min(self.bottom, other.bottom);
+
+            // Push the remainder of the bottom edge if there is any.
+            if new_bottom != self.bottom {
+                remainder.push(Rectangle {
+                    top: new_bottom,
+                    ..self
+                });
+            }
+
+            self.bottom = new_bottom;
+        }
+
+        // See if the left edge of this rectangle intersects the right edge of the other.
+        if self.left < other.right {
+            let new_right = coord::min(self.right, other.right);
+
+            // Push the remainder of the right edge if there is any.
+            if new_right != self.right {
+                remainder.push(Rectangle {
+                    left: new_right,
+                    ..self
+                });
+            }
+
+            self.right = new_right;
+        }
+
+        // See if the right edge of this rectangle intersects the left edge of the other.
+        if self.right > other.left {
+            let new_left = coord::max(self.left, other.left);
+
+            // Push the remainder of the left edge if there is any.
+            if new_left != self.left {
+                remainder.push(Rectangle {
+                    right: new_left,
+                    ..self
+                });
+            }
+
+            self.left = new_left;
+        }
+
+        Some((self, remainder))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Anchors, CirculateDirection, ConfigureError, Gravity, InsertError, Layer, Order,
+        OverlapPolicy, Point, QueryScratch, Rectangle, Region, RelativeRect, Size, SizeHints, Span,
+        Transform, WindowKey, WindowTable,
+    };
+
+    #[test]
+    fn no_intersect() {
+        let a = Rectangle::new(0, 0, 10, 10);
+        let b = Rectangle::new(15, 15, 25, 25);
+
+        assert!(a.split(b).is_none());
+    }
+
+    #[test]
+    fn intersect_inside() {
+        let a = Rectangle::new(5, 5, 10, 10);
+        let b = Rectangle::new(0, 0, 15, 15);
+
+        let (intersection, remainder) = a.split(b).unwrap();
+        assert_eq!(intersection, a);
+        assert!(remainder.is_empty());
+    }
+
+    #[test]
+    fn intersect_corner() {
+        let a = Rectangle::new(0, 0, 10, 10);
+        let b = Rectangle::new(5, 5, 15, 15);
+
+        let (intersection, remainder) = a.split(b).unwrap();
+
+        assert_eq!(intersection, Rectangle::new(5, 5, 10, 10));
+        assert_eq!(remainder.len(), 2);
+    }
+
+    #[test]
+    fn retain() {
+        let mut window_table = WindowTable::new();
+
+        let root = window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let a = window_table.insert(Rectangle::new(0, 0, 50, 50)).unwrap();
+        let b = window_table.insert(Rectangle::new(10, 10, 20, 20)).unwrap();
+        let c = window_table.insert(Rectangle::new(60, 60, 70, 70)).unwrap();
+        let d = window_table.insert(Rectangle::new(80, 80, 90, 90)).unwrap();
+        window_table.take_damage();
+        window_table.take_exposures();
+
+        let exposed = window_table.retain(|key, _| key != a && key != c);
+        assert_eq!(exposed.area(), 2600);
+        assert_eq!(window_table.len(), 3);
+        assert_eq!(window_table.parent(b), Some(root));
+        assert_eq!(window_table.parent(d), Some(root));
+        assert_eq!(window_table.take_exposures().len(), 1);
+
+        window_table.retain(|_, rect| rect.width() < 100);
+        assert!(window_table.is_empty());
+    }
+
+    #[test]
+    fn size_queries() {
+        let mut window_table = WindowTable::new();
+        assert!(window_table.is_empty());
+        assert_eq!(window_table.tree_depth(), 0);
+
+        let root = window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let a = window_table.insert(Rectangle::new(10, 10, 50, 50)).unwrap();
+        let b = window_table.insert(Rectangle::new(20, 20, 30, 30)).unwrap();
+        let c = window_table.insert(Rectangle::new(60, 60, 70, 70)).unwrap();
+
+        assert_eq!(window_table.len(), 4);
+        assert!(window_table.contains_key(b));
+        assert_eq!(window_table.depth(root), Some(0));
+        assert_eq!(window_table.depth(b), Some(2));
+        assert_eq!(window_table.depth(c), Some(1));
+        assert_eq!(window_table.tree_depth(), 3);
+
+        window_table.remove(a);
+        assert!(!window_table.contains_key(a));
+        assert_eq!(window_table.depth(a), None);
+        assert_eq!(window_table.tree_depth(), 2);
+    }
+
+    #[test]
+    fn empty_rectangles() {
+        let inverted = Rectangle::new(10, 10, 0, 0);
+        assert!(inverted.is_empty());
+        assert_eq!(inverted.normalize(), Rectangle::new(0, 0, 10, 10));
+        assert!(Rectangle::new(0, 0, 0, 10).is_empty());
+        assert!(Rectangle::new(0.0, 0.0, f64::NAN, 1.0).is_empty());
+
+        let mut window_table = WindowTable::new();
+        assert!(matches!(
+            window_table.insert(Rectangle::new(0, 0, 100, 0)),
+            Err(InsertError::EmptyRectangle)
+        ));
+
+        window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let a = window_table.insert(inverted).unwrap();
+        assert_eq!(window_table.rect(a), Rectangle::new(0, 0, 10, 10));
+        assert_eq!(
+            window_table.resize_window(a, Rectangle::new(5, 5, 5, 20)),
+            Err(ConfigureError::EmptyRectangle)
+        );
+        assert_eq!(
+            window_table.resize_window(a, Rectangle::new(20, 20, 10, 10)),
+            Ok(Rectangle::new(10, 10, 20, 20))
+        );
+    }
+
+    #[test]
+    fn rectangle_api() {
+        let a = Rectangle::new(0, 0, 10, 10);
+        let b = Rectangle::new(5, 5, 15, 15);
+
+        assert!(a.contains((0, 0)));
+        assert!(!a.contains((10, 5)));
+        assert!(a.contains_rect(Rectangle::new(2, 2, 8, 8)));
+        assert!(!a.contains_rect(b));
+        assert!(!a.intersects(Rectangle::new(10, 0, 20, 10)));
+        assert_eq!(a.intersection(b), Some(Rectangle::new(5, 5, 10, 10)));
+        assert_eq!(a.intersection(Rectangle::new(20, 20, 30, 30)), None);
+        assert_eq!(a.union(b), Rectangle::new(0, 0, 15, 15));
+        assert_eq!(a.translate(5, -5), Rectangle::new(5, -5, 15, 5));
+        assert_eq!(a.inflate(2, 1), Rectangle::new(-2, -1, 12, 11));
+        assert_eq!(a.inflate(-8, -2), Rectangle::new(5, 2, 5, 8));
+    }
+
+    #[test]
+    fn overflow() {
+        let full = Rectangle::new(i32::MIN, i32::MIN, i32::MAX, i32::MAX);
+        assert_eq!(full.checked_width(), None);
+        assert_eq!(full.saturating_width(), i32::MAX);
+        assert_eq!(full.checked_area(), None);
+        assert_eq!(full.saturating_area(), i32::MAX);
+        assert_eq!(full.wide_area(), i64::from(i32::MAX) * i64::from(i32::MAX));
+
+        let canvas = Rectangle::new(-1i64 << 40, 0, 1 << 40, 1 << 40);
+        assert_eq!(canvas.checked_area(), None);
+        assert_eq!(canvas.wide_area(), 1 << 81);
+
+        let corner = Rectangle::new(i32::MAX - 10, i32::MAX - 10, i32::MAX, i32::MAX);
+        assert_eq!(corner.checked_area(), Some(100));
+        assert!(corner.split(full).is_some());
+
+        let mut window_table = WindowTable::new();
+        let root = window_table.insert(full).unwrap();
+        let a = window_table.insert(corner).unwrap();
+        let b = window_table
+            .insert(Rectangle::new(
+                i32::MAX - 5,
+                i32::MAX - 5,
+                i32::MAX,
+                i32::MAX,
+            ))
+            .unwrap();
+        assert_eq!(window_table.parent(a), Some(root));
+        assert_eq!(window_table.parent(b), Some(a));
+        assert_eq!(
+            window_table.window_at((i32::MAX - 1, i32::MAX - 1)),
+            Some(b)
+        );
+
+        // Windows dragged past the edge of the coordinate space stop there and keep their size.
+        window_table.set_gravity(b, Gravity::Center);
+        let rect = window_table
+            .move_window(a, (i32::MAX - 5, i32::MAX - 5))
+            .unwrap();
+        assert_eq!(
+            rect,
+            Rectangle::new(i32::MAX - 5, i32::MAX - 5, i32::MAX, i32::MAX)
+        );
+        assert_eq!(
+            window_table.rect(b),
+            Rectangle::new(i32::MAX - 5, i32::MAX - 5, i32::MAX, i32::MAX)
+        );
+        assert_eq!(
+            Rectangle::new(i32::MIN, 0, i32::MIN + 5, 5).translate(-10, 0),
+            Rectangle::new(i32::MIN, 0, i32::MIN + 5, 5)
+        );
+        assert_eq!(window_table.take_damage().area(), i32::MAX);
+    }
+
+    #[test]
+    fn insert() {
+        let mut window_table = WindowTable::new();
+
+        // Inserting a single rectangle should install it as the root.
+        let rect1 = window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        assert_eq!(Some(rect1), window_table.root);
+
+        {
+            let slot = &window_table.windows[rect1.0];
+            assert!(slot.children.is_empty());
+            assert!(slot.parents.is_empty());
+        }
+    }
+
+    #[test]
+    fn insert_outside_root() {
+        let mut window_table = WindowTable::new();
+
+        let root = window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        assert!(matches!(
+            window_table.insert(Rectangle::new(100, 0, 150, 50)),
+            Err(InsertError::OutsideRoot)
+        ));
+        assert_eq!(window_table.iter().len(), 1);
+
+        // Windows only need to overlap the root, not fit inside of it.
+        let a = window_table
+            .insert(Rectangle::new(90, 90, 150, 150))
+            .unwrap();
+        assert_eq!(&window_table.windows[a.0].parents[..], &[root]);
+
+        // Windows covered by another window are not linked to the root as well.
+        let b = window_table.insert(Rectangle::new(95, 95, 99, 99)).unwrap();
+        assert_eq!(&window_table.windows[b.0].parents[..], &[a]);
+    }
+
+    #[test]
+    fn insert_nested() {
+        let mut window_table = WindowTable::new();
+
+        let root = window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let panel = window_table.insert(Rectangle::new(10, 10, 60, 60)).unwrap();
+        let button = window_table.insert(Rectangle::new(20, 20, 30, 30)).unwrap();
+
+        assert_eq!(&window_table.windows[panel.0].parents[..], &[root]);
+        assert_eq!(&window_table.windows[button.0].parents[..], &[panel]);
+        assert_eq!(&window_table.windows[root.0].children[..], &[panel]);
+    }
+
+    #[test]
+    fn remove_relinks() {
+        let mut window_table = WindowTable::new();
+
+        let root = window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let panel = window_table.insert(Rectangle::new(10, 10, 60, 60)).unwrap();
+        let button = window_table.insert(Rectangle::new(20, 20, 30, 30)).unwrap();
+
+        assert_eq!(
+            window_table.remove(panel),
+            Some(Rectangle::new(10, 10, 60, 60))
+        );
+        assert_eq!(window_table.remove(panel), None);
+        assert_eq!(window_table.parent(button), Some(root));
+        assert_eq!(window_table.window_at((25, 25)), Some(button));
+
+        window_table.remove(root);
+        assert_eq!(window_table.iter().len(), 0);
+        assert_eq!(window_table.root(), None);
+    }
+
+    #[test]
+    fn ancestors() {
+        let mut window_table = WindowTable::new();
+
+        let root = window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let panel = window_table.insert(Rectangle::new(10, 10, 60, 60)).unwrap();
+        let button = window_table.insert(Rectangle::new(20, 20, 30, 30)).unwrap();
+
+        let ancestors = window_table
+            .ancestors(button)
+            .collect::<alloc::vec::Vec<_>>();
+        assert_eq!(ancestors, [button, panel, root]);
+    }
+
+    #[test]
+    fn restack() {
+        let mut window_table = WindowTable::new();
+
+        let root = window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let a = window_table.insert(Rectangle::new(0, 0, 50, 50)).unwrap();
+        let button = window_table.insert(Rectangle::new(10, 10, 20, 20)).unwrap();
+        let b = window_table.insert(Rectangle::new(30, 30, 80, 80)).unwrap();
+
+        assert_eq!(window_table.window_at((40, 40)), Some(b));
+
+        window_table.raise(a);
+        assert_eq!(window_table.window_at((40, 40)), Some(a));
+        assert_eq!(window_table.window_at((15, 15)), Some(button));
+
+        window_table.lower(a);
+        assert_eq!(window_table.window_at((40, 40)), Some(b));
+        assert_eq!(window_table.parent(a), Some(root));
+    }
+
+    #[test]
+    fn move_window() {
+        let mut window_table = WindowTable::new();
+
+        let root = window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let a = window_table.insert(Rectangle::new(0, 0, 50, 50)).unwrap();
+        let b = window_table.insert(Rectangle::new(60, 60, 70, 70)).unwrap();
+
+        assert_eq!(window_table.parent(b), Some(root));
+        assert_eq!(
+            window_table.move_window(a, (40, 40)),
+            Ok(Rectangle::new(40, 40, 90, 90))
+        );
+
+        // The window is still stacked below `b`.
+        assert_eq!(window_table.parent(b), Some(a));
+        assert_eq!(
+            window_table.move_window(a, (200, 200)),
+            Err(ConfigureError::OutsideRoot)
+        );
+    }
+
+    #[test]
+    fn take_damage() {
+        let mut window_table = WindowTable::new();
+
+        window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let a = window_table.insert(Rectangle::new(10, 10, 20, 20)).unwrap();
+        assert_eq!(window_table.take_damage().area(), 10000);
+        assert!(window_table.take_damage().is_empty());
+
+        window_table.move_window(a, (30, 10)).unwrap();
+        window_table.unmap(a);
+        assert_eq!(window_table.take_damage().area(), 200);
+
+        // Changes to unmapped windows don't damage anything.
+        window_table.move_window(a, (50, 10)).unwrap();
+        assert!(window_table.take_damage().is_empty());
+
+        window_table.remove(a);
+        assert!(window_table.take_damage().is_empty());
+    }
+
+    #[test]
+    fn visible_region() {
+        let mut window_table = WindowTable::new();
+
+        let root = window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let a = window_table.insert(Rectangle::new(0, 0, 50, 50)).unwrap();
+        window_table.insert(Rectangle::new(25, 25, 75, 75)).unwrap();
+
+        assert_eq!(window_table.visible_region(a).area(), 1875);
+        assert_eq!(
+            window_table.visible_region(root).area(),
+            10000 - 1875 - 2500
+        );
+    }
+
+    #[test]
+    fn uncovered_region() {
+        let mut window_table = WindowTable::new();
+        assert!(window_table.uncovered_region().is_empty());
+
+        window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let a = window_table.insert(Rectangle::new(0, 0, 50, 50)).unwrap();
+        let b = window_table.insert(Rectangle::new(25, 25, 75, 75)).unwrap();
+        let c = window_table.insert(Rectangle::new(80, 80, 90, 90)).unwrap();
+        window_table.set_opacity(a, 128);
+        window_table.unmap(c);
+
+        assert_eq!(window_table.uncovered_region().area(), 10000 - 2500 - 1875);
+
+        window_table
+            .resize_window(b, Rectangle::new(0, 0, 100, 100))
+            .unwrap();
+        assert!(window_table.uncovered_region().is_empty());
+    }
+
+    #[test]
+    fn exposures() {
+        let mut window_table = WindowTable::new();
+
+        let root = window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let a = window_table.insert(Rectangle::new(10, 10, 50, 50)).unwrap();
+        let b = window_table.insert(Rectangle::new(30, 30, 60, 60)).unwrap();
+        window_table.take_exposures();
+
+        window_table.remove(b);
+        let exposures = window_table.take_exposures();
+        assert_eq!(exposures.len(), 2);
+
+        // The exposed part of `a` is relative to its origin.
+        let (_, exposed) = exposures.iter().find(|(key, _)| *key == a).unwrap();
+        assert_eq!(exposed.bounds(), Some(Rectangle::new(20, 20, 40, 40)));
+
+        let (_, exposed) = exposures.iter().find(|(key, _)| *key == root).unwrap();
+        assert_eq!(exposed.area(), 900 - 400);
+
+        // Moving a window doesn't expose parts of it that were already visible.
+        window_table.move_window(a, (0, 0)).unwrap();
+        let exposures = window_table.take_exposures();
+        assert!(exposures.iter().all(|(key, _)| *key == root));
+    }
+
+    #[test]
+    fn paint_order() {
+        let mut window_table = WindowTable::new();
+
+        let root = window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let a = window_table.insert(Rectangle::new(0, 0, 50, 50)).unwrap();
+        let b = window_table.insert(Rectangle::new(60, 60, 90, 90)).unwrap();
+        let c = window_table.insert(Rectangle::new(10, 10, 20, 20)).unwrap();
+        let d = window_table.insert(Rectangle::new(40, 40, 70, 70)).unwrap();
+        window_table.unmap(b);
+
+        let order = window_table
+            .paint_order()
+            .map(|(key, _)| key)
+            .collect::<alloc::vec::Vec<_>>();
+        assert_eq!(order, [root, a, c, d]);
+    }
+
+    #[test]
+    fn traverse() {
+        let mut window_table = WindowTable::new();
+
+        let root = window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let a = window_table.insert(Rectangle::new(0, 0, 50, 50)).unwrap();
+        let b = window_table.insert(Rectangle::new(60, 60, 90, 90)).unwrap();
+        let c = window_table.insert(Rectangle::new(10, 10, 20, 20)).unwrap();
+        let d = window_table.insert(Rectangle::new(65, 65, 70, 70)).unwrap();
+
+        assert!(window_table.children(root).eq([a, b]));
+        assert!(window_table
+            .traverse(root, Order::DepthFirst)
+            .eq([root, a, c, b, d]));
+        assert!(window_table
+            .traverse(root, Order::BreadthFirst)
+            .eq([root, a, b, c, d]));
+        assert!(window_table.traverse(b, Order::DepthFirst).eq([b, d]));
+    }
+
+    #[test]
+    fn border_width() {
+        let mut window_table = WindowTable::new();
+
+        window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let a = window_table.insert(Rectangle::new(10, 10, 50, 50)).unwrap();
+        window_table.take_damage();
+        window_table.set_border_width(a, 5);
+
+        // The border grows the window around its content.
+        assert_eq!(window_table.rect(a), Rectangle::new(5, 5, 55, 55));
+        assert_eq!(window_table.inner_rect(a), Rectangle::new(10, 10, 50, 50));
+        assert_eq!(window_table.window_at((7, 7)), Some(a));
+        let damage = window_table.take_damage();
+        assert_eq!(damage.bounds(), Some(Rectangle::new(5, 5, 55, 55)));
+        assert_eq!(damage.area(), 2500);
+
+        window_table.set_border_width(a, 30);
+        assert_eq!(window_table.rect(a), Rectangle::new(-20, -20, 80, 80));
+        assert_eq!(window_table.inner_rect(a), Rectangle::new(10, 10, 50, 50));
+        window_table.set_border_width(a, 2);
+        assert_eq!(window_table.rect(a), Rectangle::new(8, 8, 52, 52));
+        assert_eq!(window_table.validate(), Ok(()));
+
+        // Resizing the window must leave room for its content.
+        assert_eq!(
+            window_table.resize_window(a, Rectangle::new(8, 8, 12, 20)),
+            Err(ConfigureError::EmptyRectangle)
+        );
+        assert_eq!(
+            window_table.resize_window(a, Rectangle::new(8, 8, 13, 20)),
+            Ok(Rectangle::new(8, 8, 13, 20))
+        );
+        assert_eq!(window_table.inner_rect(a), Rectangle::new(10, 10, 11, 18));
+
+        // The root window has no border.
+        let root = window_table.root().unwrap();
+        window_table.set_border_width(root, 5);
+        assert_eq!(window_table.border_width(root), 0);
+        assert_eq!(window_table.rect(root), Rectangle::new(0, 0, 100, 100));
+    }
+
+    #[test]
+    fn gravity() {
+        let mut window_table = WindowTable::new();
+
+        window_table.insert(Rectangle::new(0, 0, 200, 200)).unwrap();
+        let panel = window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let top_left = window_table.insert(Rectangle::new(10, 10, 20, 20)).unwrap();
+        let bottom_right = window_table.insert(Rectangle::new(80, 80, 90, 90)).unwrap();
+        let fixed = window_table.insert(Rectangle::new(40, 40, 50, 50)).unwrap();
+        let hidden = window_table.insert(Rectangle::new(60, 10, 70, 20)).unwrap();
+        let nested = window_table.insert(Rectangle::new(82, 82, 88, 88)).unwrap();
+
+        window_table.set_gravity(bottom_right, Gravity::SouthEast);
+        window_table.set_gravity(fixed, Gravity::Static);
+        window_table.set_gravity(hidden, Gravity::Unmap);
+
+        window_table
+            .resize_window(panel, Rectangle::new(10, 10, 160, 160))
+            .unwrap();
+
+        assert_eq!(window_table.rect(top_left), Rectangle::new(20, 20, 30, 30));
+        assert_eq!(
+            window_table.rect(bottom_right),
+            Rectangle::new(140, 140, 150, 150)
+        );
+        assert_eq!(
+            window_table.rect(nested),
+            Rectangle::new(142, 142, 148, 148)
+        );
+        assert_eq!(window_table.rect(fixed), Rectangle::new(40, 40, 50, 50));
+        assert!(!window_table.is_mapped(hidden));
+    }
+
+    #[test]
+    fn size_hints() {
+        let mut window_table = WindowTable::new();
+
+        window_table.insert(Rectangle::new(0, 0, 200, 200)).unwrap();
+        let a = window_table.insert(Rectangle::new(10, 10, 50, 50)).unwrap();
+        window_table.set_size_hints(
+            a,
+            SizeHints {
+                min_size: Some(Size::new(20, 20)),
+                max_size: Some(Size::new(100, 100)),
+                max_aspect: Some((2, 1)),
+                ..SizeHints::default()
+            },
+        );
+
+        assert_eq!(
+            window_table.resize_window(a, Rectangle::new(10, 10, 15, 15)),
+            Ok(Rectangle::new(10, 10, 30, 30))
+        );
+        assert_eq!(
+            window_table.resize_window(a, Rectangle::new(10, 10, 160, 40)),
+            Ok(Rectangle::new(10, 10, 70, 40))
+        );
+        assert_eq!(window_table.rect(a), Rectangle::new(10, 10, 70, 40));
+    }
+
+    #[test]
+    fn size_hints_never_empty() {
+        let mut window_table = WindowTable::new();
+
+        window_table.insert(Rectangle::new(0, 0, 200, 200)).unwrap();
+        let a = window_table.insert(Rectangle::new(10, 10, 20, 20)).unwrap();
+        window_table.set_size_hints(
+            a,
+            SizeHints {
+                min_aspect: Some((1000, 1)),
+                ..SizeHints::default()
+            },
+        );
+
+        // The aspect ratio would round the height down to zero.
+        assert_eq!(
+            window_table.resize_window(a, Rectangle::new(10, 10, 20, 20)),
+            Ok(Rectangle::new(10, 10, 20, 11))
+        );
+        assert_eq!(window_table.validate(), Ok(()));
+
+        window_table.set_size_hints(
+            a,
+            SizeHints {
+                max_size: Some(Size::new(0, 10)),
+                ..SizeHints::default()
+            },
+        );
+        assert_eq!(
+            window_table.resize_window(a, Rectangle::new(10, 10, 20, 20)),
+            Err(ConfigureError::EmptyRectangle)
+        );
+        assert_eq!(window_table.rect(a), Rectangle::new(10, 10, 20, 11));
+        assert_eq!(window_table.validate(), Ok(()));
+    }
+
+    #[test]
+    fn save_under() {
+        let mut window_table = WindowTable::new();
+
+        let root = window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let menu = window_table.insert(Rectangle::new(10, 10, 30, 30)).unwrap();
+        window_table.set_save_under(menu, true);
+        window_table.take_exposures();
+
+        window_table.unmap(menu);
+        assert!(window_table.take_exposures().is_empty());
 
-        // See if the bottom edge of this rectangle intersects the top edge of the other.
-        if self.bottom > other.top {
-            let new_top = cmp::max(self.top, other.top);
+        let save_unders = window_table.take_save_unders();
+        assert_eq!(save_unders.len(), 1);
+        assert_eq!(save_unders[0].0, menu);
+        assert_eq!(
+            save_unders[0].1.bounds(),
+            Some(Rectangle::new(10, 10, 30, 30))
+        );
 
-            // Push the remainder of the top edge if there is any.
-            if new_top != self.top {
-                remainder.push(Rectangle {
-                    bottom: new_top,
-                    ..self
-                });
-            }
+        // Windows without save-under are exposed as usual.
+        window_table.set_save_under(menu, false);
+        window_table.map(menu);
+        window_table.unmap(menu);
+        assert!(window_table
+            .take_exposures()
+            .iter()
+            .any(|(key, _)| *key == root));
+    }
 
-            self.top = new_top;
-        }
+    #[test]
+    fn opacity() {
+        let mut window_table = WindowTable::new();
 
-        // See if the top edge of this rectangle intersects the bottom edge of the other.
-        if self.top < other.bottom {
-            let new_bottom = cmp::min(self.bottom, other.bottom);
+        let root = window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let panel = window_table.insert(Rectangle::new(0, 0, 50, 50)).unwrap();
+        let button = window_table.insert(Rectangle::new(10, 10, 20, 20)).unwrap();
 
-            // Push the remainder of the bottom edge if there is any.
-            if new_bottom != self.bottom {
-                remainder.push(Rectangle {
-                    top: new_bottom,
-                    ..self
-                });
-            }
+        window_table.set_opacity(panel, 128);
+        window_table.set_opacity(button, 0);
+        assert_eq!(window_table.effective_opacity(button), 0);
+        assert_eq!(window_table.effective_opacity(panel), 128);
+        assert!(window_table.is_effectively_visible(panel, 100));
+        assert!(!window_table.is_effectively_visible(button, 0));
 
-            self.bottom = new_bottom;
+        // Translucent windows don't hide what's beneath them.
+        assert_eq!(window_table.visible_region(root).area(), 10000);
+    }
+
+    #[test]
+    fn shape() {
+        let mut window_table = WindowTable::new();
+
+        let root = window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let a = window_table.insert(Rectangle::new(0, 0, 50, 50)).unwrap();
+
+        // An L-shaped window.
+        let shape: Region = [Rectangle::new(0, 0, 10, 50), Rectangle::new(0, 40, 50, 50)]
+            .into_iter()
+            .collect();
+        window_table.set_shape(a, Some(shape));
+
+        assert_eq!(window_table.window_at((5, 5)), Some(a));
+        assert_eq!(window_table.window_at((25, 25)), Some(root));
+        assert_eq!(window_table.visible_region(a).area(), 900);
+        assert_eq!(window_table.visible_region(root).area(), 10000 - 900);
+    }
+
+    #[test]
+    fn unmap_hides_children() {
+        let mut window_table = WindowTable::new();
+
+        let root = window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let panel = window_table.insert(Rectangle::new(10, 10, 60, 60)).unwrap();
+        let button = window_table.insert(Rectangle::new(20, 20, 30, 30)).unwrap();
+
+        window_table.unmap(panel);
+        assert!(!window_table.is_viewable(button));
+        assert_eq!(window_table.window_at((25, 25)), Some(root));
+
+        window_table.map(panel);
+        assert!(window_table.is_viewable(button));
+        assert_eq!(window_table.window_at((25, 25)), Some(button));
+    }
+
+    #[test]
+    fn many_windows() {
+        let mut window_table = WindowTable::new();
+        let root = window_table
+            .insert(Rectangle::new(0, 0, 4000, 4000))
+            .unwrap();
+
+        let mut keys = alloc::vec::Vec::new();
+        for i in 0..40 {
+            for j in 0..40 {
+                let (x, y) = (i * 100, j * 100);
+                let rect = Rectangle::new(x, y, x + 90, y + 90);
+                keys.push(window_table.insert(rect).unwrap());
+            }
         }
 
-        // See if the left edge of this rectangle intersects the right edge of the other.
-        if self.left < other.right {
-            let new_right = cmp::min(self.right, other.right);
+        assert_eq!(
+            window_table.window_at((1250, 3710)),
+            Some(keys[12 * 40 + 37])
+        );
+        assert_eq!(window_table.window_at((1295, 3710)), Some(root));
+        assert!(keys
+            .iter()
+            .all(|&key| window_table.parent(key) == Some(root)));
+    }
 
-            // Push the remainder of the right edge if there is any.
-            if new_right != self.right {
-                remainder.push(Rectangle {
-                    left: new_right,
-                    ..self
-                });
+    #[test]
+    fn float_coordinates() {
+        let mut window_table = WindowTable::<f64>::new();
+
+        window_table
+            .insert(Rectangle::new(0.0, 0.0, 100.0, 100.0))
+            .unwrap();
+        let a = window_table
+            .insert(Rectangle::new(10.5, 10.5, 20.25, 20.25))
+            .unwrap();
+        let b = window_table
+            .insert(Rectangle::new(20.25, 10.5, 30.0, 20.25))
+            .unwrap();
+
+        // There is no seam between windows placed at fractional positions.
+        assert_eq!(window_table.window_at((20.2, 15.0)), Some(a));
+        assert_eq!(window_table.window_at((20.25, 15.0)), Some(b));
+        assert_eq!(window_table.visible_region(a).area(), 95.0625);
+
+        window_table.move_window(a, (0.5, 0.5)).unwrap();
+        assert_eq!(window_table.rect(a), Rectangle::new(0.5, 0.5, 10.25, 10.25));
+    }
+
+    #[test]
+    fn window_at() {
+        let mut window_table = WindowTable::new();
+
+        let root = window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let panel = window_table.insert(Rectangle::new(10, 10, 60, 60)).unwrap();
+
+        assert_eq!(window_table.window_at((5, 5)), Some(root));
+        assert_eq!(window_table.window_at((15, 15)), Some(panel));
+        assert_eq!(window_table.window_at((150, 150)), None);
+    }
+
+    #[test]
+    fn input_region() {
+        let mut window_table = WindowTable::new();
+
+        let root = window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let panel = window_table.insert(Rectangle::new(10, 10, 60, 60)).unwrap();
+
+        // Only the top-left quarter of the panel accepts input.
+        window_table.set_input_region(panel, Region::from(Rectangle::new(0, 0, 25, 25)));
+
+        assert_eq!(window_table.window_at((15, 15)), Some(panel));
+        assert_eq!(window_table.window_at((50, 50)), Some(root));
+
+        window_table.reset_input_region(panel);
+        assert_eq!(window_table.input_region(panel), None);
+        assert_eq!(window_table.window_at((50, 50)), Some(panel));
+    }
+
+    #[test]
+    fn input_transparent() {
+        let mut window_table = WindowTable::new();
+
+        let root = window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let overlay = window_table.insert(Rectangle::new(0, 0, 50, 50)).unwrap();
+        let button = window_table.insert(Rectangle::new(10, 10, 20, 20)).unwrap();
+
+        window_table.set_input_transparent(overlay, true);
+
+        assert_eq!(window_table.window_at((5, 5)), Some(root));
+        assert_eq!(window_table.window_at((15, 15)), Some(button));
+    }
+
+    #[test]
+    fn names() {
+        let mut window_table = WindowTable::new();
+
+        let root = window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let a = window_table.insert(Rectangle::new(0, 0, 50, 50)).unwrap();
+        let b = window_table.insert(Rectangle::new(25, 25, 75, 75)).unwrap();
+
+        window_table.set_name(root, "root");
+        window_table.set_name(a, "panel");
+        window_table.set_name(b, "panel");
+        assert_eq!(window_table.name(root), Some("root"));
+        assert_eq!(window_table.find_by_name("root"), Some(root));
+        assert_eq!(window_table.find_by_name("panel"), Some(b));
+        assert_eq!(window_table.find_by_name("button"), None);
+        assert!(alloc::format!("{:?}", window_table).contains("\"root\""));
+
+        window_table.raise(a);
+        assert_eq!(window_table.find_by_name("panel"), Some(a));
+
+        window_table.clear_name(a);
+        assert_eq!(window_table.name(a), None);
+        assert_eq!(window_table.find_by_name("panel"), Some(b));
+    }
+
+    #[test]
+    fn insert_stacked() {
+        let mut window_table = WindowTable::new();
+
+        let root = window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let a = window_table.insert(Rectangle::new(0, 0, 50, 50)).unwrap();
+        let b = window_table.insert(Rectangle::new(10, 10, 20, 20)).unwrap();
+        let c = window_table.insert(Rectangle::new(40, 40, 90, 90)).unwrap();
+
+        // Above `a` and its child `b`, but below `c`.
+        let above = window_table
+            .insert_above(Rectangle::new(30, 30, 48, 48), a)
+            .unwrap();
+        assert_eq!(window_table.window_at((35, 35)), Some(above));
+        assert_eq!(window_table.window_at((45, 45)), Some(c));
+        assert_eq!(window_table.parent(above), Some(a));
+        assert_eq!(window_table.parent(b), Some(a));
+
+        // Below `a`, so only visible where `a` isn't.
+        let below = window_table
+            .insert_below(Rectangle::new(0, 0, 60, 20), a)
+            .unwrap();
+        assert_eq!(window_table.window_at((5, 5)), Some(a));
+        assert_eq!(window_table.window_at((55, 5)), Some(below));
+        assert_eq!(window_table.parent(below), Some(root));
+        assert_eq!(window_table.visible_region(below).area(), 200);
+
+        assert!(matches!(
+            window_table.insert_below(Rectangle::new(0, 0, 10, 10), root),
+            Err(InsertError::BelowRoot)
+        ));
+        assert_eq!(window_table.validate(), Ok(()));
+    }
+
+    #[test]
+    fn circulate() {
+        let mut window_table = WindowTable::new();
+
+        let root = window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let a = window_table.insert(Rectangle::new(0, 0, 50, 50)).unwrap();
+        let b = window_table.insert(Rectangle::new(25, 25, 75, 75)).unwrap();
+        let c = window_table.insert(Rectangle::new(80, 80, 90, 90)).unwrap();
+        let d = window_table.insert(Rectangle::new(10, 10, 20, 20)).unwrap();
+
+        assert_eq!(
+            window_table.circulate(root, CirculateDirection::RaiseLowest),
+            Some(a)
+        );
+        assert_eq!(window_table.window_at((30, 30)), Some(a));
+        assert_eq!(window_table.window_at((15, 15)), Some(d));
+        assert_eq!(window_table.window_at((85, 85)), Some(c));
+
+        assert_eq!(
+            window_table.circulate(root, CirculateDirection::LowerHighest),
+            Some(a)
+        );
+        assert_eq!(window_table.window_at((30, 30)), Some(b));
+        assert_eq!(window_table.window_at((15, 15)), Some(d));
+
+        // Nothing overlaps `c`.
+        assert_eq!(
+            window_table.circulate(c, CirculateDirection::RaiseLowest),
+            None
+        );
+        assert_eq!(window_table.validate(), Ok(()));
+    }
+
+    #[test]
+    fn resize_root() {
+        let mut window_table = WindowTable::new();
+
+        let root = window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let a = window_table.insert(Rectangle::new(0, 0, 20, 20)).unwrap();
+        let b = window_table
+            .insert(Rectangle::new(80, 80, 100, 100))
+            .unwrap();
+        let c = window_table.insert(Rectangle::new(85, 85, 90, 90)).unwrap();
+        let d = window_table.insert(Rectangle::new(60, 0, 80, 10)).unwrap();
+        window_table.set_gravity(d, Gravity::NorthEast);
+        window_table.take_damage();
+        window_table.take_configure_events();
+
+        let outside = window_table
+            .resize_root(Rectangle::new(0, 0, 50, 50))
+            .unwrap();
+        assert_eq!(outside, [b]);
+        assert_eq!(window_table.rect(root), Rectangle::new(0, 0, 50, 50));
+        assert_eq!(window_table.rect(a), Rectangle::new(0, 0, 20, 20));
+        assert_eq!(window_table.rect(b), Rectangle::new(30, 30, 50, 50));
+        assert_eq!(window_table.rect(c), Rectangle::new(35, 35, 40, 40));
+        assert_eq!(window_table.rect(d), Rectangle::new(10, 0, 30, 10));
+        assert_eq!(window_table.validate(), Ok(()));
+
+        assert!(window_table.take_damage().contains((95, 95)));
+        let events = window_table.take_configure_events();
+        assert!(events.iter().any(|event| event.window == b));
+        assert!(events.iter().any(|event| event.window == c));
+
+        assert!(matches!(
+            window_table.resize_root(Rectangle::new(0, 0, 0, 10)),
+            Err(ConfigureError::EmptyRectangle)
+        ));
+        assert!(matches!(
+            window_table.resize_window(root, Rectangle::new(0, 0, 10, 10)),
+            Err(ConfigureError::RootWindow)
+        ));
+    }
+
+    #[test]
+    fn scale_factor() {
+        let mut window_table = WindowTable::new();
+
+        let root = window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let a = window_table.insert(Rectangle::new(0, 0, 50, 50)).unwrap();
+        let b = window_table.insert(Rectangle::new(10, 10, 20, 20)).unwrap();
+        assert_eq!(window_table.scale_factor(b), 1.0);
+
+        window_table.set_scale_factor(root, Some(2.0));
+        window_table.set_scale_factor(a, Some(1.5));
+        assert_eq!(window_table.scale_factor(root), 2.0);
+        assert_eq!(window_table.scale_factor(b), 1.5);
+        assert_eq!(window_table.to_physical(b, (10, 10)), Point::new(15, 15));
+        assert_eq!(window_table.to_logical(b, (15, 15)), Point::new(10, 10));
+        assert_eq!(
+            window_table.physical_rect(b),
+            Rectangle::new(15, 15, 30, 30)
+        );
+
+        assert_eq!(window_table.window_at_physical((30, 30)), Some(b));
+        assert_eq!(window_table.window_at_physical((90, 90)), Some(a));
+        assert_eq!(window_table.window_at_physical((150, 150)), Some(root));
+
+        window_table.set_scale_factor(a, None);
+        assert_eq!(window_table.scale_factor(b), 2.0);
+    }
+
+    #[test]
+    fn output_transform() {
+        let mut window_table = WindowTable::new();
+
+        let root = window_table.insert(Rectangle::new(0, 0, 100, 50)).unwrap();
+        let a = window_table.insert(Rectangle::new(0, 0, 10, 20)).unwrap();
+        window_table.take_damage();
+
+        window_table.set_transform(Transform::Rotate90);
+        assert_eq!(window_table.transform(), Transform::Rotate90);
+        assert_eq!(window_table.take_damage().area(), 5000);
+        assert_eq!(window_table.output_rect(a), Rectangle::new(30, 0, 50, 10));
+        assert_eq!(window_table.to_output((5, 5)), Point::new(45, 5));
+        assert_eq!(window_table.from_output((45, 5)), Point::new(5, 5));
+        assert_eq!(window_table.window_at_output((45, 5)), Some(a));
+        assert_eq!(window_table.window_at_output((5, 5)), Some(root));
+
+        window_table.unmap(a);
+        let damage = window_table.take_output_damage();
+        assert_eq!(damage.bounds(), Some(Rectangle::new(30, 0, 50, 10)));
+    }
+
+    #[test]
+    fn composite() {
+        let mut window_table = WindowTable::new();
+
+        let root = window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let a = window_table.insert(Rectangle::new(0, 0, 50, 50)).unwrap();
+        let b = window_table.insert(Rectangle::new(25, 25, 75, 75)).unwrap();
+        let c = window_table.insert(Rectangle::new(30, 30, 40, 40)).unwrap();
+        window_table.set_opacity(c, 128);
+        window_table.insert(Rectangle::new(0, 0, 10, 10)).unwrap();
+        let e = window_table.insert(Rectangle::new(0, 0, 10, 10)).unwrap();
+
+        let mut visited = alloc::vec::Vec::new();
+        window_table.composite(|key, rects| {
+            let mut region = Region::new();
+            for &rect in rects {
+                region.union_rect(rect);
             }
+            let expected = window_table.visible_region(key);
+            assert_eq!(region.area(), expected.area());
+            region.subtract(&expected);
+            assert!(region.is_empty());
+            visited.push((key, expected.area()));
+        });
 
-            self.right = new_right;
+        // The window beneath `e` is completely covered.
+        assert_eq!(
+            visited,
+            [
+                (root, 10000 - 2500 - 1875),
+                (a, 2500 - 625 - 100),
+                (b, 2500),
+                (c, 100),
+                (e, 100)
+            ]
+        );
+    }
+
+    #[test]
+    fn spans() {
+        let mut window_table = WindowTable::new();
+
+        let root = window_table.insert(Rectangle::new(0, 0, 4, 2)).unwrap();
+        let a = window_table.insert(Rectangle::new(1, 0, 3, 1)).unwrap();
+
+        assert_eq!(
+            window_table.visible_spans(a),
+            [Span {
+                y: 0,
+                x_start: 1,
+                x_end: 3,
+                window: a
+            }]
+        );
+
+        let span = |y, x_start, x_end, window| Span {
+            y,
+            x_start,
+            x_end,
+            window,
+        };
+        assert_eq!(
+            window_table.damage_spans(),
+            [
+                span(0, 0, 1, root),
+                span(0, 1, 3, a),
+                span(0, 3, 4, root),
+                span(1, 0, 4, root)
+            ]
+        );
+
+        window_table.take_damage();
+        assert!(window_table.damage_spans().is_empty());
+    }
+
+    #[test]
+    fn visitor_queries() {
+        let mut window_table = WindowTable::new();
+
+        let root = window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let a = window_table.insert(Rectangle::new(0, 0, 50, 50)).unwrap();
+        let b = window_table.insert(Rectangle::new(25, 25, 75, 75)).unwrap();
+        let c = window_table.insert(Rectangle::new(0, 0, 10, 10)).unwrap();
+        window_table.unmap(c);
+
+        let mut scratch = QueryScratch::new();
+        let mut windows = alloc::vec::Vec::new();
+        window_table.for_each_window_in(Rectangle::new(20, 20, 30, 30), &mut scratch, |key| {
+            windows.push(key)
+        });
+        assert_eq!(windows, [b, a, root]);
+        assert_eq!(
+            window_table.windows_in_rect(Rectangle::new(20, 20, 30, 30)),
+            [b, a, root]
+        );
+        assert_eq!(
+            window_table.windows_in_rect(Rectangle::new(5, 5, 30, 30)),
+            [c, b, a, root]
+        );
+        assert_eq!(
+            window_table.visible_windows_in_rect(Rectangle::new(5, 5, 30, 30)),
+            [b, a]
+        );
+        assert_eq!(
+            window_table.visible_windows_in_rect(Rectangle::new(40, 40, 90, 90)),
+            [b, root]
+        );
+
+        let mut area = [0; 3];
+        window_table.for_each_intersection(
+            Rectangle::new(0, 0, 100, 100),
+            &mut scratch,
+            |key, piece| {
+                let index = [root, a, b]
+                    .iter()
+                    .position(|&window| window == key)
+                    .unwrap();
+                area[index] += piece.width() * piece.height();
+            },
+        );
+        assert_eq!(area, [10000 - 4375, 1875, 2500]);
+    }
+
+    #[test]
+    fn deep_hierarchy() {
+        const DEPTH: i32 = 128;
+
+        let mut window_table = WindowTable::new();
+        let mut windows = alloc::vec::Vec::new();
+        for i in 0..DEPTH {
+            windows.push(
+                window_table
+                    .insert(Rectangle::new(i, i, 3 * DEPTH - i, 3 * DEPTH - i))
+                    .unwrap(),
+            );
         }
 
-        // See if the right edge of this rectangle intersects the left edge of the other.
-        if self.right > other.left {
-            let new_left = cmp::max(self.left, other.left);
+        let deepest = *windows.last().unwrap();
+        assert_eq!(window_table.tree_depth(), DEPTH as usize);
+        assert_eq!(window_table.depth(deepest), Some(DEPTH as usize - 1));
+        assert_eq!(window_table.ancestors(deepest).count(), DEPTH as usize);
+        assert_eq!(
+            window_table.traverse(windows[0], Order::DepthFirst).count(),
+            DEPTH as usize
+        );
+        assert_eq!(window_table.paint_order().count(), DEPTH as usize);
+        assert!(window_table.is_viewable(deepest));
+        assert_eq!(window_table.window_at((DEPTH, DEPTH)), Some(deepest));
 
-            // Push the remainder of the left edge if there is any.
-            if new_left != self.left {
-                remainder.push(Rectangle {
-                    right: new_left,
-                    ..self
-                });
-            }
+        window_table.raise(windows[1]);
+        window_table
+            .move_window(windows[1], (DEPTH / 2, DEPTH / 2))
+            .unwrap();
+        window_table.remove(windows[DEPTH as usize / 2]);
+        assert_eq!(window_table.validate(), Ok(()));
+    }
 
-            self.left = new_left;
+    #[test]
+    fn overlap_policy() {
+        let mut window_table = WindowTable::new();
+        assert_eq!(window_table.overlap_policy(), OverlapPolicy::AllowOverlap);
+
+        let root = window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let a = window_table.insert(Rectangle::new(10, 10, 50, 50)).unwrap();
+
+        window_table.set_overlap_policy(OverlapPolicy::Reject);
+        assert!(matches!(
+            window_table.insert(Rectangle::new(40, 40, 60, 60)),
+            Err(InsertError::Overlaps)
+        ));
+        assert!(matches!(
+            window_table.insert(Rectangle::new(90, 90, 110, 110)),
+            Err(InsertError::OutsideRoot)
+        ));
+        let b = window_table.insert(Rectangle::new(20, 20, 30, 30)).unwrap();
+        assert_eq!(window_table.parent(b), Some(a));
+        window_table.insert(Rectangle::new(60, 60, 70, 70)).unwrap();
+        let e = window_table.insert(Rectangle::new(5, 5, 55, 55)).unwrap();
+
+        // The new window goes above the window covering `a`, so it is clipped to that.
+        window_table.set_overlap_policy(OverlapPolicy::ClipToParent);
+        let c = window_table
+            .insert_above(Rectangle::new(40, 40, 60, 60), a)
+            .unwrap();
+        assert_eq!(window_table.rect(c), Rectangle::new(40, 40, 55, 55));
+        assert_eq!(window_table.parent(c), Some(e));
+        let f = window_table
+            .insert_below(Rectangle::new(35, 35, 55, 55), e)
+            .unwrap();
+        assert_eq!(window_table.rect(f), Rectangle::new(35, 35, 50, 50));
+        let d = window_table
+            .insert(Rectangle::new(90, 90, 110, 110))
+            .unwrap();
+        assert_eq!(window_table.rect(d), Rectangle::new(90, 90, 100, 100));
+        assert_eq!(window_table.parent(d), Some(root));
+        window_table.validate().unwrap();
+    }
+
+    #[test]
+    fn nearest_window() {
+        let mut window_table = WindowTable::new();
+
+        let root = window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let a = window_table.insert(Rectangle::new(10, 10, 30, 30)).unwrap();
+        let b = window_table.insert(Rectangle::new(60, 10, 80, 30)).unwrap();
+        let c = window_table.insert(Rectangle::new(20, 20, 40, 40)).unwrap();
+        let not_root = |key| key != root;
+
+        assert_eq!(window_table.nearest_window((50, 50), |_| true), Some(root));
+        assert_eq!(window_table.nearest_window((25, 25), not_root), Some(c));
+        assert_eq!(window_table.nearest_window((5, 5), not_root), Some(a));
+        assert_eq!(window_table.nearest_window((55, 20), not_root), Some(b));
+        assert_eq!(window_table.nearest_window((50, 35), not_root), Some(c));
+        assert_eq!(
+            window_table.nearest_window((50, 35), |key| key == a),
+            Some(a)
+        );
+        assert_eq!(window_table.nearest_window((50, 35), |_| false), None);
+
+        // Points on the right edge of a window are outside of it.
+        let mut window_table = WindowTable::new();
+
+        let root = window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let d = window_table.insert(Rectangle::new(30, 10, 50, 30)).unwrap();
+        window_table.insert(Rectangle::new(10, 10, 30, 30)).unwrap();
+        assert_eq!(
+            window_table.nearest_window((30, 20), |key| key != root),
+            Some(d)
+        );
+    }
+
+    #[test]
+    fn fragments() {
+        let mut window_table = WindowTable::new();
+
+        let root = window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let a = window_table.insert(Rectangle::new(0, 0, 50, 100)).unwrap();
+        let b = window_table.insert(Rectangle::new(40, 10, 60, 20)).unwrap();
+        assert_eq!(window_table.fragments(root).len(), 0);
+
+        let fragments = window_table.fragments(b).collect::<alloc::vec::Vec<_>>();
+        assert_eq!(
+            fragments,
+            [
+                (a, Rectangle::new(40, 10, 50, 20)),
+                (root, Rectangle::new(50, 10, 60, 20))
+            ]
+        );
+        assert!(fragments
+            .iter()
+            .all(|(parent, _)| window_table.windows[b.0].parents.contains(parent)));
+    }
+
+    #[test]
+    fn link_capacity() {
+        let mut window_table = WindowTable::<i32, 24>::default();
+
+        let root = window_table.insert(Rectangle::new(0, 0, 200, 10)).unwrap();
+        let children = (0..20)
+            .map(|i| {
+                window_table
+                    .insert(Rectangle::new(i * 10, 0, i * 10 + 10, 10))
+                    .unwrap()
+            })
+            .collect::<alloc::vec::Vec<_>>();
+
+        assert!(window_table.children(root).eq(children.iter().copied()));
+        assert_eq!(window_table.window_at((55, 5)), Some(children[5]));
+        window_table.validate().unwrap();
+    }
+
+    #[test]
+    fn region_capacity() {
+        let mut window_table = WindowTable::<i32, 3, 16>::default();
+
+        let root = window_table.insert(Rectangle::new(0, 0, 200, 200)).unwrap();
+        for i in 0..10 {
+            window_table
+                .insert(Rectangle::new(i * 20, i * 20, i * 20 + 10, i * 20 + 10))
+                .unwrap();
         }
 
-        Some((self, remainder))
+        let visible: Region<i32, 16> = window_table.visible_region(root);
+        assert_eq!(visible.area(), 200 * 200 - 10 * 100);
+        assert_eq!(visible.to_capacity::<4>().area(), visible.area());
+        assert_eq!(window_table.take_damage().area(), 200 * 200);
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::{Rectangle, WindowTable};
+    #[test]
+    fn z_index() {
+        let mut window_table = WindowTable::new();
+
+        let root = window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let a = window_table.insert(Rectangle::new(10, 10, 50, 50)).unwrap();
+        let b = window_table.insert(Rectangle::new(30, 30, 70, 70)).unwrap();
+        let c = window_table.insert(Rectangle::new(60, 60, 90, 90)).unwrap();
+
+        window_table.set_z_index(a, 2);
+        window_table.set_z_index(b, 1);
+        assert_eq!(window_table.z_index(a), 2);
+        assert_eq!(window_table.z_index(c), 0);
+        assert!(window_table
+            .paint_order()
+            .map(|(key, _)| key)
+            .eq([root, c, b, a]));
+        assert_eq!(window_table.window_at((40, 40)), Some(a));
+
+        // Raising only moves a window among the windows with the same z-index.
+        window_table.set_z_index(c, 1);
+        window_table.raise(c);
+        let d = window_table.insert(Rectangle::new(0, 0, 20, 20)).unwrap();
+        assert!(window_table
+            .paint_order()
+            .map(|(key, _)| key)
+            .eq([root, d, b, c, a]));
+        assert_eq!(window_table.window_at((65, 65)), Some(c));
+        window_table.validate().unwrap();
+    }
 
     #[test]
-    fn no_intersect() {
-        let a = Rectangle::new(0, 0, 10, 10);
-        let b = Rectangle::new(15, 15, 25, 25);
+    fn layers() {
+        let mut window_table = WindowTable::new();
+
+        let root = window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let dock = window_table
+            .insert(Rectangle::new(0, 80, 100, 100))
+            .unwrap();
+        window_table.set_layer(dock, Layer::Dock);
+        assert_eq!(window_table.layer(dock), Layer::Dock);
 
-        assert!(a.intersection(b).is_none());
+        // New windows and raised windows stay beneath the dock.
+        let a = window_table.insert(Rectangle::new(0, 50, 50, 90)).unwrap();
+        let b = window_table.insert(Rectangle::new(60, 60, 90, 95)).unwrap();
+        assert!(window_table
+            .paint_order()
+            .map(|(key, _)| key)
+            .eq([root, a, b, dock]));
+        window_table.raise(a);
+        assert!(window_table
+            .paint_order()
+            .map(|(key, _)| key)
+            .eq([root, b, a, dock]));
+        assert_eq!(window_table.window_at((20, 85)), Some(dock));
+
+        // Lowering the dock only lowers it within its layer.
+        window_table.lower(dock);
+        assert_eq!(window_table.window_at((20, 85)), Some(dock));
+
+        window_table.set_layer(b, Layer::Overlay);
+        window_table.set_layer(a, Layer::Background);
+        assert!(window_table
+            .paint_order()
+            .map(|(key, _)| key)
+            .eq([root, a, dock, b]));
+        assert_eq!(window_table.window_at((70, 85)), Some(b));
+        assert_eq!(window_table.validate(), Ok(()));
     }
 
     #[test]
-    fn intersect_inside() {
-        let a = Rectangle::new(5, 5, 10, 10);
-        let b = Rectangle::new(0, 0, 15, 15);
+    fn anchors() {
+        let mut window_table = WindowTable::new();
 
-        let (intersection, remainder) = a.intersection(b).unwrap();
-        assert_eq!(intersection, a);
-        assert!(remainder.is_empty());
+        window_table.insert(Rectangle::new(0, 0, 200, 200)).unwrap();
+        let panel = window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let toolbar = window_table.insert(Rectangle::new(8, 70, 92, 92)).unwrap();
+        let button = window_table.insert(Rectangle::new(10, 10, 20, 20)).unwrap();
+        let anchors = Anchors {
+            left: Some(8),
+            right: Some(8),
+            bottom: Some(8),
+            height: Some(22),
+            ..Anchors::default()
+        };
+        window_table.set_anchors(toolbar, Some(anchors));
+        window_table.set_anchors(button, Some(Anchors::default()));
+        assert_eq!(window_table.anchors(toolbar), Some(anchors));
+
+        // The toolbar stretches along the bottom, and the unanchored button follows the corner.
+        window_table
+            .resize_window(panel, Rectangle::new(20, 20, 170, 150))
+            .unwrap();
+        assert_eq!(
+            window_table.rect(toolbar),
+            Rectangle::new(28, 120, 162, 142)
+        );
+        assert_eq!(window_table.rect(button), Rectangle::new(30, 30, 40, 40));
+
+        assert_eq!(
+            Anchors {
+                right: Some(5),
+                width: Some(10),
+                ..Anchors::default()
+            }
+            .resolve(Rectangle::new(0, 0, 50, 50), Rectangle::new(0, 0, 20, 20)),
+            Rectangle::new(35, 0, 45, 20)
+        );
     }
 
     #[test]
-    fn intersect_corner() {
-        let a = Rectangle::new(0, 0, 10, 10);
-        let b = Rectangle::new(5, 5, 15, 15);
+    fn relative() {
+        let mut window_table = WindowTable::new();
 
-        let (intersection, remainder) = a.intersection(b).unwrap();
+        window_table.insert(Rectangle::new(0, 0, 400, 400)).unwrap();
+        let dashboard = window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let middle = RelativeRect::new(0.25, 0.0, 0.75, 0.5);
+        let widget = window_table.insert_relative(dashboard, middle).unwrap();
+        assert_eq!(window_table.rect(widget), Rectangle::new(25, 0, 75, 50));
+        assert_eq!(window_table.relative(widget), Some(middle));
 
-        assert_eq!(intersection, Rectangle::new(5, 5, 10, 10));
-        assert_eq!(remainder.len(), 2);
+        window_table
+            .resize_window(dashboard, Rectangle::new(100, 100, 300, 200))
+            .unwrap();
+        assert_eq!(
+            window_table.rect(widget),
+            Rectangle::new(150, 100, 250, 150)
+        );
+
+        // Without the fractions, the widget falls back to its gravity.
+        window_table.set_relative(widget, None);
+        window_table
+            .resize_window(dashboard, Rectangle::new(0, 0, 400, 400))
+            .unwrap();
+        assert_eq!(window_table.rect(widget), Rectangle::new(50, 0, 150, 50));
     }
 
     #[test]
-    fn insert() {
+    fn contents_valid() {
         let mut window_table = WindowTable::new();
 
-        // Inserting a single rectangle should install it as the root.
-        let rect1 = window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
-        assert_eq!(Some(rect1), window_table.root);
+        let root = window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let a = window_table.insert(Rectangle::new(10, 10, 40, 40)).unwrap();
+        let b = window_table.insert(Rectangle::new(60, 60, 90, 90)).unwrap();
+        assert_eq!(window_table.needs_redraw(), [root, a, b]);
 
-        {
-            let slot = &window_table.windows[rect1.0];
-            assert!(slot.children.is_empty());
-            assert!(slot.parents.is_empty());
+        for key in [root, a, b] {
+            window_table.mark_contents_valid(key);
         }
+        assert!(window_table.needs_redraw().is_empty());
+
+        // Moving a window exposes what was beneath it, but its own contents are kept.
+        window_table.move_window(a, (20, 20)).unwrap();
+        assert_eq!(window_table.needs_redraw(), [root]);
+        assert!(window_table.contents_valid(a));
+
+        window_table.mark_contents_valid(root);
+        window_table
+            .resize_window(b, Rectangle::new(60, 60, 80, 80))
+            .unwrap();
+        assert_eq!(window_table.needs_redraw(), [root, b]);
+
+        window_table.mark_contents_valid(root);
+        window_table.mark_contents_valid(b);
+        window_table.invalidate_contents(a);
+        assert_eq!(window_table.needs_redraw(), [a]);
+    }
+
+    #[test]
+    fn damage_since() {
+        let mut window_table = WindowTable::new();
+        window_table.set_damage_history_len(2);
+        assert_eq!(window_table.damage_history_len(), 2);
+
+        window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        window_table.take_damage();
+
+        window_table.insert(Rectangle::new(0, 0, 10, 10)).unwrap();
+        window_table.take_damage();
+        window_table.insert(Rectangle::new(20, 0, 30, 10)).unwrap();
+        window_table.take_damage();
+        window_table.insert(Rectangle::new(40, 0, 50, 10)).unwrap();
+
+        assert_eq!(window_table.damage_since(1).area(), 100);
+        assert_eq!(window_table.damage_since(2).area(), 200);
+        assert_eq!(window_table.damage_since(3).area(), 300);
+
+        // Older buffers than the history need a full repaint.
+        assert_eq!(window_table.damage_since(4).area(), 10000);
+        assert_eq!(window_table.damage_since(0).area(), 10000);
+    }
+
+    #[test]
+    fn key_ffi() {
+        let mut window_table = WindowTable::new();
+
+        let root = window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let a = window_table.insert(Rectangle::new(10, 10, 40, 40)).unwrap();
+        assert_eq!(WindowKey::from_ffi(a.as_ffi()), a);
+        assert_ne!(a.as_ffi(), root.as_ffi());
+
+        // The slot of a removed window is reused under a new generation.
+        window_table.remove(a);
+        let b = window_table.insert(Rectangle::new(10, 10, 40, 40)).unwrap();
+        assert_ne!(a.as_ffi(), b.as_ffi());
+        assert!(!window_table.contains_key(WindowKey::from_ffi(a.as_ffi())));
     }
 }
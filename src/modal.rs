@@ -0,0 +1,182 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later OR MPL-2.0
+// This file is a part of `windowless`.
+//
+// `windowless` is free software: you can redistribute it and/or modify it under the terms of
+// either:
+//
+// * GNU Lesser General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+// * Mozilla Public License as published by the Mozilla Foundation, version 2.
+//
+// `windowless` is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+// See the GNU Lesser General Public License or the Mozilla Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License and the Mozilla
+// Public License along with `windowless`. If not, see <https://www.gnu.org/licenses/> or
+// <https://www.mozilla.org/en-US/MPL/2.0/>.
+
+//! Modal windows, which block input to the windows around them.
+
+use crate::{Coordinate, Point, WindowKey, WindowTable};
+use core::fmt;
+
+/// The windows that a modal window blocks input to.
+///
+/// Set with [`WindowTable::set_modal`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub enum ModalScope {
+    /// The window is not modal.
+    #[default]
+    Modeless,
+
+    /// The window blocks input to its primary parent and the parent's other descendants.
+    Parent,
+
+    /// The window blocks input to every other window in the table.
+    Root,
+}
+
+/// Input was refused because a modal window is blocking the window it was meant for.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct ModalBlocked {
+    /// The window that the input was meant for.
+    pub window: WindowKey,
+
+    /// The modal window blocking it.
+    pub modal: WindowKey,
+}
+
+impl fmt::Display for ModalBlocked {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "input to window {:?} is blocked by modal window {:?}",
+            self.window, self.modal
+        )
+    }
+}
+
+impl<T: Coordinate, const N: usize, const R: usize> WindowTable<T, N, R> {
+    /// Set whether a window is modal, and which windows it blocks input to.
+    ///
+    /// A modal window only blocks input while it is viewable. The modal window and its own
+    /// descendants are never blocked by it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn set_modal(&mut self, key: WindowKey, scope: ModalScope) {
+        self.windows[key.0].modal = scope;
+    }
+
+    /// Returns which windows a window blocks input to.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn modal(&self, key: WindowKey) -> ModalScope {
+        self.windows[key.0].modal
+    }
+
+    /// Find the modal window blocking input to a window.
+    ///
+    /// If several modal windows block it, the topmost one is returned. Returns `None` if the
+    /// window is not blocked, or does not exist.
+    pub fn blocking_modal(&self, key: WindowKey) -> Option<WindowKey> {
+        if !self.contains_key(key) {
+            return None;
+        }
+
+        self.order.iter().rev().copied().find(|&modal| {
+            let scope = match self.windows[modal.0].modal {
+                ModalScope::Modeless => return false,
+                ModalScope::Parent => self.parent(modal),
+                ModalScope::Root => self.root,
+            };
+
+            let blocked = match scope {
+                Some(scope) => self.ancestors(key).any(|ancestor| ancestor == scope),
+                None => false,
+            };
+
+            blocked
+                && self.is_viewable(modal)
+                && !self.ancestors(key).any(|ancestor| ancestor == modal)
+        })
+    }
+
+    /// Find the topmost window that accepts input at a position, unless a modal window blocks
+    /// it.
+    ///
+    /// This is [`window_at`](Self::window_at), but input to windows blocked by a modal window is
+    /// refused.
+    ///
+    /// # Errors
+    ///
+    /// Returns the blocked window and the modal window blocking it.
+    pub fn hit_test(
+        &self,
+        position: impl Into<Point<T>>,
+    ) -> Result<Option<WindowKey>, ModalBlocked> {
+        match self.window_at(position) {
+            Some(window) => match self.blocking_modal(window) {
+                Some(modal) => Err(ModalBlocked { window, modal }),
+                None => Ok(Some(window)),
+            },
+            None => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ModalBlocked, ModalScope};
+    use crate::{FocusState, Rectangle, WindowTable};
+
+    #[test]
+    fn modal() {
+        let mut window_table = WindowTable::new();
+
+        let root = window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let editor = window_table.insert(Rectangle::new(0, 0, 50, 100)).unwrap();
+        let other = window_table
+            .insert(Rectangle::new(60, 0, 100, 100))
+            .unwrap();
+        let dialog = window_table.insert(Rectangle::new(10, 10, 40, 40)).unwrap();
+        let button = window_table.insert(Rectangle::new(20, 20, 30, 30)).unwrap();
+        window_table.set_modal(dialog, ModalScope::Parent);
+        assert_eq!(window_table.parent(dialog), Some(editor));
+
+        // The dialog blocks its parent, but not the windows beside it.
+        assert_eq!(
+            window_table.hit_test((5, 50)),
+            Err(ModalBlocked {
+                window: editor,
+                modal: dialog
+            })
+        );
+        assert_eq!(window_table.hit_test((25, 25)), Ok(Some(button)));
+        assert_eq!(window_table.hit_test((70, 50)), Ok(Some(other)));
+        assert_eq!(window_table.blocking_modal(root), None);
+
+        window_table.set_modal(dialog, ModalScope::Root);
+        assert_eq!(window_table.blocking_modal(other), Some(dialog));
+        assert_eq!(window_table.blocking_modal(root), Some(dialog));
+
+        let mut focus = FocusState::new();
+        focus.set_focus(&window_table, Some(other));
+        assert_eq!(
+            focus.route_key_event(&window_table, 'a').unwrap_err(),
+            ModalBlocked {
+                window: other,
+                modal: dialog
+            }
+        );
+
+        // Unmapped modal windows don't block anything.
+        window_table.unmap(dialog);
+        assert_eq!(window_table.hit_test((5, 50)), Ok(Some(editor)));
+        assert!(focus.route_key_event(&window_table, 'a').is_ok());
+    }
+}
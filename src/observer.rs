@@ -0,0 +1,183 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later OR MPL-2.0
+// This file is a part of `windowless`.
+//
+// `windowless` is free software: you can redistribute it and/or modify it under the terms of
+// either:
+//
+// * GNU Lesser General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+// * Mozilla Public License as published by the Mozilla Foundation, version 2.
+//
+// `windowless` is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+// See the GNU Lesser General Public License or the Mozilla Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License and the Mozilla
+// Public License along with `windowless`. If not, see <https://www.gnu.org/licenses/> or
+// <https://www.mozilla.org/en-US/MPL/2.0/>.
+
+//! Callbacks for changes to the window table.
+
+use crate::{Coordinate, Rectangle, WindowKey};
+use alloc::boxed::Box;
+use core::fmt;
+use slotmap::{new_key_type, SlotMap};
+
+/// Receives callbacks when the structure of a window table changes.
+///
+/// Register an observer with [`WindowTable::add_observer`](crate::WindowTable::add_observer).
+/// Every method does nothing by default.
+pub trait WindowObserver<T: Coordinate = i32> {
+    /// A window was inserted.
+    fn on_insert(&mut self, key: WindowKey, rect: Rectangle<T>) {
+        let _ = (key, rect);
+    }
+
+    /// A window was removed.
+    fn on_remove(&mut self, key: WindowKey, rect: Rectangle<T>) {
+        let _ = (key, rect);
+    }
+
+    /// The rectangle of a window changed.
+    fn on_geometry_change(&mut self, key: WindowKey, old: Rectangle<T>, new: Rectangle<T>) {
+        let _ = (key, old, new);
+    }
+
+    /// A window was raised or lowered, along with its descendants.
+    fn on_restack(&mut self, key: WindowKey) {
+        let _ = key;
+    }
+}
+
+/// The key of a registered observer.
+///
+/// Returned by [`WindowTable::add_observer`](crate::WindowTable::add_observer).
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ObserverKey(ObserverSlot);
+
+new_key_type! {
+    struct ObserverSlot;
+}
+
+/// The observers registered on a table.
+///
+/// Observers are not copied when the table is cloned.
+pub(crate) struct Observers<T: Coordinate> {
+    /// The observers.
+    observers: SlotMap<ObserverSlot, Box<dyn WindowObserver<T> + Send>>,
+}
+
+impl<T: Coordinate> Observers<T> {
+    /// Register an observer.
+    pub(crate) fn insert(&mut self, observer: Box<dyn WindowObserver<T> + Send>) -> ObserverKey {
+        ObserverKey(self.observers.insert(observer))
+    }
+
+    /// Unregister an observer.
+    pub(crate) fn remove(&mut self, key: ObserverKey) -> Option<Box<dyn WindowObserver<T> + Send>> {
+        self.observers.remove(key.0)
+    }
+
+    /// Tell if there are no observers.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.observers.is_empty()
+    }
+
+    /// Call every observer.
+    pub(crate) fn notify(&mut self, mut f: impl FnMut(&mut dyn WindowObserver<T>)) {
+        for observer in self.observers.values_mut() {
+            f(&mut **observer);
+        }
+    }
+}
+
+impl<T: Coordinate> Default for Observers<T> {
+    fn default() -> Self {
+        Self {
+            observers: SlotMap::with_key(),
+        }
+    }
+}
+
+impl<T: Coordinate> Clone for Observers<T> {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl<T: Coordinate> fmt::Debug for Observers<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Observers")
+            .field("len", &self.observers.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::WindowObserver;
+    use crate::{Rectangle, WindowKey, WindowTable};
+    use alloc::sync::Arc;
+    use core::cell::Cell;
+    use core::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Counts the calls to each method.
+    #[derive(Default)]
+    struct Counter([AtomicUsize; 4]);
+
+    impl WindowObserver for Arc<Counter> {
+        fn on_insert(&mut self, _: WindowKey, _: Rectangle) {
+            self.0[0].fetch_add(1, Ordering::Relaxed);
+        }
+
+        fn on_remove(&mut self, _: WindowKey, _: Rectangle) {
+            self.0[1].fetch_add(1, Ordering::Relaxed);
+        }
+
+        fn on_geometry_change(&mut self, _: WindowKey, _: Rectangle, _: Rectangle) {
+            self.0[2].fetch_add(1, Ordering::Relaxed);
+        }
+
+        fn on_restack(&mut self, _: WindowKey) {
+            self.0[3].fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    #[test]
+    fn notify() {
+        let counter = Arc::new(Counter::default());
+        let mut window_table = WindowTable::new();
+        let observer = window_table.add_observer(counter.clone());
+
+        let root = window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let a = window_table.insert(Rectangle::new(0, 0, 50, 50)).unwrap();
+        window_table.insert(Rectangle::new(10, 10, 20, 20)).unwrap();
+        window_table.move_window(a, (10, 10)).unwrap();
+        window_table.raise(a);
+        window_table.remove(root);
+
+        let counts = counter.0.iter().map(|count| count.load(Ordering::Relaxed));
+        assert!(counts.eq([3, 3, 2, 1]));
+
+        assert!(window_table.remove_observer(observer).is_some());
+        window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        assert_eq!(counter.0[0].load(Ordering::Relaxed), 3);
+    }
+
+    #[test]
+    fn not_sync() {
+        /// Only needs to be `Send` to be registered.
+        struct Inserts(Cell<usize>);
+
+        impl WindowObserver for Inserts {
+            fn on_insert(&mut self, _: WindowKey, _: Rectangle) {
+                self.0.set(self.0.get() + 1);
+            }
+        }
+
+        let mut window_table = WindowTable::new();
+        let observer = window_table.add_observer(Inserts(Cell::new(0)));
+        window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        assert!(window_table.remove_observer(observer).is_some());
+    }
+}
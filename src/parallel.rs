@@ -0,0 +1,114 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later OR MPL-2.0
+// This file is a part of `windowless`.
+//
+// `windowless` is free software: you can redistribute it and/or modify it under the terms of
+// either:
+//
+// * GNU Lesser General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+// * Mozilla Public License as published by the Mozilla Foundation, version 2.
+//
+// `windowless` is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+// See the GNU Lesser General Public License or the Mozilla Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License and the Mozilla
+// Public License along with `windowless`. If not, see <https://www.gnu.org/licenses/> or
+// <https://www.mozilla.org/en-US/MPL/2.0/>.
+
+//! Computing the visible parts of windows on several threads.
+
+use crate::{Coordinate, Rectangle, Region, WindowKey, WindowTable};
+use alloc::collections::BTreeSet;
+use alloc::vec::Vec;
+use rayon::prelude::*;
+
+impl<T: Coordinate + Send + Sync, const N: usize, const R: usize> WindowTable<T, N, R> {
+    /// Get the [visible region](Self::visible_region) of every viewable window, computing them
+    /// in parallel.
+    ///
+    /// The windows come in paint order. Each window's region only depends on the windows above
+    /// it, so large scenes with many independent windows spread well across threads.
+    pub fn par_visible_regions(&self) -> Vec<(WindowKey, Region<T, R>)> {
+        let order = self.paint_order().map(|(key, _)| key).collect::<Vec<_>>();
+        let occluders = order
+            .iter()
+            .copied()
+            .filter(|&key| self.occludes(key))
+            .collect::<BTreeSet<_>>();
+
+        // The observers and the damage sink don't need to be `Sync`, so the table can't be
+        // shared between threads. Only the windows and the index are.
+        let (windows, index) = (&self.windows, &self.index);
+        let root = self.root.map(|root| windows[root.0].rect);
+        order
+            .into_par_iter()
+            .map(|key| {
+                let window = &windows[key.0];
+                let mut region = window.bounding_region();
+                if let Some(root) = root {
+                    region.intersect_rect(root);
+                }
+
+                for other in index.query_rect(window.rect) {
+                    if windows[other.0].z > window.z && occluders.contains(&other) {
+                        region.subtract(&windows[other.0].bounding_region());
+                    }
+                }
+
+                (key, region)
+            })
+            .collect()
+    }
+
+    /// Like [`composite`](Self::composite), but the visible parts of the windows are computed in
+    /// parallel.
+    ///
+    /// The windows are still handed to `f` one at a time, in paint order.
+    pub fn par_composite(&self, mut f: impl FnMut(WindowKey, &[Rectangle<T>])) {
+        for (key, region) in self.par_visible_regions() {
+            if !region.is_empty() {
+                f(key, region.rects());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Rectangle, WindowTable};
+    use alloc::vec::Vec;
+
+    #[test]
+    fn par_composite() {
+        let mut window_table = WindowTable::new();
+
+        window_table
+            .insert(Rectangle::new(0, 0, 1000, 1000))
+            .unwrap();
+        for i in 0..64 {
+            let offset = (i * 37) % 900;
+            window_table
+                .insert(Rectangle::new(offset, i * 10, offset + 100, i * 10 + 100))
+                .unwrap();
+        }
+        let hidden = window_table.insert(Rectangle::new(0, 0, 10, 10)).unwrap();
+        window_table.insert(Rectangle::new(0, 0, 10, 10)).unwrap();
+
+        let regions = window_table.par_visible_regions();
+        assert!(regions
+            .iter()
+            .map(|(key, _)| *key)
+            .eq(window_table.paint_order().map(|(key, _)| key)));
+        for (key, region) in &regions {
+            assert_eq!(*region, window_table.visible_region(*key));
+        }
+
+        let mut sequential = Vec::new();
+        window_table.composite(|key, _| sequential.push(key));
+        let mut parallel = Vec::new();
+        window_table.par_composite(|key, _| parallel.push(key));
+        assert_eq!(sequential, parallel);
+        assert!(!parallel.contains(&hidden));
+    }
+}
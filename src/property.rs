@@ -0,0 +1,163 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later OR MPL-2.0
+// This file is a part of `windowless`.
+//
+// `windowless` is free software: you can redistribute it and/or modify it under the terms of
+// either:
+//
+// * GNU Lesser General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+// * Mozilla Public License as published by the Mozilla Foundation, version 2.
+//
+// `windowless` is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+// See the GNU Lesser General Public License or the Mozilla Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License and the Mozilla
+// Public License along with `windowless`. If not, see <https://www.gnu.org/licenses/> or
+// <https://www.mozilla.org/en-US/MPL/2.0/>.
+
+//! Named, typed values attached to windows.
+
+use crate::WindowKey;
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// An interned property name.
+///
+/// Created by [`WindowTable::intern_atom`](crate::WindowTable::intern_atom). Atoms are only
+/// meaningful for the table that created them.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Atom(u32);
+
+/// The value of a property.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Property {
+    /// An integer.
+    Integer(i64),
+
+    /// A string.
+    Text(String),
+
+    /// An atom.
+    Atom(Atom),
+
+    /// A reference to another window.
+    ///
+    /// The window may no longer exist.
+    Window(WindowKey),
+
+    /// Raw bytes.
+    Bytes(Vec<u8>),
+}
+
+/// A change to a property of a window.
+///
+/// Returned by [`WindowTable::take_property_events`](crate::WindowTable::take_property_events).
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct PropertyEvent {
+    /// The window whose property changed.
+    pub window: WindowKey,
+
+    /// The name of the property.
+    pub atom: Atom,
+
+    /// What happened to the property.
+    pub state: PropertyState,
+}
+
+/// What happened to a property.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum PropertyState {
+    /// The property was given a new value.
+    NewValue,
+
+    /// The property was deleted.
+    Deleted,
+}
+
+/// The table of interned atoms.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Atoms {
+    /// The name of each atom, indexed by the atom.
+    names: Vec<String>,
+
+    /// The atom for each name.
+    atoms: BTreeMap<String, Atom>,
+}
+
+impl Atoms {
+    /// Get the atom for a name, creating it if it doesn't exist.
+    pub(crate) fn intern(&mut self, name: &str) -> Atom {
+        if let Some(&atom) = self.atoms.get(name) {
+            return atom;
+        }
+
+        let atom = Atom(self.names.len() as u32);
+        self.names.push(name.into());
+        self.atoms.insert(name.into(), atom);
+        atom
+    }
+
+    /// Get the atom for a name, if it exists.
+    pub(crate) fn get(&self, name: &str) -> Option<Atom> {
+        self.atoms.get(name).copied()
+    }
+
+    /// Get the name of an atom.
+    pub(crate) fn name(&self, atom: Atom) -> Option<&str> {
+        self.names.get(atom.0 as usize).map(|name| name.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Property, PropertyEvent, PropertyState};
+    use crate::{Rectangle, WindowTable};
+
+    #[test]
+    fn properties() {
+        let mut window_table = WindowTable::new();
+
+        window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let a = window_table.insert(Rectangle::new(0, 0, 50, 50)).unwrap();
+
+        let title = window_table.intern_atom("TITLE");
+        assert_eq!(window_table.intern_atom("TITLE"), title);
+        assert_eq!(window_table.atom("TITLE"), Some(title));
+        assert_eq!(window_table.atom("PID"), None);
+        assert_eq!(window_table.atom_name(title), Some("TITLE"));
+
+        let old = window_table.set_property(a, title, Property::Text("Editor".into()));
+        assert_eq!(old, None);
+        assert_eq!(
+            window_table.property(a, title),
+            Some(&Property::Text("Editor".into()))
+        );
+        assert_eq!(window_table.properties(a).count(), 1);
+
+        assert_eq!(
+            window_table.delete_property(a, title),
+            Some(Property::Text("Editor".into()))
+        );
+        assert_eq!(window_table.delete_property(a, title), None);
+        assert_eq!(window_table.property(a, title), None);
+
+        assert_eq!(
+            window_table.take_property_events(),
+            [
+                PropertyEvent {
+                    window: a,
+                    atom: title,
+                    state: PropertyState::NewValue
+                },
+                PropertyEvent {
+                    window: a,
+                    atom: title,
+                    state: PropertyState::Deleted
+                },
+            ]
+        );
+        assert!(window_table.take_property_events().is_empty());
+    }
+}
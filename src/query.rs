@@ -0,0 +1,50 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later OR MPL-2.0
+// This file is a part of `windowless`.
+//
+// `windowless` is free software: you can redistribute it and/or modify it under the terms of
+// either:
+//
+// * GNU Lesser General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+// * Mozilla Public License as published by the Mozilla Foundation, version 2.
+//
+// `windowless` is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+// See the GNU Lesser General Public License or the Mozilla Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License and the Mozilla
+// Public License along with `windowless`. If not, see <https://www.gnu.org/licenses/> or
+// <https://www.mozilla.org/en-US/MPL/2.0/>.
+
+//! Reusable buffers for queries on the window table.
+
+use crate::{Coordinate, Rectangle, WindowKey};
+use alloc::vec::Vec;
+
+/// Buffers that queries can reuse instead of allocating.
+///
+/// Pass the same scratch space to queries like
+/// [`WindowTable::for_each_intersection`](crate::WindowTable::for_each_intersection) every
+/// frame. Once the buffers have grown large enough, the queries stop allocating.
+#[derive(Debug, Clone, Default)]
+pub struct QueryScratch<T = i32> {
+    /// The windows found by a query.
+    pub(crate) windows: Vec<WindowKey>,
+
+    /// The pieces of a rectangle that haven't been assigned to a window yet.
+    pub(crate) rects: Vec<Rectangle<T>>,
+
+    /// The pieces left over after splitting against a window.
+    pub(crate) uncovered: Vec<Rectangle<T>>,
+}
+
+impl<T: Coordinate> QueryScratch<T> {
+    /// Creates empty scratch space.
+    pub fn new() -> Self {
+        Self {
+            windows: Vec::new(),
+            rects: Vec::new(),
+            uncovered: Vec::new(),
+        }
+    }
+}
@@ -0,0 +1,758 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later OR MPL-2.0
+// This file is a part of `windowless`.
+//
+// `windowless` is free software: you can redistribute it and/or modify it under the terms of
+// either:
+//
+// * GNU Lesser General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+// * Mozilla Public License as published by the Mozilla Foundation, version 2.
+//
+// `windowless` is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+// See the GNU Lesser General Public License or the Mozilla Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License and the Mozilla
+// Public License along with `windowless`. If not, see <https://www.gnu.org/licenses/> or
+// <https://www.mozilla.org/en-US/MPL/2.0/>.
+
+//! Recording the changes made to a window table, so they can be replayed.
+
+use crate::{
+    Anchors, CirculateDirection, ConfigureError, ConfigureRequest, Coordinate, EventMask, Extents,
+    Gravity, InsertError, Layer, MaximizeAxis, ModalScope, OverlapPolicy, Point, Rectangle, Region,
+    RelativeRect, SizeHints, WindowKey, WindowTable, WorkspaceId,
+};
+use alloc::vec::Vec;
+
+/// A change made to a window table, along with its arguments.
+///
+/// Recorded by a [`Recorder`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Call<T: Coordinate = i32> {
+    /// [`WindowTable::insert`]
+    Insert(Rectangle<T>),
+
+    /// [`WindowTable::insert_above`]
+    InsertAbove(Rectangle<T>, WindowKey),
+
+    /// [`WindowTable::insert_below`]
+    InsertBelow(Rectangle<T>, WindowKey),
+
+    /// [`WindowTable::remove`]
+    Remove(WindowKey),
+
+    /// [`WindowTable::resize_window`]
+    ResizeWindow(WindowKey, Rectangle<T>),
+
+    /// [`WindowTable::move_window`]
+    MoveWindow(WindowKey, Point<T>),
+
+    /// [`WindowTable::resize_root`]
+    ResizeRoot(Rectangle<T>),
+
+    /// [`WindowTable::raise`]
+    Raise(WindowKey),
+
+    /// [`WindowTable::lower`]
+    Lower(WindowKey),
+
+    /// [`WindowTable::map`]
+    Map(WindowKey),
+
+    /// [`WindowTable::unmap`]
+    Unmap(WindowKey),
+
+    /// [`WindowTable::set_layer`]
+    SetLayer(WindowKey, Layer),
+
+    /// [`WindowTable::set_z_index`]
+    SetZIndex(WindowKey, i32),
+
+    /// [`WindowTable::set_gravity`]
+    SetGravity(WindowKey, Gravity),
+
+    /// [`WindowTable::set_border_width`]
+    SetBorderWidth(WindowKey, u16),
+
+    /// [`WindowTable::set_opacity`]
+    SetOpacity(WindowKey, u8),
+
+    /// [`WindowTable::set_shape`]
+    SetShape(WindowKey, Option<Region<T>>),
+
+    /// [`WindowTable::set_input_region`]
+    SetInputRegion(WindowKey, Region<T>),
+
+    /// [`WindowTable::reset_input_region`]
+    ResetInputRegion(WindowKey),
+
+    /// [`WindowTable::clear`]
+    Clear,
+
+    /// [`WindowTable::insert_relative`]
+    InsertRelative(WindowKey, RelativeRect),
+
+    /// [`WindowTable::set_overlap_policy`]
+    SetOverlapPolicy(OverlapPolicy),
+
+    /// [`WindowTable::set_background`]
+    SetBackground(Option<WindowKey>),
+
+    /// [`WindowTable::circulate`]
+    Circulate(WindowKey, CirculateDirection),
+
+    /// [`WindowTable::minimize`]
+    Minimize(WindowKey),
+
+    /// [`WindowTable::restore`]
+    Restore(WindowKey),
+
+    /// [`WindowTable::set_fullscreen`]
+    SetFullscreen(WindowKey, bool),
+
+    /// [`WindowTable::maximize`]
+    Maximize(WindowKey, MaximizeAxis),
+
+    /// [`WindowTable::unmaximize`]
+    Unmaximize(WindowKey),
+
+    /// [`WindowTable::request_configure`]
+    RequestConfigure(WindowKey, Rectangle<T>),
+
+    /// [`WindowTable::modify_configure`]
+    ModifyConfigure(u32, Rectangle<T>),
+
+    /// [`WindowTable::deny_configure`]
+    DenyConfigure(u32),
+
+    /// [`WindowTable::apply_configure`]
+    ApplyConfigure(WindowKey, u32),
+
+    /// [`WindowTable::set_size_hints`]
+    SetSizeHints(WindowKey, SizeHints<T>),
+
+    /// [`WindowTable::set_anchors`]
+    SetAnchors(WindowKey, Option<Anchors<T>>),
+
+    /// [`WindowTable::set_relative`]
+    SetRelative(WindowKey, Option<RelativeRect>),
+
+    /// [`WindowTable::set_visual_extents`]
+    SetVisualExtents(WindowKey, Option<Extents<T>>),
+
+    /// [`WindowTable::set_input_margin`]
+    SetInputMargin(WindowKey, Option<Extents<T>>),
+
+    /// [`WindowTable::set_input_transparent`]
+    SetInputTransparent(WindowKey, bool),
+
+    /// [`WindowTable::set_event_mask`]
+    SetEventMask(WindowKey, EventMask),
+
+    /// [`WindowTable::set_save_under`]
+    SetSaveUnder(WindowKey, bool),
+
+    /// [`WindowTable::set_modal`]
+    SetModal(WindowKey, ModalScope),
+
+    /// [`WindowTable::set_transient_for`]
+    SetTransientFor(WindowKey, Option<WindowKey>),
+
+    /// [`WindowTable::map_group`]
+    MapGroup(WindowKey),
+
+    /// [`WindowTable::unmap_group`]
+    UnmapGroup(WindowKey),
+
+    /// [`WindowTable::minimize_group`]
+    MinimizeGroup(WindowKey),
+
+    /// [`WindowTable::restore_group`]
+    RestoreGroup(WindowKey),
+
+    /// [`WindowTable::set_workspaces`]
+    SetWorkspaces(WindowKey, Vec<WorkspaceId>),
+
+    /// [`WindowTable::set_active_workspace`]
+    SetActiveWorkspace(WorkspaceId),
+}
+
+impl<T: Coordinate> Call<T> {
+    /// Make this change to a table.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the change names a window that does not exist in the table.
+    pub fn apply<const N: usize, const R: usize>(&self, table: &mut WindowTable<T, N, R>) {
+        match self {
+            Call::Insert(rect) => {
+                table.insert(*rect).ok();
+            }
+            Call::InsertAbove(rect, sibling) => {
+                table.insert_above(*rect, *sibling).ok();
+            }
+            Call::InsertBelow(rect, sibling) => {
+                table.insert_below(*rect, *sibling).ok();
+            }
+            Call::Remove(key) => {
+                table.remove(*key);
+            }
+            Call::ResizeWindow(key, rect) => {
+                table.resize_window(*key, *rect).ok();
+            }
+            Call::MoveWindow(key, position) => {
+                table.move_window(*key, *position).ok();
+            }
+            Call::ResizeRoot(rect) => {
+                table.resize_root(*rect).ok();
+            }
+            Call::Raise(key) => table.raise(*key),
+            Call::Lower(key) => table.lower(*key),
+            Call::Map(key) => table.map(*key),
+            Call::Unmap(key) => table.unmap(*key),
+            Call::SetLayer(key, layer) => table.set_layer(*key, *layer),
+            Call::SetZIndex(key, z_index) => table.set_z_index(*key, *z_index),
+            Call::SetGravity(key, gravity) => table.set_gravity(*key, *gravity),
+            Call::SetBorderWidth(key, width) => table.set_border_width(*key, *width),
+            Call::SetOpacity(key, opacity) => table.set_opacity(*key, *opacity),
+            Call::SetShape(key, shape) => {
+                table.set_shape(*key, shape.as_ref().map(Region::to_capacity));
+            }
+            Call::SetInputRegion(key, region) => table.set_input_region(*key, region.to_capacity()),
+            Call::ResetInputRegion(key) => table.reset_input_region(*key),
+            Call::Clear => table.clear(),
+            Call::InsertRelative(parent, relative) => {
+                table.insert_relative(*parent, *relative).ok();
+            }
+            Call::SetOverlapPolicy(policy) => table.set_overlap_policy(*policy),
+            Call::SetBackground(key) => {
+                table.set_background(*key).ok();
+            }
+            Call::Circulate(parent, direction) => {
+                table.circulate(*parent, *direction);
+            }
+            Call::Minimize(key) => {
+                table.minimize(*key);
+            }
+            Call::Restore(key) => {
+                table.restore(*key);
+            }
+            Call::SetFullscreen(key, fullscreen) => {
+                table.set_fullscreen(*key, *fullscreen).ok();
+            }
+            Call::Maximize(key, axis) => {
+                table.maximize(*key, *axis).ok();
+            }
+            Call::Unmaximize(key) => {
+                table.unmaximize(*key);
+            }
+            Call::RequestConfigure(key, rect) => {
+                table.request_configure(*key, *rect);
+            }
+            Call::ModifyConfigure(serial, rect) => {
+                table.modify_configure(*serial, *rect);
+            }
+            Call::DenyConfigure(serial) => {
+                table.deny_configure(*serial);
+            }
+            Call::ApplyConfigure(key, serial) => {
+                table.apply_configure(*key, *serial).ok();
+            }
+            Call::SetSizeHints(key, hints) => table.set_size_hints(*key, *hints),
+            Call::SetAnchors(key, anchors) => table.set_anchors(*key, *anchors),
+            Call::SetRelative(key, relative) => table.set_relative(*key, *relative),
+            Call::SetVisualExtents(key, extents) => table.set_visual_extents(*key, *extents),
+            Call::SetInputMargin(key, margin) => table.set_input_margin(*key, *margin),
+            Call::SetInputTransparent(key, transparent) => {
+                table.set_input_transparent(*key, *transparent)
+            }
+            Call::SetEventMask(key, mask) => table.set_event_mask(*key, *mask),
+            Call::SetSaveUnder(key, save_under) => table.set_save_under(*key, *save_under),
+            Call::SetModal(key, scope) => table.set_modal(*key, *scope),
+            Call::SetTransientFor(key, owner) => {
+                table.set_transient_for(*key, *owner);
+            }
+            Call::MapGroup(key) => table.map_group(*key),
+            Call::UnmapGroup(key) => table.unmap_group(*key),
+            Call::MinimizeGroup(key) => table.minimize_group(*key),
+            Call::RestoreGroup(key) => table.restore_group(*key),
+            Call::SetWorkspaces(key, workspaces) => table.set_workspaces(*key, workspaces),
+            Call::SetActiveWorkspace(workspace) => {
+                table.set_active_workspace(*workspace);
+            }
+        }
+    }
+}
+
+/// A window table that records every change made to it.
+///
+/// The table starts out empty. Replaying the recorded calls into a new table with
+/// [`WindowTable::replay`] builds the same table, with the same keys, so a log taken from a
+/// misbehaving program can be used to reproduce the problem.
+///
+/// Every call that changes which windows exist, where they are, how they are stacked, whether
+/// they are mapped, or how later calls treat them can be made through the recorder. A few
+/// calls are left out:
+///
+/// - [`WindowTable::retain`], [`WindowTable::transaction`], observers and damage sinks take
+///   closures, borrows or trait objects that can't be stored in a [`Call`]. Make the
+///   individual calls instead of using a transaction.
+/// - Moving subtrees between tables involves more than one table, so it can't be replayed
+///   into a single one.
+/// - Properties, names and the damage, exposure and event queues don't affect the windows,
+///   so they don't need to be replayed to reproduce the table.
+#[derive(Debug, Default)]
+pub struct Recorder<T: Coordinate = i32> {
+    /// The table being changed.
+    table: WindowTable<T>,
+
+    /// The calls made so far, oldest first.
+    calls: Vec<Call<T>>,
+}
+
+impl<T: Coordinate> Recorder<T> {
+    /// Start recording the changes made to a new, empty table.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the table.
+    pub fn table(&self) -> &WindowTable<T> {
+        &self.table
+    }
+
+    /// Returns the calls made so far, oldest first.
+    pub fn calls(&self) -> &[Call<T>] {
+        &self.calls
+    }
+
+    /// Stop recording changes, returning the table and the calls made to it.
+    pub fn into_inner(self) -> (WindowTable<T>, Vec<Call<T>>) {
+        (self.table, self.calls)
+    }
+
+    /// See [`WindowTable::insert`].
+    pub fn insert(&mut self, rect: Rectangle<T>) -> Result<WindowKey, InsertError> {
+        let key = self.table.insert(rect);
+        self.calls.push(Call::Insert(rect));
+        key
+    }
+
+    /// See [`WindowTable::insert_above`].
+    pub fn insert_above(
+        &mut self,
+        rect: Rectangle<T>,
+        sibling: WindowKey,
+    ) -> Result<WindowKey, InsertError> {
+        let key = self.table.insert_above(rect, sibling);
+        self.calls.push(Call::InsertAbove(rect, sibling));
+        key
+    }
+
+    /// See [`WindowTable::insert_below`].
+    pub fn insert_below(
+        &mut self,
+        rect: Rectangle<T>,
+        sibling: WindowKey,
+    ) -> Result<WindowKey, InsertError> {
+        let key = self.table.insert_below(rect, sibling);
+        self.calls.push(Call::InsertBelow(rect, sibling));
+        key
+    }
+
+    /// See [`WindowTable::remove`].
+    pub fn remove(&mut self, key: WindowKey) -> Option<Rectangle<T>> {
+        let rect = self.table.remove(key);
+        self.calls.push(Call::Remove(key));
+        rect
+    }
+
+    /// See [`WindowTable::resize_window`].
+    pub fn resize_window(
+        &mut self,
+        key: WindowKey,
+        rect: Rectangle<T>,
+    ) -> Result<Rectangle<T>, ConfigureError> {
+        let result = self.table.resize_window(key, rect);
+        self.calls.push(Call::ResizeWindow(key, rect));
+        result
+    }
+
+    /// See [`WindowTable::move_window`].
+    pub fn move_window(
+        &mut self,
+        key: WindowKey,
+        position: impl Into<Point<T>>,
+    ) -> Result<Rectangle<T>, ConfigureError> {
+        let position = position.into();
+        let result = self.table.move_window(key, position);
+        self.calls.push(Call::MoveWindow(key, position));
+        result
+    }
+
+    /// See [`WindowTable::resize_root`].
+    pub fn resize_root(&mut self, rect: Rectangle<T>) -> Result<Vec<WindowKey>, ConfigureError> {
+        let result = self.table.resize_root(rect);
+        self.calls.push(Call::ResizeRoot(rect));
+        result
+    }
+
+    /// See [`WindowTable::raise`].
+    pub fn raise(&mut self, key: WindowKey) {
+        self.table.raise(key);
+        self.calls.push(Call::Raise(key));
+    }
+
+    /// See [`WindowTable::lower`].
+    pub fn lower(&mut self, key: WindowKey) {
+        self.table.lower(key);
+        self.calls.push(Call::Lower(key));
+    }
+
+    /// See [`WindowTable::map`].
+    pub fn map(&mut self, key: WindowKey) {
+        self.table.map(key);
+        self.calls.push(Call::Map(key));
+    }
+
+    /// See [`WindowTable::unmap`].
+    pub fn unmap(&mut self, key: WindowKey) {
+        self.table.unmap(key);
+        self.calls.push(Call::Unmap(key));
+    }
+
+    /// See [`WindowTable::set_layer`].
+    pub fn set_layer(&mut self, key: WindowKey, layer: Layer) {
+        self.table.set_layer(key, layer);
+        self.calls.push(Call::SetLayer(key, layer));
+    }
+
+    /// See [`WindowTable::set_z_index`].
+    pub fn set_z_index(&mut self, key: WindowKey, z_index: i32) {
+        self.table.set_z_index(key, z_index);
+        self.calls.push(Call::SetZIndex(key, z_index));
+    }
+
+    /// See [`WindowTable::set_gravity`].
+    pub fn set_gravity(&mut self, key: WindowKey, gravity: Gravity) {
+        self.table.set_gravity(key, gravity);
+        self.calls.push(Call::SetGravity(key, gravity));
+    }
+
+    /// See [`WindowTable::set_border_width`].
+    pub fn set_border_width(&mut self, key: WindowKey, width: u16) {
+        self.table.set_border_width(key, width);
+        self.calls.push(Call::SetBorderWidth(key, width));
+    }
+
+    /// See [`WindowTable::set_opacity`].
+    pub fn set_opacity(&mut self, key: WindowKey, opacity: u8) {
+        self.table.set_opacity(key, opacity);
+        self.calls.push(Call::SetOpacity(key, opacity));
+    }
+
+    /// See [`WindowTable::set_shape`].
+    pub fn set_shape(&mut self, key: WindowKey, shape: Option<Region<T>>) {
+        self.table.set_shape(key, shape.clone());
+        self.calls.push(Call::SetShape(key, shape));
+    }
+
+    /// See [`WindowTable::set_input_region`].
+    pub fn set_input_region(&mut self, key: WindowKey, region: Region<T>) {
+        self.table.set_input_region(key, region.clone());
+        self.calls.push(Call::SetInputRegion(key, region));
+    }
+
+    /// See [`WindowTable::reset_input_region`].
+    pub fn reset_input_region(&mut self, key: WindowKey) {
+        self.table.reset_input_region(key);
+        self.calls.push(Call::ResetInputRegion(key));
+    }
+
+    /// See [`WindowTable::clear`].
+    pub fn clear(&mut self) {
+        self.table.clear();
+        self.calls.push(Call::Clear);
+    }
+
+    /// See [`WindowTable::insert_relative`].
+    pub fn insert_relative(
+        &mut self,
+        parent: WindowKey,
+        relative: RelativeRect,
+    ) -> Result<WindowKey, InsertError> {
+        let result = self.table.insert_relative(parent, relative);
+        self.calls.push(Call::InsertRelative(parent, relative));
+        result
+    }
+
+    /// See [`WindowTable::set_overlap_policy`].
+    pub fn set_overlap_policy(&mut self, policy: OverlapPolicy) {
+        self.table.set_overlap_policy(policy);
+        self.calls.push(Call::SetOverlapPolicy(policy));
+    }
+
+    /// See [`WindowTable::set_background`].
+    pub fn set_background(&mut self, key: Option<WindowKey>) -> Result<(), ConfigureError> {
+        let result = self.table.set_background(key);
+        self.calls.push(Call::SetBackground(key));
+        result
+    }
+
+    /// See [`WindowTable::circulate`].
+    pub fn circulate(
+        &mut self,
+        parent: WindowKey,
+        direction: CirculateDirection,
+    ) -> Option<WindowKey> {
+        let result = self.table.circulate(parent, direction);
+        self.calls.push(Call::Circulate(parent, direction));
+        result
+    }
+
+    /// See [`WindowTable::minimize`].
+    pub fn minimize(&mut self, key: WindowKey) -> bool {
+        let result = self.table.minimize(key);
+        self.calls.push(Call::Minimize(key));
+        result
+    }
+
+    /// See [`WindowTable::restore`].
+    pub fn restore(&mut self, key: WindowKey) -> bool {
+        let result = self.table.restore(key);
+        self.calls.push(Call::Restore(key));
+        result
+    }
+
+    /// See [`WindowTable::set_fullscreen`].
+    pub fn set_fullscreen(
+        &mut self,
+        key: WindowKey,
+        fullscreen: bool,
+    ) -> Result<(), ConfigureError> {
+        let result = self.table.set_fullscreen(key, fullscreen);
+        self.calls.push(Call::SetFullscreen(key, fullscreen));
+        result
+    }
+
+    /// See [`WindowTable::maximize`].
+    pub fn maximize(&mut self, key: WindowKey, axis: MaximizeAxis) -> Result<(), ConfigureError> {
+        let result = self.table.maximize(key, axis);
+        self.calls.push(Call::Maximize(key, axis));
+        result
+    }
+
+    /// See [`WindowTable::unmaximize`].
+    pub fn unmaximize(&mut self, key: WindowKey) -> bool {
+        let result = self.table.unmaximize(key);
+        self.calls.push(Call::Unmaximize(key));
+        result
+    }
+
+    /// See [`WindowTable::request_configure`].
+    pub fn request_configure(&mut self, key: WindowKey, rect: Rectangle<T>) -> u32 {
+        let result = self.table.request_configure(key, rect);
+        self.calls.push(Call::RequestConfigure(key, rect));
+        result
+    }
+
+    /// See [`WindowTable::modify_configure`].
+    pub fn modify_configure(&mut self, serial: u32, rect: Rectangle<T>) -> bool {
+        let result = self.table.modify_configure(serial, rect);
+        self.calls.push(Call::ModifyConfigure(serial, rect));
+        result
+    }
+
+    /// See [`WindowTable::deny_configure`].
+    pub fn deny_configure(&mut self, serial: u32) -> Option<ConfigureRequest<T>> {
+        let result = self.table.deny_configure(serial);
+        self.calls.push(Call::DenyConfigure(serial));
+        result
+    }
+
+    /// See [`WindowTable::apply_configure`].
+    pub fn apply_configure(
+        &mut self,
+        key: WindowKey,
+        serial: u32,
+    ) -> Result<Rectangle<T>, ConfigureError> {
+        let result = self.table.apply_configure(key, serial);
+        self.calls.push(Call::ApplyConfigure(key, serial));
+        result
+    }
+
+    /// See [`WindowTable::set_size_hints`].
+    pub fn set_size_hints(&mut self, key: WindowKey, hints: SizeHints<T>) {
+        self.table.set_size_hints(key, hints);
+        self.calls.push(Call::SetSizeHints(key, hints));
+    }
+
+    /// See [`WindowTable::set_anchors`].
+    pub fn set_anchors(&mut self, key: WindowKey, anchors: Option<Anchors<T>>) {
+        self.table.set_anchors(key, anchors);
+        self.calls.push(Call::SetAnchors(key, anchors));
+    }
+
+    /// See [`WindowTable::set_relative`].
+    pub fn set_relative(&mut self, key: WindowKey, relative: Option<RelativeRect>) {
+        self.table.set_relative(key, relative);
+        self.calls.push(Call::SetRelative(key, relative));
+    }
+
+    /// See [`WindowTable::set_visual_extents`].
+    pub fn set_visual_extents(&mut self, key: WindowKey, extents: Option<Extents<T>>) {
+        self.table.set_visual_extents(key, extents);
+        self.calls.push(Call::SetVisualExtents(key, extents));
+    }
+
+    /// See [`WindowTable::set_input_margin`].
+    pub fn set_input_margin(&mut self, key: WindowKey, margin: Option<Extents<T>>) {
+        self.table.set_input_margin(key, margin);
+        self.calls.push(Call::SetInputMargin(key, margin));
+    }
+
+    /// See [`WindowTable::set_input_transparent`].
+    pub fn set_input_transparent(&mut self, key: WindowKey, transparent: bool) {
+        self.table.set_input_transparent(key, transparent);
+        self.calls.push(Call::SetInputTransparent(key, transparent));
+    }
+
+    /// See [`WindowTable::set_event_mask`].
+    pub fn set_event_mask(&mut self, key: WindowKey, mask: EventMask) {
+        self.table.set_event_mask(key, mask);
+        self.calls.push(Call::SetEventMask(key, mask));
+    }
+
+    /// See [`WindowTable::set_save_under`].
+    pub fn set_save_under(&mut self, key: WindowKey, save_under: bool) {
+        self.table.set_save_under(key, save_under);
+        self.calls.push(Call::SetSaveUnder(key, save_under));
+    }
+
+    /// See [`WindowTable::set_modal`].
+    pub fn set_modal(&mut self, key: WindowKey, scope: ModalScope) {
+        self.table.set_modal(key, scope);
+        self.calls.push(Call::SetModal(key, scope));
+    }
+
+    /// See [`WindowTable::set_transient_for`].
+    pub fn set_transient_for(&mut self, key: WindowKey, owner: Option<WindowKey>) -> bool {
+        let result = self.table.set_transient_for(key, owner);
+        self.calls.push(Call::SetTransientFor(key, owner));
+        result
+    }
+
+    /// See [`WindowTable::map_group`].
+    pub fn map_group(&mut self, key: WindowKey) {
+        self.table.map_group(key);
+        self.calls.push(Call::MapGroup(key));
+    }
+
+    /// See [`WindowTable::unmap_group`].
+    pub fn unmap_group(&mut self, key: WindowKey) {
+        self.table.unmap_group(key);
+        self.calls.push(Call::UnmapGroup(key));
+    }
+
+    /// See [`WindowTable::minimize_group`].
+    pub fn minimize_group(&mut self, key: WindowKey) {
+        self.table.minimize_group(key);
+        self.calls.push(Call::MinimizeGroup(key));
+    }
+
+    /// See [`WindowTable::restore_group`].
+    pub fn restore_group(&mut self, key: WindowKey) {
+        self.table.restore_group(key);
+        self.calls.push(Call::RestoreGroup(key));
+    }
+
+    /// See [`WindowTable::set_workspaces`].
+    pub fn set_workspaces(&mut self, key: WindowKey, workspaces: &[WorkspaceId]) {
+        self.table.set_workspaces(key, workspaces);
+        self.calls
+            .push(Call::SetWorkspaces(key, workspaces.to_vec()));
+    }
+
+    /// See [`WindowTable::set_active_workspace`].
+    pub fn set_active_workspace(&mut self, workspace: WorkspaceId) -> Region<T> {
+        let result = self.table.set_active_workspace(workspace);
+        self.calls.push(Call::SetActiveWorkspace(workspace));
+        result
+    }
+}
+
+impl<T: Coordinate, const N: usize, const R: usize> WindowTable<T, N, R> {
+    /// Build a new table by making a sequence of recorded calls to it.
+    ///
+    /// The windows are given the same keys that they had in the table the calls were recorded
+    /// from.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a call names a window that does not exist, which can't happen for calls
+    /// recorded by a [`Recorder`].
+    pub fn replay<'a>(calls: impl IntoIterator<Item = &'a Call<T>>) -> Self
+    where
+        T: 'a,
+    {
+        let mut table = Self::default();
+        for call in calls {
+            call.apply(&mut table);
+        }
+        table
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Recorder;
+    use crate::{Layer, ModalScope, OverlapPolicy, Rectangle, RelativeRect, WindowTable};
+    use alloc::vec::Vec;
+
+    #[test]
+    fn replay() {
+        let mut recorder = Recorder::new();
+
+        let root = recorder.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let a = recorder.insert(Rectangle::new(10, 10, 50, 50)).unwrap();
+        let b = recorder.insert(Rectangle::new(30, 30, 70, 70)).unwrap();
+        recorder.remove(a);
+        let c = recorder
+            .insert_below(Rectangle::new(20, 20, 40, 40), b)
+            .unwrap();
+        recorder.move_window(b, (40, 40)).unwrap();
+        recorder
+            .resize_window(c, Rectangle::new(0, 0, 0, 0))
+            .unwrap_err();
+        recorder.set_layer(c, Layer::Dock);
+        recorder.unmap(b);
+        recorder.raise(b);
+        recorder.set_overlap_policy(OverlapPolicy::Reject);
+        let d = recorder
+            .insert_relative(root, RelativeRect::new(0.0, 0.5, 0.3, 1.0))
+            .unwrap();
+        recorder.set_transient_for(d, Some(c));
+        recorder.set_modal(d, ModalScope::Parent);
+        let serial = recorder.request_configure(c, Rectangle::new(20, 20, 45, 45));
+        recorder.apply_configure(c, serial).unwrap();
+        assert_eq!(recorder.calls().len(), 16);
+
+        let replayed = WindowTable::replay(recorder.calls());
+        let (table, _) = recorder.into_inner();
+
+        let windows = |table: &WindowTable| table.iter().collect::<Vec<_>>();
+        let order =
+            |table: &WindowTable| table.paint_order().map(|(key, _)| key).collect::<Vec<_>>();
+        assert_eq!(windows(&replayed), windows(&table));
+        assert_eq!(order(&replayed), order(&table));
+        assert_eq!(order(&replayed), [root, d, c]);
+        assert!(!replayed.contains_key(a));
+        assert!(!replayed.is_mapped(b));
+        assert_eq!(replayed.layer(c), Layer::Dock);
+        assert_eq!(replayed.overlap_policy(), OverlapPolicy::Reject);
+        assert_eq!(replayed.rect(c), Rectangle::new(20, 20, 45, 45));
+        assert_eq!(replayed.modal(d), ModalScope::Parent);
+        assert_eq!(replayed.transient_for(d), Some(c));
+    }
+}
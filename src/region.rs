@@ -0,0 +1,498 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later OR MPL-2.0
+// This file is a part of `windowless`.
+//
+// `windowless` is free software: you can redistribute it and/or modify it under the terms of
+// either:
+//
+// * GNU Lesser General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+// * Mozilla Public License as published by the Mozilla Foundation, version 2.
+//
+// `windowless` is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+// See the GNU Lesser General Public License or the Mozilla Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License and the Mozilla
+// Public License along with `windowless`. If not, see <https://www.gnu.org/licenses/> or
+// <https://www.mozilla.org/en-US/MPL/2.0/>.
+
+//! Sets of rectangles.
+
+use crate::{Coordinate, Point, Rectangle, WindowKey};
+use alloc::vec::Vec;
+use core::cmp;
+use tinyvec::TinyVec;
+
+/// A region, made up of non-overlapping rectangles.
+///
+/// Up to `R` rectangles are stored inline before the region allocates.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Region<T: Coordinate = i32, const R: usize = 4> {
+    /// The rectangles making up the region.
+    ///
+    /// None of these rectangles overlap, and none of them are empty.
+    rects: TinyVec<[Rectangle<T>; R]>,
+}
+
+impl<T: Coordinate> Region<T> {
+    /// Creates a new, empty region.
+    ///
+    /// Use [`Default::default`] to create a region with a different inline capacity.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<T: Coordinate, const R: usize> Region<T, R> {
+    /// Returns the rectangles making up this region.
+    pub fn rects(&self) -> &[Rectangle<T>] {
+        &self.rects
+    }
+
+    /// Copy this region into one that stores up to `S` rectangles inline.
+    pub fn to_capacity<const S: usize>(&self) -> Region<T, S> {
+        Region {
+            rects: self.rects.iter().copied().collect(),
+        }
+    }
+
+    /// Tell if this region is empty.
+    pub fn is_empty(&self) -> bool {
+        self.rects.is_empty()
+    }
+
+    /// Returns the total area covered by this region.
+    ///
+    /// The area saturates if it doesn't fit in the coordinate type.
+    pub fn area(&self) -> T {
+        self.rects.iter().fold(T::ZERO, |area, rect| {
+            area.saturating_add(rect.saturating_area())
+        })
+    }
+
+    /// Returns the smallest rectangle containing the entire region.
+    pub fn bounds(&self) -> Option<Rectangle<T>> {
+        let mut rects = self.rects.iter();
+        let first = *rects.next()?;
+
+        Some(rects.fold(first, |bounds, rect| bounds.union(*rect)))
+    }
+
+    /// Tell if this region contains the given point.
+    pub fn contains(&self, point: impl Into<Point<T>>) -> bool {
+        let point = point.into();
+        self.rects.iter().any(|rect| rect.contains(point))
+    }
+
+    /// Add a rectangle to this region.
+    pub fn union_rect(&mut self, rect: Rectangle<T>) {
+        if rect.is_empty() {
+            return;
+        }
+
+        // Only add the parts of the rectangle that aren't already covered.
+        let mut pieces = tinyvec::tiny_vec![[Rectangle<T>; 4] => rect];
+        for existing in &self.rects {
+            pieces = pieces
+                .into_iter()
+                .flat_map(|piece| match piece.split(*existing) {
+                    Some((_, remainder)) => TinyVec::Inline(remainder),
+                    None => tinyvec::tiny_vec![[Rectangle<T>; 4] => piece],
+                })
+                .collect();
+        }
+
+        self.rects.extend(pieces);
+    }
+
+    /// Add another region to this region.
+    pub fn union(&mut self, other: &Region<T, R>) {
+        for rect in &other.rects {
+            self.union_rect(*rect);
+        }
+    }
+
+    /// Remove a rectangle from this region.
+    ///
+    /// See [`subtract`](Self::subtract).
+    pub fn subtract_rect(&mut self, rect: Rectangle<T>) {
+        if rect.is_empty() || !self.rects.iter().any(|existing| existing.intersects(rect)) {
+            return;
+        }
+
+        self.rects = bands(&self.rects, &[rect], |inside, removed| inside && !removed);
+    }
+
+    /// Remove another region from this region.
+    ///
+    /// If anything is removed, the result is in y-x banded form, like the output of
+    /// [`simplify`](Self::simplify), so its rectangles never overlap and are as few as a band
+    /// decomposition allows.
+    pub fn subtract(&mut self, other: &Region<T, R>) {
+        let overlaps = other
+            .rects
+            .iter()
+            .any(|rect| self.rects.iter().any(|existing| existing.intersects(*rect)));
+        if !overlaps {
+            return;
+        }
+
+        self.rects = bands(&self.rects, &other.rects, |inside, removed| {
+            inside && !removed
+        });
+    }
+
+    /// Clip this region to a rectangle.
+    pub fn intersect_rect(&mut self, rect: Rectangle<T>) {
+        self.rects = self
+            .rects
+            .iter()
+            .filter_map(|existing| existing.intersection(rect))
+            .collect();
+    }
+
+    /// Clip this region to another region.
+    pub fn intersect(&mut self, other: &Region<T, R>) {
+        let rects = core::mem::take(&mut self.rects);
+        for rect in rects {
+            for clip in &other.rects {
+                if let Some(intersection) = rect.intersection(*clip) {
+                    self.rects.push(intersection);
+                }
+            }
+        }
+    }
+
+    /// Move this region by the given offset.
+    pub fn translate(&mut self, dx: T, dy: T) {
+        for rect in &mut self.rects {
+            *rect = rect.translate(dx, dy);
+        }
+    }
+
+    /// Move this region into the coordinate space of a window whose top-left corner is at
+    /// `(x, y)`.
+    pub(crate) fn relative_to(&mut self, x: T, y: T) {
+        for rect in &mut self.rects {
+            *rect = Rectangle {
+                left: rect.left.saturating_sub(x),
+                top: rect.top.saturating_sub(y),
+                right: rect.right.saturating_sub(x),
+                bottom: rect.bottom.saturating_sub(y),
+            };
+        }
+    }
+
+    /// Rebuild this region out of as few rectangles as a band decomposition allows.
+    ///
+    /// The region is cut into horizontal bands at every top and bottom edge. Touching
+    /// rectangles within a band are merged, and bands with the same rectangles that touch are
+    /// merged as well. The area covered by the region doesn't change.
+    pub fn simplify(&mut self) {
+        self.rects = bands(&self.rects, &[], |inside, _| inside);
+    }
+
+    /// Simplify this region, then merge rectangles until there are at most `max_rects` of them.
+    ///
+    /// Rectangles are merged into their bounding box, choosing the pair that adds the least
+    /// area each time, so the region may grow to cover more than it did. This bounds the work
+    /// done by renderers that are handed the rectangles of damaged regions.
+    pub fn simplify_to(&mut self, max_rects: usize) {
+        self.simplify();
+
+        let max_rects = cmp::max(max_rects, 1);
+        while self.rects.len() > max_rects {
+            // Find the pair of rectangles that wastes the least area when merged.
+            let area = |rect: Rectangle<T>| rect.saturating_area().to_f64();
+            let mut best = (f64::INFINITY, 0, 1);
+            for i in 0..self.rects.len() {
+                for j in i + 1..self.rects.len() {
+                    let (a, b) = (self.rects[i], self.rects[j]);
+                    let waste = area(a.union(b)) - area(a) - area(b);
+                    if waste < best.0 {
+                        best = (waste, i, j);
+                    }
+                }
+            }
+
+            let (_, i, j) = best;
+            let second = self.rects.swap_remove(j);
+            let mut merged = self.rects.swap_remove(i).union(second);
+
+            // Absorb every rectangle that the merged one now overlaps, so that none overlap.
+            while let Some(index) = self.rects.iter().position(|rect| rect.intersects(merged)) {
+                merged = merged.union(self.rects.swap_remove(index));
+            }
+            self.rects.push(merged);
+        }
+    }
+
+    /// Split this region into horizontal runs of pixels, one row at a time.
+    ///
+    /// Each span is returned as `(y, x_start, x_end)`, with `x_end` exclusive. Spans are sorted
+    /// by row, then from left to right, and spans that touch are merged. Fractional edges are
+    /// rounded outwards to whole pixels.
+    pub fn spans(&self) -> Vec<(i32, i32, i32)> {
+        let mut spans = Vec::new();
+        for rect in &self.rects {
+            let (left, right) = (rect.left.floor_i32(), rect.right.ceil_i32());
+            for y in rect.top.floor_i32()..rect.bottom.ceil_i32() {
+                spans.push((y, left, right));
+            }
+        }
+
+        spans.sort_unstable();
+        spans.dedup_by(|next, span| {
+            if next.0 == span.0 && next.1 <= span.2 {
+                span.2 = cmp::max(span.2, next.2);
+                true
+            } else {
+                false
+            }
+        });
+        spans
+    }
+}
+
+/// Combine two sets of non-overlapping rectangles, one horizontal band at a time.
+///
+/// `keep` is told whether each part of a band is inside of `a` and inside of `b`, and returns
+/// whether it is part of the result. The result is in y-x banded form: bands are sorted from
+/// top to bottom, rectangles within a band are sorted from left to right and don't touch, and
+/// touching bands with the same rectangles are merged.
+fn bands<T: Coordinate, const R: usize>(
+    a: &[Rectangle<T>],
+    b: &[Rectangle<T>],
+    keep: impl Fn(bool, bool) -> bool,
+) -> TinyVec<[Rectangle<T>; R]> {
+    let mut ys = a
+        .iter()
+        .chain(b)
+        .flat_map(|rect| [rect.top, rect.bottom])
+        .collect::<Vec<_>>();
+    sort_edges(&mut ys);
+
+    let mut result: TinyVec<[Rectangle<T>; R]> = TinyVec::new();
+    let mut previous = (Vec::new(), 0, None);
+    for band in ys.windows(2) {
+        let (top, bottom) = (band[0], band[1]);
+        let crossing = |rects: &[Rectangle<T>]| {
+            rects
+                .iter()
+                .filter(|rect| rect.top <= top && rect.bottom >= bottom)
+                .copied()
+                .collect::<Vec<_>>()
+        };
+        let (in_a, in_b) = (crossing(a), crossing(b));
+
+        let mut xs = in_a
+            .iter()
+            .chain(&in_b)
+            .flat_map(|rect| [rect.left, rect.right])
+            .collect::<Vec<_>>();
+        sort_edges(&mut xs);
+
+        // Find the runs of the band that are kept, merging the ones that touch.
+        let mut runs: Vec<(T, T)> = Vec::new();
+        for run in xs.windows(2) {
+            let (left, right) = (run[0], run[1]);
+            let inside = |rects: &[Rectangle<T>]| {
+                rects
+                    .iter()
+                    .any(|rect| rect.left <= left && rect.right >= right)
+            };
+            if !keep(inside(&in_a), inside(&in_b)) {
+                continue;
+            }
+
+            match runs.last_mut() {
+                Some(last) if last.1 == left => last.1 = right,
+                _ => runs.push((left, right)),
+            }
+        }
+
+        // Extend the previous band downwards if it has the same runs.
+        let (previous_runs, start, previous_bottom) = &mut previous;
+        if !runs.is_empty() && *previous_bottom == Some(top) && runs == *previous_runs {
+            for rect in &mut result[*start..] {
+                rect.bottom = bottom;
+            }
+        } else {
+            *start = result.len();
+            result.extend(runs.iter().map(|&(left, right)| Rectangle {
+                left,
+                top,
+                right,
+                bottom,
+            }));
+            *previous_runs = runs;
+        }
+        *previous_bottom = Some(bottom);
+    }
+
+    result
+}
+
+/// Sort a list of edges and remove the duplicates.
+fn sort_edges<T: Coordinate>(edges: &mut Vec<T>) {
+    edges.sort_unstable_by(|a, b| a.partial_cmp(b).unwrap_or(cmp::Ordering::Equal));
+    edges.dedup();
+}
+
+/// A horizontal run of pixels belonging to a window.
+///
+/// Returned by [`WindowTable::visible_spans`](crate::WindowTable::visible_spans) and
+/// [`WindowTable::damage_spans`](crate::WindowTable::damage_spans).
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Span {
+    /// The row of the span.
+    pub y: i32,
+
+    /// The first column of the span.
+    pub x_start: i32,
+
+    /// The column after the last column of the span.
+    pub x_end: i32,
+
+    /// The window shown in the span.
+    pub window: WindowKey,
+}
+
+impl<T: Coordinate> From<Rectangle<T>> for Region<T> {
+    fn from(rect: Rectangle<T>) -> Self {
+        let mut region = Region::default();
+        region.union_rect(rect);
+        region
+    }
+}
+
+impl<T: Coordinate, const R: usize> FromIterator<Rectangle<T>> for Region<T, R> {
+    fn from_iter<I: IntoIterator<Item = Rectangle<T>>>(iter: I) -> Self {
+        let mut region = Region::default();
+        for rect in iter {
+            region.union_rect(rect);
+        }
+        region
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Region;
+    use crate::Rectangle;
+
+    #[test]
+    fn union_overlapping() {
+        let mut region = Region::from(Rectangle::new(0, 0, 10, 10));
+        region.union_rect(Rectangle::new(5, 5, 15, 15));
+
+        assert_eq!(region.area(), 175);
+        assert_eq!(region.bounds(), Some(Rectangle::new(0, 0, 15, 15)));
+        assert!(region.contains((12, 12)));
+        assert!(!region.contains((12, 2)));
+    }
+
+    #[test]
+    fn subtract_hole() {
+        let mut region = Region::from(Rectangle::new(0, 0, 30, 30));
+        region.subtract_rect(Rectangle::new(10, 10, 20, 20));
+
+        assert_eq!(region.area(), 800);
+        assert!(!region.contains((15, 15)));
+        assert!(region.contains((5, 15)));
+    }
+
+    #[test]
+    fn subtract_banded() {
+        let mut region = Region::from(Rectangle::new(0, 0, 30, 30));
+        let other: Region = [
+            Rectangle::new(10, 10, 20, 20),
+            Rectangle::new(10, 20, 20, 40),
+        ]
+        .into_iter()
+        .collect();
+        region.subtract(&other);
+
+        assert_eq!(
+            region.rects(),
+            [
+                Rectangle::new(0, 0, 30, 10),
+                Rectangle::new(0, 10, 10, 30),
+                Rectangle::new(20, 10, 30, 30)
+            ]
+        );
+    }
+
+    #[test]
+    fn intersect() {
+        let mut region: Region = [Rectangle::new(0, 0, 10, 10), Rectangle::new(20, 0, 30, 10)]
+            .into_iter()
+            .collect();
+        region.intersect_rect(Rectangle::new(5, 0, 25, 5));
+
+        assert_eq!(region.area(), 50);
+    }
+
+    #[test]
+    fn spans() {
+        let mut region = Region::new();
+        region.union_rect(Rectangle::new(0, 0, 10, 2));
+        region.union_rect(Rectangle::new(10, 1, 20, 3));
+        region.union_rect(Rectangle::new(30, 1, 40, 2));
+
+        assert_eq!(
+            region.spans(),
+            [(0, 0, 10), (1, 0, 20), (1, 30, 40), (2, 10, 20)]
+        );
+
+        let region = Region::from(Rectangle::new(0.5, 0.25, 1.5, 1.0));
+        assert_eq!(region.spans(), [(0, 0, 2)]);
+    }
+
+    #[test]
+    fn simplify() {
+        // A 2x2 grid of squares is one rectangle.
+        let mut region: Region = [
+            Rectangle::new(0, 0, 10, 10),
+            Rectangle::new(10, 0, 20, 10),
+            Rectangle::new(0, 10, 10, 20),
+            Rectangle::new(10, 10, 20, 20),
+        ]
+        .into_iter()
+        .collect();
+        region.simplify();
+        assert_eq!(region.rects(), [Rectangle::new(0, 0, 20, 20)]);
+
+        // An L shape needs two.
+        let mut region: Region = [
+            Rectangle::new(0, 0, 10, 10),
+            Rectangle::new(0, 10, 10, 20),
+            Rectangle::new(10, 10, 20, 20),
+        ]
+        .into_iter()
+        .collect();
+        region.simplify();
+        assert_eq!(
+            region.rects(),
+            [Rectangle::new(0, 0, 10, 10), Rectangle::new(0, 10, 20, 20)]
+        );
+        assert_eq!(region.area(), 300);
+    }
+
+    #[test]
+    fn simplify_to() {
+        let mut region: Region = (0..10)
+            .map(|i| Rectangle::new(i * 20, 0, i * 20 + 10, 10))
+            .chain(Some(Rectangle::new(0, 100, 10, 110)))
+            .collect();
+        region.simplify_to(2);
+
+        assert_eq!(
+            region.rects(),
+            [
+                Rectangle::new(0, 100, 10, 110),
+                Rectangle::new(0, 0, 190, 10)
+            ]
+        );
+    }
+}
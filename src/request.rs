@@ -0,0 +1,178 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later OR MPL-2.0
+// This file is a part of `windowless`.
+//
+// `windowless` is free software: you can redistribute it and/or modify it under the terms of
+// either:
+//
+// * GNU Lesser General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+// * Mozilla Public License as published by the Mozilla Foundation, version 2.
+//
+// `windowless` is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+// See the GNU Lesser General Public License or the Mozilla Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License and the Mozilla
+// Public License along with `windowless`. If not, see <https://www.gnu.org/licenses/> or
+// <https://www.mozilla.org/en-US/MPL/2.0/>.
+
+//! Configure requests, which are held until the window manager applies them.
+
+use crate::{ConfigureError, Coordinate, Rectangle, WindowKey, WindowTable};
+
+/// A request to change the rectangle of a window, waiting to be applied.
+///
+/// Made with [`WindowTable::request_configure`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct ConfigureRequest<T = i32> {
+    /// The serial identifying the request.
+    pub serial: u32,
+
+    /// The window to change.
+    pub window: WindowKey,
+
+    /// The rectangle the window asked for, or the one the window manager replaced it with.
+    pub rect: Rectangle<T>,
+}
+
+impl<T: Coordinate, const N: usize, const R: usize> WindowTable<T, N, R> {
+    /// Ask for a window to be given a new rectangle, without changing it yet.
+    ///
+    /// The request is held until it is [applied](Self::apply_configure) or
+    /// [denied](Self::deny_configure), giving a window manager the chance to look at it with
+    /// [`configure_requests`](Self::configure_requests) and
+    /// [change the rectangle](Self::modify_configure) first. Returns the serial of the request.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn request_configure(&mut self, key: WindowKey, rect: Rectangle<T>) -> u32 {
+        assert!(self.contains_key(key), "window does not exist");
+
+        let serial = self.next_serial;
+        self.next_serial = self.next_serial.wrapping_add(1);
+        self.configure_requests.push(ConfigureRequest {
+            serial,
+            window: key,
+            rect,
+        });
+        serial
+    }
+
+    /// Returns the configure requests waiting to be applied, oldest first.
+    pub fn configure_requests(&self) -> &[ConfigureRequest<T>] {
+        &self.configure_requests
+    }
+
+    /// Replace the rectangle of a pending configure request.
+    ///
+    /// Returns `false` if no request with this serial is pending.
+    pub fn modify_configure(&mut self, serial: u32, rect: Rectangle<T>) -> bool {
+        match self
+            .configure_requests
+            .iter_mut()
+            .find(|request| request.serial == serial)
+        {
+            Some(request) => {
+                request.rect = rect;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Throw away a pending configure request, leaving the window as it is.
+    ///
+    /// Returns the request, or `None` if no request with this serial is pending.
+    pub fn deny_configure(&mut self, serial: u32) -> Option<ConfigureRequest<T>> {
+        let index = self
+            .configure_requests
+            .iter()
+            .position(|request| request.serial == serial)?;
+        Some(self.configure_requests.remove(index))
+    }
+
+    /// Apply a pending configure request, giving the window the requested rectangle.
+    ///
+    /// The rectangle is checked like in [`resize_window`](Self::resize_window). Older requests
+    /// for the same window are superseded, and are thrown away along with this one, whether or
+    /// not it could be applied. Returns the rectangle the window was given.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ConfigureError::UnknownRequest`] if no request with this serial is pending for
+    /// the window, or an error if the window can't be given the rectangle.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn apply_configure(
+        &mut self,
+        key: WindowKey,
+        serial: u32,
+    ) -> Result<Rectangle<T>, ConfigureError> {
+        assert!(self.contains_key(key), "window does not exist");
+
+        let index = self
+            .configure_requests
+            .iter()
+            .position(|request| request.serial == serial && request.window == key)
+            .ok_or(ConfigureError::UnknownRequest)?;
+        let rect = self.configure_requests[index].rect;
+
+        let mut i = 0;
+        self.configure_requests.retain(|request| {
+            i += 1;
+            request.window != key || i > index + 1
+        });
+
+        self.resize_window(key, rect)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ConfigureError, Rectangle, WindowTable};
+
+    #[test]
+    fn configure_requests() {
+        let mut window_table = WindowTable::new();
+
+        window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let a = window_table.insert(Rectangle::new(10, 10, 50, 50)).unwrap();
+        let b = window_table.insert(Rectangle::new(60, 60, 90, 90)).unwrap();
+
+        let first = window_table.request_configure(a, Rectangle::new(0, 0, 20, 20));
+        let other = window_table.request_configure(b, Rectangle::new(0, 0, 0, 0));
+        let second = window_table.request_configure(a, Rectangle::new(0, 0, 30, 30));
+        let third = window_table.request_configure(a, Rectangle::new(0, 0, 40, 40));
+        assert_eq!(window_table.configure_requests().len(), 4);
+        assert_eq!(window_table.rect(a), Rectangle::new(10, 10, 50, 50));
+
+        assert!(window_table.modify_configure(second, Rectangle::new(5, 5, 25, 25)));
+        assert_eq!(
+            window_table.apply_configure(b, first),
+            Err(ConfigureError::UnknownRequest)
+        );
+        assert_eq!(
+            window_table.apply_configure(a, second),
+            Ok(Rectangle::new(5, 5, 25, 25))
+        );
+        assert_eq!(window_table.rect(a), Rectangle::new(5, 5, 25, 25));
+
+        // The first request was superseded, and the others are still pending.
+        let pending = window_table
+            .configure_requests()
+            .iter()
+            .map(|request| request.serial);
+        assert!(pending.eq([other, third]));
+
+        assert_eq!(
+            window_table.apply_configure(b, other),
+            Err(ConfigureError::EmptyRectangle)
+        );
+        assert_eq!(window_table.deny_configure(third).unwrap().window, a);
+        assert!(window_table.configure_requests().is_empty());
+        assert_eq!(window_table.rect(a), Rectangle::new(5, 5, 25, 25));
+    }
+}
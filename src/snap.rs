@@ -0,0 +1,168 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later OR MPL-2.0
+// This file is a part of `windowless`.
+//
+// `windowless` is free software: you can redistribute it and/or modify it under the terms of
+// either:
+//
+// * GNU Lesser General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+// * Mozilla Public License as published by the Mozilla Foundation, version 2.
+//
+// `windowless` is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+// See the GNU Lesser General Public License or the Mozilla Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License and the Mozilla
+// Public License along with `windowless`. If not, see <https://www.gnu.org/licenses/> or
+// <https://www.mozilla.org/en-US/MPL/2.0/>.
+
+//! Snapping windows to the edges around them as they are moved or resized.
+
+use crate::{coord, Coordinate, Rectangle, WindowKey, WindowTable};
+use smallvec::SmallVec;
+
+impl<T: Coordinate, const N: usize, const R: usize> WindowTable<T, N, R> {
+    /// Adjust a proposed rectangle for a window so that its edges line up with the edges
+    /// around it.
+    ///
+    /// Edges within `threshold` of an edge of the window's parent, or of a sibling that is
+    /// level with the proposed rectangle, snap to the closest such edge. Edges of siblings snap
+    /// both to the matching edges and to the facing edges, so windows can be lined up or placed
+    /// side by side. If the proposed rectangle is the same size as the window, it is treated as
+    /// a move and translated as a whole. Otherwise, it is treated as a resize, and only the edges
+    /// that moved are snapped. The root window is returned unchanged.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn snap_rect(&self, key: WindowKey, proposed: Rectangle<T>, threshold: T) -> Rectangle<T> {
+        let current = self.windows[key.0].rect;
+        let parent = match self.parent(key) {
+            Some(parent) => parent,
+            None => return proposed,
+        };
+
+        // Collect the edges that could be snapped to along each axis.
+        let bounds = self.windows[parent.0].rect;
+        let mut xs = SmallVec::<[T; 16]>::from_slice(&[bounds.left, bounds.right]);
+        let mut ys = SmallVec::<[T; 16]>::from_slice(&[bounds.top, bounds.bottom]);
+        let near = |low: T, high: T, other_low: T, other_high: T| {
+            low < other_high.saturating_add(threshold) && other_low < high.saturating_add(threshold)
+        };
+        for sibling in self.children(parent).filter(|&sibling| sibling != key) {
+            let rect = self.windows[sibling.0].rect;
+            if near(proposed.top, proposed.bottom, rect.top, rect.bottom) {
+                xs.extend([rect.left, rect.right]);
+            }
+            if near(proposed.left, proposed.right, rect.left, rect.right) {
+                ys.extend([rect.top, rect.bottom]);
+            }
+        }
+
+        let mut rect = proposed;
+        if proposed.width() == current.width() && proposed.height() == current.height() {
+            let dx = snap_offset(&[proposed.left, proposed.right], &xs, threshold);
+            let dy = snap_offset(&[proposed.top, proposed.bottom], &ys, threshold);
+            if let Some(dx) = dx {
+                rect.left = rect.left.saturating_add(dx);
+                rect.right = rect.right.saturating_add(dx);
+            }
+            if let Some(dy) = dy {
+                rect.top = rect.top.saturating_add(dy);
+                rect.bottom = rect.bottom.saturating_add(dy);
+            }
+        } else {
+            let edges = [
+                (&mut rect.left, current.left, &xs),
+                (&mut rect.right, current.right, &xs),
+                (&mut rect.top, current.top, &ys),
+                (&mut rect.bottom, current.bottom, &ys),
+            ];
+            for (edge, old, candidates) in edges {
+                if *edge != old {
+                    if let Some(offset) = snap_offset(&[*edge], candidates, threshold) {
+                        *edge = edge.saturating_add(offset);
+                    }
+                }
+            }
+        }
+
+        // Don't let snapping turn the rectangle inside out.
+        if rect.left > rect.right || rect.top > rect.bottom {
+            return proposed;
+        }
+
+        rect
+    }
+}
+
+/// Find the smallest offset within the threshold that moves one of the edges onto a candidate.
+fn snap_offset<T: Coordinate>(edges: &[T], candidates: &[T], threshold: T) -> Option<T> {
+    let mut best: Option<(T, T)> = None;
+
+    for &edge in edges {
+        for &candidate in candidates {
+            let distance = match coord::checked_distance(edge, candidate) {
+                Some(distance) if distance <= threshold => distance,
+                _ => continue,
+            };
+
+            if !matches!(best, Some((best, _)) if best <= distance) {
+                best = Some((distance, candidate - edge));
+            }
+        }
+    }
+
+    best.map(|(_, offset)| offset)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Rectangle, WindowTable};
+
+    #[test]
+    fn snap_rect() {
+        let mut window_table = WindowTable::new();
+
+        let root = window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let a = window_table.insert(Rectangle::new(10, 10, 30, 30)).unwrap();
+        let b = window_table.insert(Rectangle::new(60, 60, 80, 80)).unwrap();
+        assert_eq!(window_table.parent(b), Some(root));
+
+        // Moves keep the size, snapping to the parent and to the facing edges of siblings.
+        assert_eq!(
+            window_table.snap_rect(b, Rectangle::new(78, 3, 98, 23), 5),
+            Rectangle::new(80, 0, 100, 20)
+        );
+        assert_eq!(
+            window_table.snap_rect(b, Rectangle::new(33, 12, 53, 32), 5),
+            Rectangle::new(30, 10, 50, 30)
+        );
+        assert_eq!(
+            window_table.snap_rect(b, Rectangle::new(40, 50, 60, 70), 5),
+            Rectangle::new(40, 50, 60, 70)
+        );
+
+        // Siblings that aren't level with the window are ignored.
+        assert_eq!(
+            window_table.snap_rect(b, Rectangle::new(33, 50, 53, 70), 5),
+            Rectangle::new(33, 50, 53, 70)
+        );
+
+        // Resizes only snap the edges that moved.
+        assert_eq!(
+            window_table.snap_rect(b, Rectangle::new(60, 3, 80, 80), 5),
+            Rectangle::new(60, 0, 80, 80)
+        );
+        assert_eq!(
+            window_table.snap_rect(b, Rectangle::new(60, 60, 97, 80), 5),
+            Rectangle::new(60, 60, 100, 80)
+        );
+
+        assert_eq!(
+            window_table.snap_rect(root, Rectangle::new(1, 1, 99, 99), 5),
+            Rectangle::new(1, 1, 99, 99)
+        );
+        assert_eq!(window_table.rect(a), Rectangle::new(10, 10, 30, 30));
+    }
+}
@@ -0,0 +1,281 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later OR MPL-2.0
+// This file is a part of `windowless`.
+//
+// `windowless` is free software: you can redistribute it and/or modify it under the terms of
+// either:
+//
+// * GNU Lesser General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+// * Mozilla Public License as published by the Mozilla Foundation, version 2.
+//
+// `windowless` is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+// See the GNU Lesser General Public License or the Mozilla Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License and the Mozilla
+// Public License along with `windowless`. If not, see <https://www.gnu.org/licenses/> or
+// <https://www.mozilla.org/en-US/MPL/2.0/>.
+
+//! Immutable copies of the window table.
+
+use crate::{Coordinate, Key, Rectangle, Region, WindowKey, WindowTable};
+use alloc::vec::Vec;
+use slotmap::SecondaryMap;
+
+/// An immutable copy of the windows in a table.
+///
+/// Created by [`WindowTable::snapshot`]. Two snapshots can be compared with
+/// [`Snapshot::diff`].
+#[derive(Debug, Clone)]
+pub struct Snapshot<T: Coordinate = i32> {
+    /// The root window.
+    root: Option<WindowKey>,
+
+    /// The state of each window.
+    windows: SecondaryMap<Key, Entry<T>>,
+}
+
+/// The state of a window in a snapshot.
+#[derive(Debug, Copy, Clone)]
+struct Entry<T> {
+    /// The rectangle of the window.
+    rect: Rectangle<T>,
+
+    /// The rectangle the window is painted in.
+    visual: Rectangle<T>,
+
+    /// The position of the window in the stacking order.
+    z: usize,
+
+    /// Whether the window was viewable.
+    viewable: bool,
+}
+
+/// The differences between two snapshots.
+///
+/// Returned by [`Snapshot::diff`].
+#[derive(Debug, Clone, Default)]
+pub struct Diff<T: Coordinate = i32> {
+    /// The windows that only exist in the new snapshot.
+    pub created: Vec<WindowKey>,
+
+    /// The windows that only exist in the old snapshot.
+    pub destroyed: Vec<WindowKey>,
+
+    /// The windows whose rectangles changed, along with their old and new rectangles.
+    pub moved: Vec<(WindowKey, Rectangle<T>, Rectangle<T>)>,
+
+    /// The windows that changed position in the stacking order, relative to the other windows
+    /// that exist in both snapshots.
+    pub restacked: Vec<WindowKey>,
+
+    /// The region of the screen that needs to be repainted to go from the old snapshot to the
+    /// new one.
+    pub damage: Region<T>,
+}
+
+impl<T: Coordinate> Snapshot<T> {
+    /// Take a snapshot of a table.
+    pub(crate) fn new<const N: usize, const R: usize>(table: &WindowTable<T, N, R>) -> Self {
+        let mut windows = SecondaryMap::with_capacity(table.windows.len());
+        for (key, window) in &table.windows {
+            windows.insert(
+                key,
+                Entry {
+                    rect: window.rect,
+                    visual: window.visual_rect(),
+                    z: window.z,
+                    viewable: table.is_viewable(WindowKey(key)),
+                },
+            );
+        }
+
+        Self {
+            root: table.root,
+            windows,
+        }
+    }
+
+    /// Returns the root window.
+    pub fn root(&self) -> Option<WindowKey> {
+        self.root
+    }
+
+    /// Returns the number of windows in the snapshot.
+    pub fn len(&self) -> usize {
+        self.windows.len()
+    }
+
+    /// Tell if the snapshot has no windows.
+    pub fn is_empty(&self) -> bool {
+        self.windows.is_empty()
+    }
+
+    /// Returns the rectangle of a window, or `None` if it did not exist.
+    pub fn rect(&self, key: WindowKey) -> Option<Rectangle<T>> {
+        self.windows.get(key.0).map(|entry| entry.rect)
+    }
+
+    /// Tell if a window was viewable.
+    pub fn is_viewable(&self, key: WindowKey) -> bool {
+        matches!(self.windows.get(key.0), Some(entry) if entry.viewable)
+    }
+
+    /// Iterate over the windows and their rectangles.
+    pub fn iter(&self) -> impl Iterator<Item = (WindowKey, Rectangle<T>)> + '_ {
+        self.windows
+            .iter()
+            .map(|(key, entry)| (WindowKey(key), entry.rect))
+    }
+
+    /// Compare this snapshot with a newer one.
+    pub fn diff(&self, new: &Self) -> Diff<T> {
+        let mut diff = Diff::default();
+
+        for (key, entry) in &self.windows {
+            if !new.windows.contains_key(key) {
+                diff.destroyed.push(WindowKey(key));
+                if entry.viewable {
+                    diff.damage.union_rect(entry.visual);
+                }
+            }
+        }
+
+        // The windows in both snapshots, in their old stacking order.
+        let mut survivors = Vec::new();
+
+        for (key, entry) in &new.windows {
+            let old = match self.windows.get(key) {
+                Some(old) => old,
+                None => {
+                    diff.created.push(WindowKey(key));
+                    if entry.viewable {
+                        diff.damage.union_rect(entry.visual);
+                    }
+                    continue;
+                }
+            };
+
+            if old.rect != entry.rect {
+                diff.moved.push((WindowKey(key), old.rect, entry.rect));
+            }
+
+            if old.visual != entry.visual || old.viewable != entry.viewable {
+                if old.viewable {
+                    diff.damage.union_rect(old.visual);
+                }
+                if entry.viewable {
+                    diff.damage.union_rect(entry.visual);
+                }
+            }
+
+            survivors.push((old.z, entry.z, key));
+        }
+
+        // The windows that kept their relative order form the longest increasing run of new
+        // positions. Every other window was restacked.
+        survivors.sort_unstable_by_key(|&(old, _, _)| old);
+        let new_order = survivors.iter().map(|&(_, z, _)| z).collect::<Vec<_>>();
+        let kept = longest_increasing(&new_order);
+
+        let mut kept = kept.into_iter().peekable();
+        for (i, &(_, _, key)) in survivors.iter().enumerate() {
+            if kept.peek() == Some(&i) {
+                kept.next();
+                continue;
+            }
+
+            diff.restacked.push(WindowKey(key));
+            for entry in [&self.windows[key], &new.windows[key]] {
+                if entry.viewable {
+                    diff.damage.union_rect(entry.visual);
+                }
+            }
+        }
+
+        diff
+    }
+}
+
+/// Get the indices of the longest strictly increasing subsequence of a list.
+fn longest_increasing(values: &[usize]) -> Vec<usize> {
+    // The index of the smallest value ending an increasing run of each length.
+    let mut tails = Vec::<usize>::new();
+
+    // The index of the previous value in the run ending at each index.
+    let mut previous = Vec::with_capacity(values.len());
+
+    for (i, &value) in values.iter().enumerate() {
+        let length = tails.partition_point(|&tail| values[tail] < value);
+        previous.push(length.checked_sub(1).map(|length| tails[length]));
+
+        if length == tails.len() {
+            tails.push(i);
+        } else {
+            tails[length] = i;
+        }
+    }
+
+    let mut run = Vec::with_capacity(tails.len());
+    let mut next = tails.last().copied();
+    while let Some(i) = next {
+        run.push(i);
+        next = previous[i];
+    }
+
+    run.reverse();
+    run
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Rectangle, WindowTable};
+
+    #[test]
+    fn diff() {
+        let mut window_table = WindowTable::new();
+
+        window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let a = window_table.insert(Rectangle::new(0, 0, 10, 10)).unwrap();
+        let b = window_table.insert(Rectangle::new(20, 20, 30, 30)).unwrap();
+        window_table.insert(Rectangle::new(25, 25, 40, 40)).unwrap();
+        let old = window_table.snapshot();
+
+        window_table.remove(a);
+        window_table.move_window(b, (50, 50)).unwrap();
+        let d = window_table.insert(Rectangle::new(80, 80, 90, 90)).unwrap();
+        let new = window_table.snapshot();
+
+        let diff = old.diff(&new);
+        assert_eq!(diff.created, [d]);
+        assert_eq!(diff.destroyed, [a]);
+        assert_eq!(
+            diff.moved,
+            [(
+                b,
+                Rectangle::new(20, 20, 30, 30),
+                Rectangle::new(50, 50, 60, 60)
+            )]
+        );
+        assert!(diff.restacked.is_empty());
+        assert_eq!(diff.damage.area(), 400);
+        assert_eq!(old.rect(a), Some(Rectangle::new(0, 0, 10, 10)));
+        assert_eq!(new.rect(a), None);
+    }
+
+    #[test]
+    fn restack() {
+        let mut window_table = WindowTable::new();
+
+        window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let a = window_table.insert(Rectangle::new(0, 0, 50, 50)).unwrap();
+        window_table.insert(Rectangle::new(25, 25, 75, 75)).unwrap();
+        let old = window_table.snapshot();
+
+        window_table.raise(a);
+        let diff = old.diff(&window_table.snapshot());
+        assert_eq!(diff.restacked, [a]);
+        assert!(diff.moved.is_empty());
+        assert_eq!(diff.damage.area(), 2500);
+    }
+}
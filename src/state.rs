@@ -0,0 +1,411 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later OR MPL-2.0
+// This file is a part of `windowless`.
+//
+// `windowless` is free software: you can redistribute it and/or modify it under the terms of
+// either:
+//
+// * GNU Lesser General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+// * Mozilla Public License as published by the Mozilla Foundation, version 2.
+//
+// `windowless` is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+// See the GNU Lesser General Public License or the Mozilla Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License and the Mozilla
+// Public License along with `windowless`. If not, see <https://www.gnu.org/licenses/> or
+// <https://www.mozilla.org/en-US/MPL/2.0/>.
+
+//! Window states that can be undone, such as being minimized, maximized or fullscreen.
+
+use crate::{ConfigureError, Coordinate, Layer, Rectangle, WindowKey, WindowTable};
+use alloc::collections::BTreeMap;
+
+/// Where a window was before its state changed, so that it can be put back.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Placement<T> {
+    /// The rectangle of the window.
+    rect: Rectangle<T>,
+
+    /// The window stacked directly beneath it.
+    below: Option<WindowKey>,
+}
+
+/// The directions a window can be maximized in.
+///
+/// Passed to [`WindowTable::maximize`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum MaximizeAxis {
+    /// Fill the usable area in both directions.
+    Both,
+
+    /// Fill the width of the usable area, keeping the window's vertical position.
+    Horizontal,
+
+    /// Fill the height of the usable area, keeping the window's horizontal position.
+    Vertical,
+}
+
+impl<T: Coordinate> Placement<T> {
+    /// Move this placement into another table, where the windows have new keys.
+    ///
+    /// The window beneath is forgotten if it has no key in the other table.
+    pub(crate) fn moved(self, dx: T, dy: T, keys: &BTreeMap<WindowKey, WindowKey>) -> Self {
+        Self {
+            rect: self.rect.translate(dx, dy),
+            below: self.below.and_then(|below| keys.get(&below).copied()),
+        }
+    }
+}
+
+impl<T: Coordinate, const N: usize, const R: usize> WindowTable<T, N, R> {
+    /// Minimize a window, unmapping it until it is [restored](Self::restore).
+    ///
+    /// The rectangle and stacking position of the window are remembered, so that restoring it
+    /// puts it back exactly where it was. Returns `false` if the window is already minimized.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn minimize(&mut self, key: WindowKey) -> bool {
+        if self.is_minimized(key) {
+            return false;
+        }
+
+        let placement = self.placement(key);
+        self.windows[key.0].attributes_mut().minimized = Some(placement);
+        self.unmap(key);
+        true
+    }
+
+    /// Restore a minimized window, mapping it with the rectangle and stacking position it had
+    /// when it was minimized.
+    ///
+    /// If the window it was stacked above has since been removed, it keeps its current stacking
+    /// position. Returns `false` if the window is not minimized.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn restore(&mut self, key: WindowKey) -> bool {
+        let placement = match self.windows[key.0].attributes.as_mut() {
+            Some(attributes) => attributes.minimized.take(),
+            None => None,
+        };
+
+        match placement {
+            Some(placement) => {
+                self.put_back(key, placement, true);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Tell if a window is minimized.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn is_minimized(&self, key: WindowKey) -> bool {
+        self.windows[key.0]
+            .attributes()
+            .and_then(|attributes| attributes.minimized.as_ref())
+            .is_some()
+    }
+
+    /// Make a window fullscreen, or put it back where it was before it was made fullscreen.
+    ///
+    /// A fullscreen window is given the rectangle of the root window and raised to the top of
+    /// its layer. Its previous rectangle and stacking position are remembered and restored
+    /// when it leaves fullscreen. Setting the state a window is already in does nothing.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the window can't be given the root's rectangle, in which case it is
+    /// not made fullscreen.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn set_fullscreen(
+        &mut self,
+        key: WindowKey,
+        fullscreen: bool,
+    ) -> Result<(), ConfigureError> {
+        if fullscreen {
+            if self.is_fullscreen(key) {
+                return Ok(());
+            }
+
+            let root = self.rect(self.root.expect("table has a window"));
+            let placement = self.placement(key);
+
+            // Raise the window before growing it, so that the windows it comes to cover stay
+            // beneath it.
+            let mut transaction = self.transaction();
+            transaction.raise(key);
+            if placement.rect != root {
+                transaction.resize_window(key, root)?;
+            }
+            transaction.commit();
+
+            self.windows[key.0].attributes_mut().fullscreen = Some(placement);
+        } else if let Some(placement) = self.windows[key.0]
+            .attributes
+            .as_mut()
+            .and_then(|attributes| attributes.fullscreen.take())
+        {
+            self.put_back(key, placement, false);
+        }
+
+        Ok(())
+    }
+
+    /// Tell if a window is fullscreen.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn is_fullscreen(&self, key: WindowKey) -> bool {
+        self.windows[key.0]
+            .attributes()
+            .and_then(|attributes| attributes.fullscreen.as_ref())
+            .is_some()
+    }
+
+    /// Maximize a window within the [usable area](Self::usable_area) of its primary parent.
+    ///
+    /// The window's rectangle is still constrained by its [`SizeHints`](crate::SizeHints). The
+    /// rectangle it had before it was first maximized is remembered, so that maximizing it
+    /// along another axis and then [unmaximizing](Self::unmaximize) it puts it back where it
+    /// started.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the window is the root window or can't be given the maximized
+    /// rectangle, in which case it is left as it was.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn maximize(&mut self, key: WindowKey, axis: MaximizeAxis) -> Result<(), ConfigureError> {
+        let parent = self.parent(key).ok_or(ConfigureError::RootWindow)?;
+        let area = self.usable_area(parent);
+        let (_, original) = self.windows[key.0]
+            .attributes()
+            .and_then(|attributes| attributes.maximized)
+            .unwrap_or((axis, self.rect(key)));
+
+        let mut rect = original;
+        if axis != MaximizeAxis::Vertical {
+            rect.left = area.left;
+            rect.right = area.right;
+        }
+        if axis != MaximizeAxis::Horizontal {
+            rect.top = area.top;
+            rect.bottom = area.bottom;
+        }
+
+        if rect != self.rect(key) {
+            self.resize_window(key, rect)?;
+        }
+        self.windows[key.0].attributes_mut().maximized = Some((axis, original));
+        Ok(())
+    }
+
+    /// Put a maximized window back to the rectangle it had before it was maximized.
+    ///
+    /// Returns `false` if the window is not maximized.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn unmaximize(&mut self, key: WindowKey) -> bool {
+        let maximized = self.windows[key.0]
+            .attributes
+            .as_mut()
+            .and_then(|attributes| attributes.maximized.take());
+
+        match maximized {
+            Some((_, rect)) => {
+                if rect != self.rect(key) {
+                    // The window can't be put back if the rectangle no longer fits.
+                    self.resize_window(key, rect).ok();
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns the axis a window is maximized along, or `None` if it is not maximized.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn maximized(&self, key: WindowKey) -> Option<MaximizeAxis> {
+        self.windows[key.0]
+            .attributes()
+            .and_then(|attributes| attributes.maximized)
+            .map(|(axis, _)| axis)
+    }
+
+    /// Returns the part of a window that maximized windows may cover.
+    ///
+    /// This is the rectangle of the window, minus any viewable windows in [`Layer::Dock`] that
+    /// span one of its edges.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn usable_area(&self, key: WindowKey) -> Rectangle<T> {
+        let full = self.windows[key.0].rect;
+        let mut area = full;
+
+        for &dock in &self.order {
+            let window = &self.windows[dock.0];
+            if dock == key || window.layer != Layer::Dock || !self.is_viewable(dock) {
+                continue;
+            }
+
+            let rect = window.rect;
+            if rect.left <= full.left && rect.right >= full.right {
+                if rect.top <= full.top && rect.bottom > area.top {
+                    area.top = rect.bottom;
+                } else if rect.bottom >= full.bottom && rect.top < area.bottom {
+                    area.bottom = rect.top;
+                }
+            } else if rect.top <= full.top && rect.bottom >= full.bottom {
+                if rect.left <= full.left && rect.right > area.left {
+                    area.left = rect.right;
+                } else if rect.right >= full.right && rect.left < area.right {
+                    area.right = rect.left;
+                }
+            }
+        }
+
+        area
+    }
+
+    /// Remember where a window is.
+    pub(crate) fn placement(&self, key: WindowKey) -> Placement<T> {
+        let window = &self.windows[key.0];
+        Placement {
+            rect: window.rect,
+            below: window.z.checked_sub(1).map(|z| self.order[z]),
+        }
+    }
+
+    /// Put a window back where it was, mapping it as well if asked to.
+    pub(crate) fn put_back(&mut self, key: WindowKey, placement: Placement<T>, map: bool) {
+        let resize = self.rect(key) != placement.rect;
+
+        let mut transaction = self.transaction();
+        if resize {
+            // The window can't be put back if the rectangle no longer fits.
+            transaction.resize_window(key, placement.rect).ok();
+        }
+        if let Some(below) = placement.below {
+            transaction.place_above(key, below);
+        }
+        if map {
+            transaction.map(key);
+        }
+        transaction.commit();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MaximizeAxis;
+    use crate::{Layer, Rectangle, WindowTable};
+
+    #[test]
+    fn minimize() {
+        let mut window_table = WindowTable::new();
+
+        let root = window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let a = window_table.insert(Rectangle::new(0, 0, 50, 50)).unwrap();
+        let b = window_table.insert(Rectangle::new(25, 25, 75, 75)).unwrap();
+        let c = window_table.insert(Rectangle::new(40, 40, 90, 90)).unwrap();
+
+        assert!(window_table.minimize(b));
+        assert!(!window_table.minimize(b));
+        assert!(window_table.is_minimized(b));
+        assert!(!window_table.is_viewable(b));
+
+        // Move things around while the window is minimized.
+        window_table.raise(a);
+        window_table
+            .resize_window(b, Rectangle::new(0, 0, 10, 10))
+            .unwrap();
+
+        assert!(window_table.restore(b));
+        assert!(!window_table.restore(b));
+        assert!(!window_table.is_minimized(b));
+        assert!(window_table.is_viewable(b));
+        assert_eq!(window_table.rect(b), Rectangle::new(25, 25, 75, 75));
+        assert!(window_table
+            .paint_order()
+            .map(|(key, _)| key)
+            .eq([root, c, a, b]));
+        assert_eq!(window_table.validate(), Ok(()));
+    }
+
+    #[test]
+    fn fullscreen() {
+        let mut window_table = WindowTable::new();
+
+        let root = window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let a = window_table.insert(Rectangle::new(0, 0, 30, 30)).unwrap();
+        let b = window_table.insert(Rectangle::new(50, 50, 90, 90)).unwrap();
+
+        window_table.set_fullscreen(a, true).unwrap();
+        assert!(window_table.is_fullscreen(a));
+        assert_eq!(window_table.rect(a), Rectangle::new(0, 0, 100, 100));
+        assert!(window_table
+            .paint_order()
+            .map(|(key, _)| key)
+            .eq([root, b, a]));
+
+        // Setting it again doesn't forget the original placement.
+        window_table.set_fullscreen(a, true).unwrap();
+
+        window_table.set_fullscreen(a, false).unwrap();
+        assert!(!window_table.is_fullscreen(a));
+        assert_eq!(window_table.rect(a), Rectangle::new(0, 0, 30, 30));
+        assert!(window_table
+            .paint_order()
+            .map(|(key, _)| key)
+            .eq([root, a, b]));
+        assert_eq!(window_table.validate(), Ok(()));
+    }
+
+    #[test]
+    fn maximize() {
+        let mut window_table = WindowTable::new();
+
+        window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let dock = window_table.insert(Rectangle::new(0, 0, 100, 10)).unwrap();
+        window_table.set_layer(dock, Layer::Dock);
+        let a = window_table.insert(Rectangle::new(20, 20, 40, 40)).unwrap();
+
+        window_table.maximize(a, MaximizeAxis::Horizontal).unwrap();
+        assert_eq!(window_table.maximized(a), Some(MaximizeAxis::Horizontal));
+        assert_eq!(window_table.rect(a), Rectangle::new(0, 20, 100, 40));
+
+        window_table.maximize(a, MaximizeAxis::Both).unwrap();
+        assert_eq!(window_table.maximized(a), Some(MaximizeAxis::Both));
+        assert_eq!(window_table.rect(a), Rectangle::new(0, 10, 100, 100));
+
+        window_table.maximize(a, MaximizeAxis::Vertical).unwrap();
+        assert_eq!(window_table.rect(a), Rectangle::new(20, 10, 40, 100));
+
+        assert!(window_table.unmaximize(a));
+        assert!(!window_table.unmaximize(a));
+        assert_eq!(window_table.maximized(a), None);
+        assert_eq!(window_table.rect(a), Rectangle::new(20, 20, 40, 40));
+        assert_eq!(window_table.validate(), Ok(()));
+    }
+}
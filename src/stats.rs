@@ -0,0 +1,163 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later OR MPL-2.0
+// This file is a part of `windowless`.
+//
+// `windowless` is free software: you can redistribute it and/or modify it under the terms of
+// either:
+//
+// * GNU Lesser General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+// * Mozilla Public License as published by the Mozilla Foundation, version 2.
+//
+// `windowless` is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+// See the GNU Lesser General Public License or the Mozilla Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License and the Mozilla
+// Public License along with `windowless`. If not, see <https://www.gnu.org/licenses/> or
+// <https://www.mozilla.org/en-US/MPL/2.0/>.
+
+//! Statistics about the windows in a table.
+
+use crate::{Attributes, Coordinate, Region, Window, WindowKey, WindowTable};
+use core::mem;
+
+use slotmap::SecondaryMap;
+
+/// Statistics about the windows in a table.
+///
+/// Returned by [`WindowTable::stats`].
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct Stats<T = i32> {
+    /// The number of windows in the table.
+    pub windows: usize,
+
+    /// The largest [depth](WindowTable::depth) of any window.
+    pub max_depth: usize,
+
+    /// The average depth of the windows, or zero if there are none.
+    pub average_depth: f64,
+
+    /// The area of the root window covered by the viewable windows above it.
+    pub covered_area: T,
+
+    /// The area of the root window, or zero if there is none.
+    pub root_area: T,
+
+    /// The number of pairs of windows stacked directly on top of one another.
+    ///
+    /// This is the number of parent links between windows, primary or not.
+    pub overlaps: usize,
+
+    /// An estimate of the number of bytes the table takes up, including its allocations.
+    pub memory: usize,
+}
+
+impl<T: Coordinate, const N: usize, const R: usize> WindowTable<T, N, R> {
+    /// Gather statistics about the windows in the table.
+    ///
+    /// This walks every window, so it is meant for diagnostics rather than for every frame.
+    pub fn stats(&self) -> Stats<T> {
+        // Primary parents always come before their children in the stacking order.
+        let mut depths = SecondaryMap::<_, usize>::with_capacity(self.windows.len());
+        let (mut max_depth, mut total_depth) = (0, 0);
+        for &key in &self.order {
+            let depth = match self.parent(key) {
+                Some(parent) => depths[parent.0] + 1,
+                None => 0,
+            };
+            depths.insert(key.0, depth);
+            max_depth = max_depth.max(depth);
+            total_depth += depth;
+        }
+
+        let (covered_area, root_area) = match self.root {
+            Some(root) => {
+                let rect = self.windows[root.0].rect;
+                let mut covered = Region::default();
+                for (key, _) in self.paint_order().skip(1) {
+                    covered.union(&self.windows[key.0].bounding_region());
+                }
+                covered.intersect_rect(rect);
+                (covered.area(), rect.saturating_area())
+            }
+            None => (T::ZERO, T::ZERO),
+        };
+
+        Stats {
+            windows: self.windows.len(),
+            max_depth,
+            average_depth: if self.windows.is_empty() {
+                0.0
+            } else {
+                total_depth as f64 / self.windows.len() as f64
+            },
+            covered_area,
+            root_area,
+            overlaps: self
+                .windows
+                .values()
+                .map(|window| window.parents.len())
+                .sum(),
+            memory: self.memory(),
+        }
+    }
+
+    /// Estimate the number of bytes the table takes up, including its allocations.
+    fn memory(&self) -> usize {
+        let key = mem::size_of::<WindowKey>();
+        let region = |region: &Region<T, R>| mem::size_of_val(region.rects());
+
+        // Each slot also stores a version alongside the window.
+        let mut memory = mem::size_of::<Self>()
+            + self.windows.capacity() * (mem::size_of::<Window<T>>() + mem::size_of::<u32>())
+            + self.order.capacity() * key
+            + self.index.heap_size()
+            + region(&self.damage)
+            + self.damage_history.iter().map(region).sum::<usize>();
+
+        for window in self.windows.values() {
+            if window.attributes.is_some() {
+                memory += mem::size_of::<Attributes<T>>();
+            }
+            if window.parents.spilled() {
+                memory += window.parents.capacity() * key;
+            }
+            if window.children.spilled() {
+                memory += window.children.capacity() * key;
+            }
+            memory += window.input_region.as_ref().map_or(0, region);
+            memory += window.shape.as_ref().map_or(0, region);
+        }
+
+        memory
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Rectangle, WindowTable};
+
+    #[test]
+    fn stats() {
+        let mut window_table = WindowTable::new();
+        let stats = window_table.stats();
+        assert_eq!(stats.windows, 0);
+        assert_eq!(stats.average_depth, 0.0);
+
+        window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        window_table.insert(Rectangle::new(0, 0, 50, 50)).unwrap();
+        window_table.insert(Rectangle::new(10, 10, 40, 40)).unwrap();
+        let hidden = window_table.insert(Rectangle::new(80, 80, 90, 90)).unwrap();
+        window_table.unmap(hidden);
+
+        let stats = window_table.stats();
+        assert_eq!(stats.windows, 4);
+        assert_eq!(stats.max_depth, 2);
+        assert_eq!(stats.average_depth, 1.0);
+        assert_eq!(stats.covered_area, 50 * 50);
+        assert_eq!(stats.root_area, 100 * 100);
+        assert_eq!(stats.overlaps, 3);
+        assert!(stats.memory > 4 * core::mem::size_of::<Rectangle>());
+    }
+}
@@ -0,0 +1,351 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later OR MPL-2.0
+// This file is a part of `windowless`.
+//
+// `windowless` is free software: you can redistribute it and/or modify it under the terms of
+// either:
+//
+// * GNU Lesser General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+// * Mozilla Public License as published by the Mozilla Foundation, version 2.
+//
+// `windowless` is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+// See the GNU Lesser General Public License or the Mozilla Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License and the Mozilla
+// Public License along with `windowless`. If not, see <https://www.gnu.org/licenses/> or
+// <https://www.mozilla.org/en-US/MPL/2.0/>.
+
+//! Moving and copying subtrees of windows, within a table and between tables.
+
+use crate::{Coordinate, InsertError, Order, Point, Property, WindowKey, WindowTable};
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+use slotmap::SecondaryMap;
+
+impl<T: Coordinate, const N: usize, const R: usize> WindowTable<T, N, R> {
+    /// Remove a window and its descendants, returning them as a new table.
+    ///
+    /// The window becomes the root window of the new table. The windows keep their rectangles,
+    /// stacking order and state, but are given new keys. These are returned as a map from the
+    /// old keys to the new ones, so that other state can follow the windows. Windows stacked on
+    /// top of the subtree are re-parented as if the windows had been [removed](Self::remove).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn detach(&mut self, key: WindowKey) -> (Self, BTreeMap<WindowKey, WindowKey>) {
+        assert!(self.contains_key(key), "window does not exist");
+
+        let subtree = self.subtree_in_order(key);
+        let mut table = Self::default();
+        let keys = table
+            .copy_windows(self, &subtree, T::ZERO, T::ZERO)
+            .expect("descendants always overlap the root window");
+
+        let mut transaction = self.transaction();
+        for &window in &subtree {
+            transaction.remove(window);
+        }
+        transaction.commit();
+
+        (table, keys)
+    }
+
+    /// Insert the windows of another table above a window, moving them by an offset.
+    ///
+    /// This is the inverse of [`detach`](Self::detach). The windows are stacked directly above
+    /// `parent` and its descendants, keeping their stacking order and state, so the root window
+    /// of the other table becomes a child of `parent` wherever it mostly lies on it. Returns a
+    /// map from the keys in the other table to the new keys, so that other state can follow the
+    /// windows. If any of the windows fall outside of the root window, none are inserted.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the parent does not exist.
+    pub fn graft(
+        &mut self,
+        parent: WindowKey,
+        other: Self,
+        offset: impl Into<Point<T>>,
+    ) -> Result<BTreeMap<WindowKey, WindowKey>, InsertError> {
+        assert!(self.contains_key(parent), "window does not exist");
+
+        let offset = offset.into();
+        let keys = self.copy_windows(&other, &other.order, offset.x, offset.y)?;
+
+        // The windows were inserted on top of the stack, so move them down above the parent.
+        let mut order = self.stacking_order();
+        order.truncate(order.len() - keys.len());
+        let last = *self.subtree_in(&order, parent).last().unwrap();
+        let position = order.iter().position(|&window| window == last).unwrap() + 1;
+        order.splice(
+            position..position,
+            other.order.iter().map(|window| keys[window]),
+        );
+        self.relink(&order);
+
+        Ok(keys)
+    }
+
+    /// Copy a window and its descendants, moving the copies by an offset.
+    ///
+    /// The copies keep the stacking order and state of the windows they were copied from, and
+    /// are stacked on top of the other windows in their layers. Returns the key of the copy of
+    /// the window. If any of the copies fall outside of the root window, none are inserted.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn duplicate_subtree(
+        &mut self,
+        key: WindowKey,
+        offset: impl Into<Point<T>>,
+    ) -> Result<WindowKey, InsertError> {
+        assert!(self.contains_key(key), "window does not exist");
+
+        // Copy the windows out first, so that the table isn't borrowed while inserting them.
+        let mut template = Self::default();
+        let template_keys = template
+            .copy_windows(self, &self.subtree_in_order(key), T::ZERO, T::ZERO)
+            .expect("descendants always overlap the root window");
+
+        let offset = offset.into();
+        let keys = self.copy_windows(&template, &template.order, offset.x, offset.y)?;
+        Ok(keys[&template_keys[&key]])
+    }
+
+    /// Returns a window and its descendants, from the bottom of the stack up.
+    pub(crate) fn subtree_in_order(&self, key: WindowKey) -> Vec<WindowKey> {
+        let mut in_subtree = SecondaryMap::new();
+        for window in self.traverse(key, Order::DepthFirst) {
+            in_subtree.insert(window.0, ());
+        }
+
+        self.order
+            .iter()
+            .copied()
+            .filter(|window| in_subtree.contains_key(window.0))
+            .collect()
+    }
+
+    /// Insert copies of windows from another table on top of the stack, moved by an offset.
+    ///
+    /// The windows must come from the bottom of the stack up. If any of them cannot be
+    /// inserted, the ones already inserted are removed again.
+    pub(crate) fn copy_windows(
+        &mut self,
+        from: &Self,
+        windows: &[WindowKey],
+        dx: T,
+        dy: T,
+    ) -> Result<BTreeMap<WindowKey, WindowKey>, InsertError> {
+        let mut keys = BTreeMap::new();
+        for &window in windows {
+            match self.insert(from.windows[window.0].rect.translate(dx, dy)) {
+                Ok(key) => {
+                    keys.insert(window, key);
+                }
+                Err(err) => {
+                    let mut transaction = self.transaction();
+                    for &key in keys.values() {
+                        transaction.remove(key);
+                    }
+                    transaction.commit();
+                    return Err(err);
+                }
+            }
+        }
+
+        // Now that every window has a key, copy their state across.
+        for (&old, &new) in &keys {
+            self.copy_state(from, old, new, dx, dy, &keys);
+        }
+
+        // Restack the copies that were given z-indices.
+        if keys.values().any(|&key| self.z_index(key) != 0) {
+            let order = self.stacking_order();
+            self.relink(&order);
+        }
+
+        Ok(keys)
+    }
+
+    /// Copy the state of a window in another table onto a newly inserted window.
+    fn copy_state(
+        &mut self,
+        from: &Self,
+        old: WindowKey,
+        new: WindowKey,
+        dx: T,
+        dy: T,
+        keys: &BTreeMap<WindowKey, WindowKey>,
+    ) {
+        let source = &from.windows[old.0];
+
+        let mut attributes = source.attributes.clone();
+        if let Some(attributes) = &mut attributes {
+            attributes.minimized = attributes
+                .minimized
+                .map(|placement| placement.moved(dx, dy, keys));
+            attributes.fullscreen = attributes
+                .fullscreen
+                .map(|placement| placement.moved(dx, dy, keys));
+            if let Some((_, rect)) = &mut attributes.maximized {
+                *rect = rect.translate(dx, dy);
+            }
+
+            // Atoms are interned separately by each table.
+            let properties = core::mem::take(&mut attributes.properties);
+            for (atom, property) in properties {
+                let atom = self.atoms.intern(from.atom_name(atom).unwrap());
+                let property = match property {
+                    Property::Atom(value) => {
+                        Property::Atom(self.atoms.intern(from.atom_name(value).unwrap()))
+                    }
+                    Property::Window(window) => {
+                        Property::Window(keys.get(&window).copied().unwrap_or(window))
+                    }
+                    property => property,
+                };
+                attributes.properties.insert(atom, property);
+            }
+        }
+
+        let window = &mut self.windows[new.0];
+        window.attributes = attributes;
+        window.input_region = source.input_region.clone();
+        window.input_transparent = source.input_transparent;
+        window.event_mask = source.event_mask;
+        window.accepts_drops = source.accepts_drops;
+        window.focusable = source.focusable;
+        window.modal = source.modal;
+        window.border_width = source.border_width;
+        window.gravity = source.gravity;
+        window.save_under = source.save_under;
+        window.opacity = source.opacity;
+        window.shape = source.shape.clone();
+
+        if let Some(&owner) = from.transients.get(&old).and_then(|owner| keys.get(owner)) {
+            self.transients.insert(new, owner);
+        }
+        if from.input_margins.contains(&old) {
+            self.input_margins.insert(new);
+        }
+        if source.layer != window.layer {
+            self.set_layer(new, source.layer);
+        }
+        if !source.mapped {
+            self.unmap(new);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Layer, Rectangle, WindowTable};
+    use alloc::vec::Vec;
+
+    #[test]
+    fn detach() {
+        let mut window_table = WindowTable::new();
+
+        let root = window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let panel = window_table.insert(Rectangle::new(10, 10, 50, 50)).unwrap();
+        let button = window_table.insert(Rectangle::new(20, 20, 30, 30)).unwrap();
+        let hidden = window_table.insert(Rectangle::new(35, 35, 45, 45)).unwrap();
+        let other = window_table.insert(Rectangle::new(60, 60, 90, 90)).unwrap();
+        window_table.set_name(button, "button");
+        window_table.unmap(hidden);
+        window_table.set_layer(button, Layer::Overlay);
+
+        let (detached, keys) = window_table.detach(panel);
+
+        assert_eq!(window_table.len(), 2);
+        assert!(window_table.contains_key(root));
+        assert!(window_table.contains_key(other));
+        assert!(!window_table.contains_key(panel));
+        window_table.validate().unwrap();
+
+        assert_eq!(detached.len(), 3);
+        assert_eq!(keys.len(), 3);
+        assert_eq!(detached.root(), Some(keys[&panel]));
+        assert_eq!(detached.rect(keys[&panel]), Rectangle::new(10, 10, 50, 50));
+        assert_eq!(detached.rect(keys[&button]), Rectangle::new(20, 20, 30, 30));
+        assert_eq!(detached.parent(keys[&button]), Some(keys[&panel]));
+        assert_eq!(detached.name(keys[&button]), Some("button"));
+        assert_eq!(detached.layer(keys[&button]), Layer::Overlay);
+        assert!(!detached.is_mapped(keys[&hidden]));
+        detached.validate().unwrap();
+    }
+
+    #[test]
+    fn graft() {
+        let mut panel = WindowTable::new();
+        let panel_root = panel.insert(Rectangle::new(0, 0, 40, 40)).unwrap();
+        let button = panel.insert(Rectangle::new(10, 10, 20, 20)).unwrap();
+        panel.set_name(button, "button");
+
+        let mut window_table = WindowTable::new();
+        let root = window_table.insert(Rectangle::new(0, 0, 200, 200)).unwrap();
+        let host = window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let above = window_table
+            .insert(Rectangle::new(120, 120, 180, 180))
+            .unwrap();
+
+        let keys = window_table.graft(host, panel, (50, 50)).unwrap();
+
+        assert_eq!(window_table.len(), 5);
+        assert_eq!(
+            window_table.rect(keys[&panel_root]),
+            Rectangle::new(50, 50, 90, 90)
+        );
+        assert_eq!(
+            window_table.rect(keys[&button]),
+            Rectangle::new(60, 60, 70, 70)
+        );
+        assert_eq!(window_table.parent(keys[&panel_root]), Some(host));
+        assert_eq!(window_table.parent(keys[&button]), Some(keys[&panel_root]));
+        assert_eq!(window_table.name(keys[&button]), Some("button"));
+        assert_eq!(
+            window_table
+                .paint_order()
+                .map(|(key, _)| key)
+                .collect::<Vec<_>>(),
+            [root, host, keys[&panel_root], keys[&button], above]
+        );
+        window_table.validate().unwrap();
+
+        let (detached, _) = window_table.detach(keys[&panel_root]);
+        assert!(window_table.graft(host, detached, (500, 500)).is_err());
+        assert_eq!(window_table.len(), 3);
+    }
+
+    #[test]
+    fn duplicate_subtree() {
+        let mut window_table = WindowTable::new();
+
+        window_table.insert(Rectangle::new(0, 0, 200, 100)).unwrap();
+        let panel = window_table.insert(Rectangle::new(10, 10, 50, 50)).unwrap();
+        let button = window_table.insert(Rectangle::new(20, 20, 30, 30)).unwrap();
+        window_table.set_name(button, "button");
+
+        let copy = window_table.duplicate_subtree(panel, (100, 0)).unwrap();
+
+        assert_eq!(window_table.len(), 5);
+        assert_eq!(window_table.rect(copy), Rectangle::new(110, 10, 150, 50));
+        let children = window_table.children(copy).collect::<Vec<_>>();
+        assert_eq!(children.len(), 1);
+        assert_eq!(
+            window_table.rect(children[0]),
+            Rectangle::new(120, 20, 130, 30)
+        );
+        assert_eq!(window_table.name(children[0]), Some("button"));
+        assert_eq!(window_table.rect(button), Rectangle::new(20, 20, 30, 30));
+        window_table.validate().unwrap();
+
+        assert!(window_table.duplicate_subtree(panel, (500, 0)).is_err());
+        assert_eq!(window_table.len(), 5);
+    }
+}
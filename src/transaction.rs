@@ -0,0 +1,1090 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later OR MPL-2.0
+// This file is a part of `windowless`.
+//
+// `windowless` is free software: you can redistribute it and/or modify it under the terms of
+// either:
+//
+// * GNU Lesser General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+// * Mozilla Public License as published by the Mozilla Foundation, version 2.
+//
+// `windowless` is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+// See the GNU Lesser General Public License or the Mozilla Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License and the Mozilla
+// Public License along with `windowless`. If not, see <https://www.gnu.org/licenses/> or
+// <https://www.mozilla.org/en-US/MPL/2.0/>.
+
+//! Batched changes to the window table.
+
+use crate::{
+    CirculateDirection, ConfigureError, Coordinate, Key, Layer, Rectangle, Region, WindowKey,
+    WindowTable,
+};
+use alloc::vec::Vec;
+use slotmap::SecondaryMap;
+use smallvec::SmallVec;
+
+/// A batch of changes to a window table.
+///
+/// Created by [`WindowTable::transaction`]. Changes are queued up and then applied all at once
+/// by [`Transaction::commit`], which only has to rebuild the links between windows once.
+/// Dropping the transaction without committing it discards the changes.
+#[derive(Debug)]
+pub struct Transaction<'a, T: Coordinate = i32, const N: usize = 3, const R: usize = 4> {
+    /// The table being changed.
+    table: &'a mut WindowTable<T, N, R>,
+
+    /// The queued changes.
+    ops: Vec<Op<T>>,
+}
+
+/// A change to the rectangle or stacking position of a window.
+///
+/// Returned by [`WindowTable::take_configure_events`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct ConfigureEvent<T = i32> {
+    /// The window that changed.
+    pub window: WindowKey,
+
+    /// The rectangle of the window before the change.
+    pub old: Rectangle<T>,
+
+    /// The rectangle of the window after the change.
+    pub new: Rectangle<T>,
+
+    /// The window stacked directly beneath this one after the change.
+    ///
+    /// This is `None` if the window is at the bottom of the stack.
+    pub above: Option<WindowKey>,
+
+    /// Why the window changed.
+    pub cause: ConfigureCause,
+}
+
+/// The reason a window was configured.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ConfigureCause {
+    /// The rectangle of the window was changed directly.
+    Explicit,
+
+    /// The window was moved because its primary parent was moved or resized, or because the
+    /// root window was resized out from under it.
+    Gravity,
+
+    /// The window was raised or lowered, on its own or along with an ancestor.
+    Restack,
+}
+
+/// A queued change.
+#[derive(Debug, Copy, Clone)]
+enum Op<T: Coordinate> {
+    /// Change the rectangle of a window.
+    Configure(WindowKey, Rectangle<T>),
+
+    /// Change the rectangle of a window, leaving its children where they are.
+    Place(WindowKey, Rectangle<T>),
+
+    /// Raise a window to the top of the stack.
+    Raise(WindowKey),
+
+    /// Lower a window to the bottom of the stack.
+    Lower(WindowKey),
+
+    /// Restack one of the children of a window.
+    Circulate(WindowKey, CirculateDirection),
+
+    /// Map or unmap a window.
+    SetMapped(WindowKey, bool),
+
+    /// Remove a window.
+    Remove(WindowKey),
+
+    /// Move a window into a layer of the stacking order.
+    SetLayer(WindowKey, Layer),
+
+    /// Give a window a position within its layer.
+    SetZIndex(WindowKey, i32),
+
+    /// Restack a window directly above another window.
+    PlaceAbove(WindowKey, WindowKey),
+}
+
+impl<'a, T: Coordinate, const N: usize, const R: usize> Transaction<'a, T, N, R> {
+    /// Creates a new, empty transaction.
+    pub(crate) fn new(table: &'a mut WindowTable<T, N, R>) -> Self {
+        Self {
+            table,
+            ops: Vec::new(),
+        }
+    }
+
+    /// Queue a change to the rectangle of a window.
+    ///
+    /// The rectangle is constrained by the window's [`SizeHints`](crate::SizeHints).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn resize_window(
+        &mut self,
+        key: WindowKey,
+        rect: Rectangle<T>,
+    ) -> Result<&mut Self, ConfigureError> {
+        self.configure(key, rect)?;
+        Ok(self)
+    }
+
+    /// Queue a change to the rectangle of a window, returning the constrained rectangle.
+    pub(crate) fn configure(
+        &mut self,
+        key: WindowKey,
+        rect: Rectangle<T>,
+    ) -> Result<Rectangle<T>, ConfigureError> {
+        let rect = self.table.check_configure(key, rect)?;
+        self.ops.push(Op::Configure(key, rect));
+        Ok(rect)
+    }
+
+    /// Queue moving a window to exactly the given rectangle, without applying size hints or
+    /// moving its children.
+    pub(crate) fn place(&mut self, key: WindowKey, rect: Rectangle<T>) -> &mut Self {
+        self.push(key, Op::Place(key, rect))
+    }
+
+    /// Queue raising a window, along with its descendants, to the top of the stack.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn raise(&mut self, key: WindowKey) -> &mut Self {
+        self.push(key, Op::Raise(key))
+    }
+
+    /// Queue lowering a window, along with its descendants, to the bottom of the stack.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn lower(&mut self, key: WindowKey) -> &mut Self {
+        self.push(key, Op::Lower(key))
+    }
+
+    /// Queue circulating the children of a window.
+    ///
+    /// See [`WindowTable::circulate`] for more information.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn circulate(&mut self, parent: WindowKey, direction: CirculateDirection) -> &mut Self {
+        self.push(parent, Op::Circulate(parent, direction))
+    }
+
+    /// Queue moving a window into a layer of the stacking order.
+    ///
+    /// See [`WindowTable::set_layer`] for more information.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn set_layer(&mut self, key: WindowKey, layer: Layer) -> &mut Self {
+        self.push(key, Op::SetLayer(key, layer))
+    }
+
+    /// Queue giving a window a z-index within its layer.
+    ///
+    /// See [`WindowTable::set_z_index`] for more information.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn set_z_index(&mut self, key: WindowKey, z_index: i32) -> &mut Self {
+        self.push(key, Op::SetZIndex(key, z_index))
+    }
+
+    /// Queue restacking a window, along with its descendants, directly above another window.
+    ///
+    /// The change is skipped if the other window has been removed, or is a descendant of the
+    /// window.
+    pub(crate) fn place_above(&mut self, key: WindowKey, below: WindowKey) -> &mut Self {
+        self.push(key, Op::PlaceAbove(key, below))
+    }
+
+    /// Queue mapping a window.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn map(&mut self, key: WindowKey) -> &mut Self {
+        self.push(key, Op::SetMapped(key, true))
+    }
+
+    /// Queue unmapping a window.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn unmap(&mut self, key: WindowKey) -> &mut Self {
+        self.push(key, Op::SetMapped(key, false))
+    }
+
+    /// Queue removing a window.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn remove(&mut self, key: WindowKey) -> &mut Self {
+        self.push(key, Op::Remove(key))
+    }
+
+    /// Queue a change after checking that the window exists.
+    fn push(&mut self, key: WindowKey, op: Op<T>) -> &mut Self {
+        assert!(
+            self.table.windows.contains_key(key.0),
+            "window does not exist"
+        );
+        self.ops.push(op);
+        self
+    }
+
+    /// Apply the queued changes to the table.
+    ///
+    /// Returns the region of the screen that was damaged by the changes, which is also added to
+    /// the damage accumulated by the table. Changes to windows that were removed earlier in the
+    /// transaction are ignored.
+    pub fn commit(self) -> Region<T, R> {
+        let Self { table, ops } = self;
+
+        let mut damage = Region::default();
+        let mut order = table.stacking_order();
+
+        // Remember what the windows near the changes look like, to find out what gets exposed.
+        let snapshot = Visibility::new(table, &order, &ops);
+
+        // Whether the links between windows are out of date.
+        let mut dirty = false;
+
+        // The windows that were configured, along with their old rectangles.
+        let mut configured = Vec::new();
+
+        for op in ops {
+            let key = match op {
+                Op::Configure(key, _)
+                | Op::Place(key, _)
+                | Op::Raise(key)
+                | Op::Lower(key)
+                | Op::Circulate(key, _)
+                | Op::SetMapped(key, _)
+                | Op::Remove(key)
+                | Op::SetLayer(key, _)
+                | Op::SetZIndex(key, _)
+                | Op::PlaceAbove(key, _) => key,
+            };
+
+            if !table.windows.contains_key(key.0) {
+                continue;
+            }
+
+            match op {
+                Op::Configure(key, rect) => {
+                    configure(
+                        table,
+                        key,
+                        rect,
+                        &mut damage,
+                        ConfigureCause::Explicit,
+                        &mut configured,
+                    );
+                    dirty = true;
+                }
+
+                Op::Place(key, rect) => {
+                    set_rect(
+                        table,
+                        key,
+                        rect,
+                        &mut damage,
+                        ConfigureCause::Explicit,
+                        &mut configured,
+                    );
+                    dirty = true;
+                }
+
+                Op::Raise(_) | Op::Lower(_) | Op::Circulate(..) | Op::PlaceAbove(..) => {
+                    if dirty {
+                        table.relink(&order);
+                    }
+
+                    let key = match op {
+                        Op::Circulate(parent, direction) => {
+                            match table.circulate_target(parent, direction) {
+                                Some(child) => child,
+                                None => continue,
+                            }
+                        }
+                        _ => key,
+                    };
+                    if table.root == Some(key) || table.background == Some(key) {
+                        continue;
+                    }
+
+                    let subtree = table.subtree_in(&order, key);
+                    if let Op::PlaceAbove(_, below) = op {
+                        if !table.windows.contains_key(below.0) || subtree.contains(&below) {
+                            continue;
+                        }
+                    }
+
+                    for &window in &subtree {
+                        let rect = table.windows[window.0].rect;
+                        if table.is_viewable(window) {
+                            damage.union_rect(rect);
+                        }
+                        configured.push((window, rect, ConfigureCause::Restack));
+                    }
+
+                    table.observers.notify(|observer| observer.on_restack(key));
+                    order.retain(|window| !subtree.contains(window));
+                    let position = match op {
+                        Op::Raise(_) => order.len(),
+                        Op::Circulate(parent, CirculateDirection::RaiseLowest) => {
+                            // The top of the parent's subtree.
+                            let last = *table.subtree_in(&order, parent).last().unwrap();
+                            order.iter().position(|&window| window == last).unwrap() + 1
+                        }
+                        Op::Circulate(parent, CirculateDirection::LowerHighest) => {
+                            order.iter().position(|&window| window == parent).unwrap() + 1
+                        }
+                        Op::PlaceAbove(_, below) => {
+                            order.iter().position(|&window| window == below).unwrap() + 1
+                        }
+                        _ => 1,
+                    };
+                    order.splice(position..position, subtree);
+                    table.sort_layers(&mut order);
+                    dirty = true;
+                }
+
+                Op::SetLayer(key, _) | Op::SetZIndex(key, _) => {
+                    let unchanged = match op {
+                        Op::SetLayer(_, layer) => table.windows[key.0].layer == layer,
+                        Op::SetZIndex(_, z_index) => table.z_index(key) == z_index,
+                        _ => unreachable!(),
+                    };
+                    if unchanged {
+                        continue;
+                    }
+
+                    if dirty {
+                        table.relink(&order);
+                    }
+
+                    // The window's descendants may end up on either side of it.
+                    for window in table.subtree_in(&order, key) {
+                        if table.is_viewable(window) {
+                            damage.union_rect(table.windows[window.0].visual_rect());
+                        }
+                    }
+                    configured.push((key, table.windows[key.0].rect, ConfigureCause::Restack));
+
+                    match op {
+                        Op::SetLayer(_, layer) => table.windows[key.0].layer = layer,
+                        Op::SetZIndex(_, z_index) => {
+                            table.windows[key.0].attributes_mut().z_index = z_index
+                        }
+                        _ => unreachable!(),
+                    }
+                    table.observers.notify(|observer| observer.on_restack(key));
+                    table.sort_layers(&mut order);
+                    dirty = true;
+                }
+
+                Op::SetMapped(key, mapped) => {
+                    if table.windows[key.0].mapped == mapped {
+                        continue;
+                    }
+
+                    if dirty {
+                        table.relink(&order);
+                        dirty = false;
+                    }
+
+                    // Hide or show the window, then damage whatever is now viewable.
+                    table.windows[key.0].mapped = true;
+                    for window in table.subtree_in(&order, key) {
+                        if table.is_viewable(window) {
+                            damage.union_rect(table.windows[window.0].visual_rect());
+                        }
+                    }
+                    table.windows[key.0].mapped = mapped;
+                }
+
+                Op::Remove(key) => {
+                    if table.root == Some(key) {
+                        damage.union_rect(table.windows[key.0].visual_rect());
+                        table.clear();
+                        table.add_damage(&damage);
+                        return damage;
+                    }
+
+                    let window = table.windows.remove(key.0).unwrap();
+                    table.transients.remove(&key);
+                    table.transients.retain(|_, owner| *owner != key);
+                    if table.background == Some(key) {
+                        table.background = None;
+                    }
+                    table.input_margins.remove(&key);
+                    table
+                        .configure_requests
+                        .retain(|request| request.window != key);
+                    table.index.remove(key, window.rect);
+                    table
+                        .observers
+                        .notify(|observer| observer.on_remove(key, window.rect));
+                    if window.mapped {
+                        damage.union_rect(window.visual_rect());
+                    }
+
+                    // Removed windows are skipped when relinking, so the order doesn't have to
+                    // be searched for them.
+                    dirty = true;
+                }
+            }
+        }
+
+        if dirty {
+            table.relink(&order);
+        }
+
+        if !configured.is_empty() {
+            report(table, configured);
+        }
+
+        snapshot.finish(table, &mut damage);
+        table.add_damage(&damage);
+        damage
+    }
+}
+
+/// Change the rectangle of the root window.
+///
+/// The root's children are moved according to their gravity. Windows that no longer overlap the
+/// root afterwards are moved back onto it, along with their children, and returned.
+pub(crate) fn resize_root<T: Coordinate, const N: usize, const R: usize>(
+    table: &mut WindowTable<T, N, R>,
+    rect: Rectangle<T>,
+) -> Vec<WindowKey> {
+    let root = table.root.unwrap();
+    let order = table.stacking_order();
+    let snapshot = Visibility::new(table, &order, &[Op::Configure(root, rect)]);
+
+    let mut damage = Region::default();
+    let mut configured = Vec::new();
+    configure(
+        table,
+        root,
+        rect,
+        &mut damage,
+        ConfigureCause::Explicit,
+        &mut configured,
+    );
+
+    let mut outside = Vec::new();
+    for &key in &order[1..] {
+        let old = table.windows[key.0].rect;
+        if old.intersects(rect) {
+            continue;
+        }
+
+        // Move the window back across the edge of the root it fell past, until its own edge on
+        // that side lines up with it.
+        let offset = |low: T, high: T, start: T, end: T| {
+            if high <= start {
+                start.saturating_sub(low)
+            } else if low >= end {
+                end.saturating_sub(high)
+            } else {
+                T::ZERO
+            }
+        };
+        let dx = offset(old.left, old.right, rect.left, rect.right);
+        let dy = offset(old.top, old.bottom, rect.top, rect.bottom);
+        let mut moved = old.translate(dx, dy);
+
+        // If the window couldn't be moved back onto the root, put it in the root's top-left
+        // corner instead.
+        if moved.is_empty() || !moved.intersects(rect) {
+            moved = Rectangle::from_origin_size(rect.origin(), old.size());
+        }
+        if moved.is_empty() || !moved.intersects(rect) {
+            moved = rect;
+        }
+
+        configure(
+            table,
+            key,
+            moved,
+            &mut damage,
+            ConfigureCause::Gravity,
+            &mut configured,
+        );
+        outside.push(key);
+    }
+
+    table.relink(&order);
+    if !configured.is_empty() {
+        report(table, configured);
+    }
+
+    snapshot.finish(table, &mut damage);
+    table.add_damage(&damage);
+    outside
+}
+
+/// Change the rectangle of a window, moving its children according to their gravity.
+///
+/// The links between windows are not updated. Children are found using the links as they were
+/// before the transaction began.
+fn configure<T: Coordinate, const N: usize, const R: usize>(
+    table: &mut WindowTable<T, N, R>,
+    key: WindowKey,
+    rect: Rectangle<T>,
+    damage: &mut Region<T, R>,
+    cause: ConfigureCause,
+    configured: &mut Vec<(WindowKey, Rectangle<T>, ConfigureCause)>,
+) {
+    let mut pending = SmallVec::<[(WindowKey, Rectangle<T>, ConfigureCause); 4]>::new();
+    pending.push((key, rect, cause));
+
+    while let Some((key, rect, cause)) = pending.pop() {
+        let old = set_rect(table, key, rect, damage, cause, configured);
+
+        let bounds = table.windows[table.root.unwrap().0].rect;
+        for child in table.children(key).collect::<SmallVec<[_; 4]>>() {
+            let window = &mut table.windows[child.0];
+
+            // Relative and anchored children are laid out against the new rectangle instead.
+            let laid_out = window.attributes().and_then(|attributes| {
+                match (attributes.relative, attributes.anchors) {
+                    (Some(relative), _) => Some(relative.resolve(rect)),
+                    (None, Some(anchors)) => {
+                        let followed = window.rect.translate(
+                            rect.left.saturating_sub(old.left),
+                            rect.top.saturating_sub(old.top),
+                        );
+                        Some(anchors.resolve(rect, followed))
+                    }
+                    (None, None) => None,
+                }
+            });
+            if let Some(moved) = laid_out {
+                if moved != window.rect && !moved.is_empty() && moved.intersects(bounds) {
+                    pending.push((child, moved, ConfigureCause::Gravity));
+                }
+                continue;
+            }
+
+            match window.gravity.offset(old, rect) {
+                Some((dx, dy)) if dx == T::ZERO && dy == T::ZERO => {}
+                Some((dx, dy)) => {
+                    // Children that would be pushed off the root window stay where they are.
+                    let moved = window.rect.translate(dx, dy);
+                    if !moved.is_empty() && moved.intersects(bounds) {
+                        pending.push((child, moved, ConfigureCause::Gravity));
+                    }
+                }
+                None => {
+                    if window.mapped {
+                        window.mapped = false;
+                        damage.union_rect(window.visual_rect());
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Change the rectangle of a single window, returning its old rectangle.
+fn set_rect<T: Coordinate, const N: usize, const R: usize>(
+    table: &mut WindowTable<T, N, R>,
+    key: WindowKey,
+    rect: Rectangle<T>,
+    damage: &mut Region<T, R>,
+    cause: ConfigureCause,
+    configured: &mut Vec<(WindowKey, Rectangle<T>, ConfigureCause)>,
+) -> Rectangle<T> {
+    let window = &mut table.windows[key.0];
+    if window.mapped {
+        damage.union_rect(window.visual_rect());
+    }
+    let old = core::mem::replace(&mut window.rect, rect);
+    if window.mapped {
+        damage.union_rect(window.visual_rect());
+    }
+
+    table.index.remove(key, old);
+    table.index.insert(key, rect);
+    if old != rect {
+        table
+            .observers
+            .notify(|observer| observer.on_geometry_change(key, old, rect));
+        configured.push((key, old, cause));
+    }
+
+    old
+}
+
+/// Queue up events for the windows that were configured.
+fn report<T: Coordinate, const N: usize, const R: usize>(
+    table: &mut WindowTable<T, N, R>,
+    configured: Vec<(WindowKey, Rectangle<T>, ConfigureCause)>,
+) {
+    let order = table.stacking_order();
+
+    for (window, old, cause) in configured {
+        let new = match table.windows.get_mut(window.0) {
+            Some(window) => {
+                if old.size() != window.rect.size() {
+                    window.contents_valid = false;
+                }
+
+                window.rect
+            }
+            None => continue,
+        };
+
+        let z = table.windows[window.0].z;
+        table.configure_events.push(ConfigureEvent {
+            window,
+            old,
+            new,
+            above: z.checked_sub(1).map(|z| order[z]),
+            cause,
+        });
+    }
+}
+
+/// The visible parts of the windows around a set of changes.
+#[derive(Debug)]
+struct Visibility<T: Coordinate, const R: usize> {
+    /// The area affected by the changes themselves.
+    area: Region<T, R>,
+
+    /// The state of every window before the changes.
+    windows: SecondaryMap<Key, State<T, R>>,
+
+    /// The rectangle of the root window before the changes.
+    root: Option<Rectangle<T>>,
+
+    /// The visible part of each save-under window that may be hidden.
+    saved: SmallVec<[(WindowKey, Region<T, R>); 1]>,
+}
+
+/// The state of a window that decides which parts of it are visible.
+#[derive(Debug)]
+struct State<T: Coordinate, const R: usize> {
+    /// The rectangle of the window.
+    rect: Rectangle<T>,
+
+    /// The position of the window in the stacking order.
+    z: usize,
+
+    /// Whether the window is viewable.
+    viewable: bool,
+
+    /// The effective opacity of the window, or zero if it isn't viewable.
+    opacity: u8,
+
+    /// The shape of the window, relative to its origin.
+    shape: Option<Region<T, R>>,
+}
+
+impl<T: Coordinate, const R: usize> State<T, R> {
+    /// Tell if this window hides the windows beneath it.
+    fn occludes(&self) -> bool {
+        self.viewable && self.opacity == u8::MAX
+    }
+
+    /// Get the part of the screen covered by the shape of this window.
+    fn bounding_region(&self) -> Region<T, R> {
+        match &self.shape {
+            Some(shape) => {
+                let mut region = shape.clone();
+                region.translate(self.rect.left, self.rect.top);
+                region.intersect_rect(self.rect);
+                region
+            }
+            None => core::iter::once(self.rect).collect(),
+        }
+    }
+}
+
+impl<T: Coordinate, const R: usize> Visibility<T, R> {
+    /// Take a snapshot of the windows before these changes.
+    fn new<const N: usize>(
+        table: &WindowTable<T, N, R>,
+        order: &[WindowKey],
+        ops: &[Op<T>],
+    ) -> Self {
+        let mut area = Region::default();
+        let mut saved = SmallVec::new();
+
+        for op in ops {
+            match *op {
+                Op::SetMapped(key, false) | Op::Remove(key)
+                    if table.windows[key.0].save_under && table.is_viewable(key) =>
+                {
+                    saved.push((key, table.visible_region(key)));
+                    for window in table.subtree_in(order, key) {
+                        area.union_rect(table.windows[window.0].rect);
+                    }
+                }
+
+                Op::Configure(key, rect) => {
+                    // Children may be moved along with the window.
+                    for window in table.subtree_in(order, key) {
+                        area.union_rect(table.windows[window.0].rect);
+                    }
+                    area.union_rect(rect);
+                }
+
+                Op::Place(key, rect) => {
+                    area.union_rect(table.windows[key.0].rect);
+                    area.union_rect(rect);
+                }
+
+                Op::Remove(key) => {
+                    // Windows shown or hidden by relinking are found after the changes.
+                    area.union_rect(table.windows[key.0].rect);
+                }
+
+                Op::Raise(key)
+                | Op::Lower(key)
+                | Op::Circulate(key, _)
+                | Op::SetMapped(key, _)
+                | Op::SetLayer(key, _)
+                | Op::SetZIndex(key, _)
+                | Op::PlaceAbove(key, _) => {
+                    for window in table.subtree_in(order, key) {
+                        area.union_rect(table.windows[window.0].rect);
+                    }
+                }
+            }
+        }
+
+        Self {
+            area,
+            windows: states(table),
+            root: table.root.map(|root| table.windows[root.0].rect),
+            saved,
+        }
+    }
+
+    /// Damage the windows that were shown, hidden or faded since the snapshot was taken, then
+    /// record the parts of the windows that have become visible.
+    ///
+    /// Relinking can give any window a different primary parent, not just the windows that were
+    /// changed, and a window is only viewable if its primary parent is. Parts uncovered by
+    /// hiding a save-under window are restored from its saved contents instead of being
+    /// exposed.
+    fn finish<const N: usize>(self, table: &mut WindowTable<T, N, R>, damage: &mut Region<T, R>) {
+        let Self {
+            mut area,
+            windows,
+            root,
+            saved,
+        } = self;
+
+        // Find the windows that changed, wherever they are.
+        let after = states(table);
+        for (key, old) in &windows {
+            let new = after.get(key);
+            let changed = match new {
+                Some(new) => {
+                    if (old.viewable, old.opacity) != (new.viewable, new.opacity) {
+                        damage.union_rect(table.windows[key].visual_rect());
+                    }
+                    (old.rect, old.viewable, old.opacity) != (new.rect, new.viewable, new.opacity)
+                }
+                None => true,
+            };
+
+            if changed {
+                if old.viewable {
+                    area.union_rect(old.rect);
+                }
+                if let Some(new) = new.filter(|new| new.viewable) {
+                    area.union_rect(new.rect);
+                }
+            }
+        }
+
+        let mut restored = Region::default();
+        for (key, region) in saved {
+            if !table.is_viewable(key) {
+                restored.union(&region);
+                table.save_unders.push((key, region));
+            }
+        }
+
+        let bounds = match area.bounds() {
+            Some(bounds) => bounds,
+            None => return,
+        };
+
+        // The windows that hid parts of the area before the changes.
+        let occluders = windows
+            .iter()
+            .filter(|(_, state)| state.occludes() && state.rect.intersects(bounds))
+            .map(|(_, state)| (state.z, state.bounding_region()))
+            .collect::<Vec<_>>();
+
+        for key in table.index.query_rect(bounds) {
+            let rect = table.windows[key.0].rect;
+            let mut exposed = Self::visible(table, &area, key);
+
+            if let Some(old) = windows.get(key.0).filter(|old| old.viewable) {
+                let mut before = old.bounding_region();
+                if let Some(root) = root {
+                    before.intersect_rect(root);
+                }
+                before.intersect(&area);
+                for (z, region) in &occluders {
+                    if *z > old.z {
+                        before.subtract(region);
+                    }
+                }
+
+                before.relative_to(old.rect.left, old.rect.top);
+                exposed.subtract(&before);
+            }
+
+            if !restored.is_empty() {
+                let mut restored = restored.clone();
+                restored.relative_to(rect.left, rect.top);
+                exposed.subtract(&restored);
+            }
+
+            table.expose(key, &exposed);
+        }
+    }
+
+    /// Get the visible part of a window in the area, relative to its origin.
+    fn visible<const N: usize>(
+        table: &WindowTable<T, N, R>,
+        area: &Region<T, R>,
+        key: WindowKey,
+    ) -> Region<T, R> {
+        let rect = table.windows[key.0].rect;
+        let mut visible = table.visible_region(key);
+        visible.intersect(area);
+        visible.relative_to(rect.left, rect.top);
+        visible
+    }
+}
+
+/// Find the state of every window that decides which parts of it are visible.
+fn states<T: Coordinate, const N: usize, const R: usize>(
+    table: &WindowTable<T, N, R>,
+) -> SecondaryMap<Key, State<T, R>> {
+    // Primary parents come before their children in the stacking order, like in `paint_order`.
+    let mut states = SecondaryMap::<Key, State<T, R>>::with_capacity(table.windows.len());
+    for &key in &table.order {
+        let window = &table.windows[key.0];
+        let (viewable, opacity) = match window.parents.first() {
+            Some(parent) => (states[parent.0].viewable, states[parent.0].opacity),
+            None => (table.root == Some(key), u8::MAX),
+        };
+
+        let viewable = viewable && window.mapped;
+        states.insert(
+            key.0,
+            State {
+                rect: window.rect,
+                z: window.z,
+                viewable,
+                opacity: if viewable {
+                    (u32::from(opacity) * u32::from(window.opacity) / 255) as u8
+                } else {
+                    0
+                },
+                shape: window.shape.clone(),
+            },
+        );
+    }
+
+    states
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ConfigureCause, ConfigureError, ConfigureEvent, Rectangle, WindowTable};
+
+    #[test]
+    fn batch_damage() {
+        let mut window_table = WindowTable::new();
+
+        let root = window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let a = window_table.insert(Rectangle::new(0, 0, 10, 10)).unwrap();
+        let b = window_table.insert(Rectangle::new(50, 50, 60, 60)).unwrap();
+
+        let mut transaction = window_table.transaction();
+        transaction
+            .resize_window(a, Rectangle::new(10, 0, 20, 10))
+            .unwrap()
+            .unmap(b);
+        assert_eq!(
+            transaction
+                .resize_window(root, Rectangle::new(0, 0, 5, 5))
+                .unwrap_err(),
+            ConfigureError::RootWindow
+        );
+        let damage = transaction.commit();
+
+        assert_eq!(damage.area(), 300);
+        assert_eq!(damage.bounds(), Some(Rectangle::new(0, 0, 60, 60)));
+        assert_eq!(window_table.rect(a), Rectangle::new(10, 0, 20, 10));
+        assert!(!window_table.is_mapped(b));
+    }
+
+    #[test]
+    fn configure_events() {
+        let mut window_table = WindowTable::new();
+
+        let root = window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let a = window_table.insert(Rectangle::new(0, 0, 50, 50)).unwrap();
+        let b = window_table.insert(Rectangle::new(10, 10, 20, 20)).unwrap();
+        let c = window_table.insert(Rectangle::new(60, 60, 70, 70)).unwrap();
+
+        window_table.move_window(a, (5, 5)).unwrap();
+        let events = window_table.take_configure_events();
+        assert_eq!(
+            events,
+            [
+                ConfigureEvent {
+                    window: a,
+                    old: Rectangle::new(0, 0, 50, 50),
+                    new: Rectangle::new(5, 5, 55, 55),
+                    above: Some(root),
+                    cause: ConfigureCause::Explicit,
+                },
+                ConfigureEvent {
+                    window: b,
+                    old: Rectangle::new(10, 10, 20, 20),
+                    new: Rectangle::new(15, 15, 25, 25),
+                    above: Some(a),
+                    cause: ConfigureCause::Gravity,
+                },
+            ]
+        );
+
+        window_table.lower(c);
+        let events = window_table.take_configure_events();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].above, Some(root));
+        assert_eq!(events[0].cause, ConfigureCause::Restack);
+    }
+
+    #[test]
+    fn relink_hides_other_windows() {
+        let mut window_table = WindowTable::new();
+
+        window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let w = window_table.insert(Rectangle::new(0, 22, 40, 40)).unwrap();
+        let u = window_table.insert(Rectangle::new(0, 0, 40, 22)).unwrap();
+        window_table.unmap(u);
+        let a = window_table.insert(Rectangle::new(0, 0, 40, 12)).unwrap();
+        let b = window_table.insert(Rectangle::new(0, 0, 40, 40)).unwrap();
+        assert_eq!(window_table.parent(b), Some(w));
+        assert!(window_table.is_viewable(b));
+        window_table.take_damage();
+
+        // Lowering `a` uncovers more of the unmapped window beneath `b`, which makes it `b`'s
+        // primary parent and hides `b`, even though `b` wasn't restacked.
+        window_table.lower(a);
+        assert_eq!(window_table.parent(b), Some(u));
+        assert!(!window_table.is_viewable(b));
+        assert!(window_table.take_damage().contains((5, 30)));
+    }
+
+    #[test]
+    fn relink_exposes_other_windows() {
+        let mut window_table = WindowTable::new();
+
+        window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let w = window_table.insert(Rectangle::new(0, 22, 40, 40)).unwrap();
+        let u = window_table.insert(Rectangle::new(0, 0, 40, 22)).unwrap();
+        window_table.unmap(u);
+        let a = window_table.insert(Rectangle::new(0, 0, 40, 12)).unwrap();
+        window_table.insert(Rectangle::new(0, 0, 40, 40)).unwrap();
+        window_table.take_exposures();
+
+        // Hiding the window on top of `w` exposes `w`.
+        window_table.lower(a);
+        let exposures = window_table.take_exposures();
+        let (_, exposed) = exposures.iter().find(|(key, _)| *key == w).unwrap();
+        assert_eq!(exposed.bounds(), Some(Rectangle::new(0, 0, 40, 18)));
+
+        // Removing an unmapped window shows the windows that were stacked on it.
+        let mut window_table = WindowTable::new();
+
+        window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let u = window_table.insert(Rectangle::new(10, 10, 50, 50)).unwrap();
+        let c = window_table.insert(Rectangle::new(20, 20, 30, 30)).unwrap();
+        window_table.unmap(u);
+        window_table.take_damage();
+        window_table.take_exposures();
+
+        window_table.remove(u);
+        assert!(window_table.is_viewable(c));
+        assert!(window_table.take_damage().contains((25, 25)));
+        let exposures = window_table.take_exposures();
+        let (_, exposed) = exposures.iter().find(|(key, _)| *key == c).unwrap();
+        assert_eq!(exposed.area(), 100);
+    }
+
+    #[test]
+    fn discard() {
+        let mut window_table = WindowTable::new();
+
+        window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let a = window_table.insert(Rectangle::new(0, 0, 10, 10)).unwrap();
+
+        window_table.transaction().remove(a);
+        assert_eq!(window_table.rect(a), Rectangle::new(0, 0, 10, 10));
+    }
+
+    #[test]
+    fn gravity_off_root() {
+        let mut window_table = WindowTable::new();
+
+        window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let a = window_table.insert(Rectangle::new(0, 0, 50, 50)).unwrap();
+        let b = window_table.insert(Rectangle::new(5, 5, 10, 10)).unwrap();
+        let c = window_table.insert(Rectangle::new(40, 40, 45, 45)).unwrap();
+
+        // `b` would end up outside of the root window, so it stays put.
+        window_table.move_window(a, (-40, -40)).unwrap();
+        assert_eq!(window_table.rect(b), Rectangle::new(5, 5, 10, 10));
+        assert_eq!(window_table.rect(c), Rectangle::new(0, 0, 5, 5));
+        assert_eq!(window_table.validate(), Ok(()));
+
+        // Children pushed against the edge of the coordinate space keep their size.
+        let mut window_table = WindowTable::new();
+
+        window_table
+            .insert(Rectangle::new(0, 0, i32::MAX, 100))
+            .unwrap();
+        let a = window_table.insert(Rectangle::new(0, 0, 50, 50)).unwrap();
+        let b = window_table.insert(Rectangle::new(30, 5, 40, 10)).unwrap();
+
+        window_table.move_window(a, (i32::MAX - 20, 0)).unwrap();
+        assert_eq!(
+            window_table.rect(b),
+            Rectangle::new(i32::MAX - 10, 5, i32::MAX, 10)
+        );
+        assert_eq!(window_table.validate(), Ok(()));
+    }
+}
@@ -0,0 +1,203 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later OR MPL-2.0
+// This file is a part of `windowless`.
+//
+// `windowless` is free software: you can redistribute it and/or modify it under the terms of
+// either:
+//
+// * GNU Lesser General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+// * Mozilla Public License as published by the Mozilla Foundation, version 2.
+//
+// `windowless` is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+// See the GNU Lesser General Public License or the Mozilla Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License and the Mozilla
+// Public License along with `windowless`. If not, see <https://www.gnu.org/licenses/> or
+// <https://www.mozilla.org/en-US/MPL/2.0/>.
+
+//! Rotating and flipping the output.
+
+use crate::{Coordinate, Point, Rectangle, Region};
+
+/// How the contents of the root window are rotated and flipped on the output.
+///
+/// Rotations are clockwise. In the flipped variants, the contents are mirrored around the
+/// vertical axis before being rotated.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub enum Transform {
+    /// The contents are shown as they are.
+    #[default]
+    Normal,
+
+    /// The contents are rotated by 90 degrees.
+    Rotate90,
+
+    /// The contents are rotated by 180 degrees.
+    Rotate180,
+
+    /// The contents are rotated by 270 degrees.
+    Rotate270,
+
+    /// The contents are flipped.
+    Flipped,
+
+    /// The contents are flipped, then rotated by 90 degrees.
+    Flipped90,
+
+    /// The contents are flipped, then rotated by 180 degrees.
+    Flipped180,
+
+    /// The contents are flipped, then rotated by 270 degrees.
+    Flipped270,
+}
+
+impl Transform {
+    /// Tell if this transform swaps the width and height of the output.
+    pub fn swaps_axes(self) -> bool {
+        self.quarter_turns() % 2 == 1
+    }
+
+    /// Returns whether the contents are flipped.
+    fn flipped(self) -> bool {
+        matches!(
+            self,
+            Transform::Flipped
+                | Transform::Flipped90
+                | Transform::Flipped180
+                | Transform::Flipped270
+        )
+    }
+
+    /// Returns the number of clockwise quarter turns.
+    fn quarter_turns(self) -> u8 {
+        match self {
+            Transform::Normal | Transform::Flipped => 0,
+            Transform::Rotate90 | Transform::Flipped90 => 1,
+            Transform::Rotate180 | Transform::Flipped180 => 2,
+            Transform::Rotate270 | Transform::Flipped270 => 3,
+        }
+    }
+
+    /// Transform a point in `bounds` into a point on the output.
+    ///
+    /// The output has the same top-left corner as `bounds`.
+    pub(crate) fn apply<T: Coordinate>(self, bounds: Rectangle<T>, point: Point<T>) -> Point<T> {
+        let (mut width, mut height) = (bounds.width(), bounds.height());
+        let mut x = point.x.saturating_sub(bounds.left);
+        let mut y = point.y.saturating_sub(bounds.top);
+
+        if self.flipped() {
+            x = width.saturating_sub(x);
+        }
+
+        for _ in 0..self.quarter_turns() {
+            let turned = height.saturating_sub(y);
+            y = x;
+            x = turned;
+            core::mem::swap(&mut width, &mut height);
+        }
+
+        Point::new(x.saturating_add(bounds.left), y.saturating_add(bounds.top))
+    }
+
+    /// Transform a point on the output back into a point in `bounds`.
+    pub(crate) fn invert<T: Coordinate>(self, bounds: Rectangle<T>, point: Point<T>) -> Point<T> {
+        let (mut width, mut height) = (bounds.width(), bounds.height());
+        if self.swaps_axes() {
+            core::mem::swap(&mut width, &mut height);
+        }
+
+        let mut x = point.x.saturating_sub(bounds.left);
+        let mut y = point.y.saturating_sub(bounds.top);
+
+        for _ in 0..self.quarter_turns() {
+            let turned = width.saturating_sub(x);
+            x = y;
+            y = turned;
+            core::mem::swap(&mut width, &mut height);
+        }
+
+        if self.flipped() {
+            x = width.saturating_sub(x);
+        }
+
+        Point::new(x.saturating_add(bounds.left), y.saturating_add(bounds.top))
+    }
+
+    /// Transform a rectangle in `bounds` into a rectangle on the output.
+    pub(crate) fn apply_rect<T: Coordinate>(
+        self,
+        bounds: Rectangle<T>,
+        rect: Rectangle<T>,
+    ) -> Rectangle<T> {
+        let a = self.apply(bounds, rect.origin());
+        let b = self.apply(bounds, Point::new(rect.right, rect.bottom));
+        Rectangle::new(a.x, a.y, b.x, b.y).normalize()
+    }
+
+    /// Transform a region in `bounds` into a region on the output.
+    pub(crate) fn apply_region<T: Coordinate, const R: usize>(
+        self,
+        bounds: Rectangle<T>,
+        region: &Region<T, R>,
+    ) -> Region<T, R> {
+        let mut transformed = Region::default();
+        for &rect in region.rects() {
+            transformed.union_rect(self.apply_rect(bounds, rect));
+        }
+        transformed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Transform;
+    use crate::{Point, Rectangle};
+
+    const ALL: [Transform; 8] = [
+        Transform::Normal,
+        Transform::Rotate90,
+        Transform::Rotate180,
+        Transform::Rotate270,
+        Transform::Flipped,
+        Transform::Flipped90,
+        Transform::Flipped180,
+        Transform::Flipped270,
+    ];
+
+    #[test]
+    fn round_trip() {
+        let bounds = Rectangle::new(10, 20, 110, 70);
+        let point = Point::new(15, 30);
+
+        for transform in ALL {
+            let output = transform.apply(bounds, point);
+            assert_eq!(transform.invert(bounds, output), point, "{:?}", transform);
+        }
+    }
+
+    #[test]
+    fn rotate() {
+        let bounds = Rectangle::new(0, 0, 100, 50);
+        let rect = Rectangle::new(0, 0, 10, 20);
+
+        assert_eq!(Transform::Normal.apply_rect(bounds, rect), rect);
+        assert_eq!(
+            Transform::Rotate90.apply_rect(bounds, rect),
+            Rectangle::new(30, 0, 50, 10)
+        );
+        assert_eq!(
+            Transform::Rotate180.apply_rect(bounds, rect),
+            Rectangle::new(90, 30, 100, 50)
+        );
+        assert_eq!(
+            Transform::Rotate270.apply_rect(bounds, rect),
+            Rectangle::new(0, 90, 20, 100)
+        );
+        assert_eq!(
+            Transform::Flipped.apply_rect(bounds, rect),
+            Rectangle::new(90, 0, 100, 20)
+        );
+    }
+}
@@ -0,0 +1,234 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later OR MPL-2.0
+// This file is a part of `windowless`.
+//
+// `windowless` is free software: you can redistribute it and/or modify it under the terms of
+// either:
+//
+// * GNU Lesser General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+// * Mozilla Public License as published by the Mozilla Foundation, version 2.
+//
+// `windowless` is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+// See the GNU Lesser General Public License or the Mozilla Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License and the Mozilla
+// Public License along with `windowless`. If not, see <https://www.gnu.org/licenses/> or
+// <https://www.mozilla.org/en-US/MPL/2.0/>.
+
+//! Transient windows, such as dialogs, which are kept above the windows that own them.
+
+use crate::{Coordinate, WindowKey, WindowTable};
+use alloc::vec::Vec;
+
+impl<T: Coordinate, const N: usize, const R: usize> WindowTable<T, N, R> {
+    /// Make a window transient for an owner, or pass `None` to stop it being transient.
+    ///
+    /// A transient window, such as a dialog, is always stacked above its owner and the owner's
+    /// descendants, unless it is in a lower [`Layer`](crate::Layer). Raising the owner raises its
+    /// transients with it. The owner and its transients form a group, which can be mapped,
+    /// unmapped and minimized together. Returns `false`, changing nothing, if the owner is the
+    /// window itself or is transient for it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either window does not exist.
+    pub fn set_transient_for(&mut self, key: WindowKey, owner: Option<WindowKey>) -> bool {
+        assert!(self.contains_key(key), "window does not exist");
+
+        let owner = match owner {
+            Some(owner) => owner,
+            None => {
+                self.transients.remove(&key);
+                return true;
+            }
+        };
+
+        assert!(self.contains_key(owner), "window does not exist");
+        let mut next = Some(owner);
+        while let Some(window) = next {
+            if window == key {
+                return false;
+            }
+            next = self.transient_for(window);
+        }
+
+        self.transients.insert(key, owner);
+
+        // Move the window above its owner, if it isn't already.
+        let order = self.stacking_order();
+        let top = *self.subtree_in(&order, owner).last().unwrap();
+        let position = |window| order.iter().position(|&other| other == window);
+        if self.windows[key.0].layer == self.windows[owner.0].layer && position(key) < position(top)
+        {
+            let mut transaction = self.transaction();
+            transaction.place_above(key, top);
+            transaction.commit();
+        }
+
+        true
+    }
+
+    /// Returns the window that a window is transient for, if any.
+    pub fn transient_for(&self, key: WindowKey) -> Option<WindowKey> {
+        self.transients.get(&key).copied()
+    }
+
+    /// Iterate over the windows that are transient for a window.
+    pub fn transients(&self, owner: WindowKey) -> impl Iterator<Item = WindowKey> + '_ {
+        self.transients
+            .iter()
+            .filter(move |&(_, &other)| other == owner)
+            .map(|(&key, _)| key)
+    }
+
+    /// Map a window along with the windows transient for it, and their transients.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn map_group(&mut self, key: WindowKey) {
+        let group = self.group(key);
+        let mut transaction = self.transaction();
+        for window in group {
+            transaction.map(window);
+        }
+        transaction.commit();
+    }
+
+    /// Unmap a window along with the windows transient for it, and their transients.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn unmap_group(&mut self, key: WindowKey) {
+        let group = self.group(key);
+        let mut transaction = self.transaction();
+        for window in group {
+            transaction.unmap(window);
+        }
+        transaction.commit();
+    }
+
+    /// [Minimize](Self::minimize) a window along with the windows transient for it, and their
+    /// transients.
+    ///
+    /// Windows in the group that are already minimized are left alone.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn minimize_group(&mut self, key: WindowKey) {
+        for window in self.group(key) {
+            self.minimize(window);
+        }
+    }
+
+    /// [Restore](Self::restore) a window along with the windows transient for it, and their
+    /// transients.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn restore_group(&mut self, key: WindowKey) {
+        for window in self.group(key) {
+            self.restore(window);
+        }
+    }
+
+    /// Returns a window and the windows transient for it, recursively, owners first.
+    fn group(&self, key: WindowKey) -> Vec<WindowKey> {
+        assert!(self.contains_key(key), "window does not exist");
+
+        let mut group = alloc::vec![key];
+        let mut i = 0;
+        while let Some(&owner) = group.get(i) {
+            group.extend(self.transients(owner));
+            i += 1;
+        }
+        group
+    }
+
+    /// Move transient windows above their owners in a stacking order.
+    pub(crate) fn stack_transients(&self, order: &mut Vec<WindowKey>) {
+        // Moving one transient may put another one beneath its owner, so repeat until nothing
+        // moves. Every pass settles at least one more link in each chain of transients.
+        for _ in 0..=self.transients.len() {
+            let mut moved = false;
+
+            for (&key, &owner) in &self.transients {
+                let position = |window| order.iter().position(|&other| other == window);
+                let (Some(at), Some(owner_at)) = (position(key), position(owner)) else {
+                    continue;
+                };
+                if at > owner_at || self.windows[key.0].layer != self.windows[owner.0].layer {
+                    continue;
+                }
+
+                let subtree = self.subtree_in(order, key);
+                if subtree.contains(&owner) {
+                    continue;
+                }
+
+                order.retain(|window| !subtree.contains(window));
+                let top = *self.subtree_in(order, owner).last().unwrap();
+                let position = order.iter().position(|&window| window == top).unwrap() + 1;
+                order.splice(position..position, subtree);
+                moved = true;
+            }
+
+            if !moved {
+                break;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Rectangle, WindowTable};
+    use alloc::vec::Vec;
+
+    #[test]
+    fn transient_for() {
+        let mut window_table = WindowTable::new();
+
+        let root = window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let dialog = window_table.insert(Rectangle::new(20, 20, 40, 40)).unwrap();
+        let owner = window_table.insert(Rectangle::new(50, 50, 90, 90)).unwrap();
+        let other = window_table.insert(Rectangle::new(10, 60, 30, 80)).unwrap();
+
+        assert!(window_table.set_transient_for(dialog, Some(owner)));
+        assert!(!window_table.set_transient_for(owner, Some(dialog)));
+        assert!(!window_table.set_transient_for(owner, Some(owner)));
+        assert_eq!(window_table.transient_for(dialog), Some(owner));
+        assert_eq!(window_table.transients(owner).collect::<Vec<_>>(), [dialog]);
+
+        let order =
+            |table: &WindowTable| table.paint_order().map(|(key, _)| key).collect::<Vec<_>>();
+        assert_eq!(order(&window_table), [root, owner, dialog, other]);
+
+        window_table.lower(dialog);
+        assert_eq!(order(&window_table), [root, owner, dialog, other]);
+
+        window_table.raise(other);
+        window_table.lower(other);
+        window_table.raise(owner);
+        assert_eq!(order(&window_table), [root, other, owner, dialog]);
+        window_table.validate().unwrap();
+
+        window_table.unmap_group(owner);
+        assert!(!window_table.is_mapped(dialog));
+        window_table.map_group(owner);
+        assert!(window_table.is_mapped(dialog));
+
+        window_table.minimize_group(owner);
+        assert!(window_table.is_minimized(owner));
+        assert!(window_table.is_minimized(dialog));
+        window_table.restore_group(owner);
+        assert_eq!(order(&window_table), [root, other, owner, dialog]);
+
+        window_table.remove(owner);
+        assert_eq!(window_table.transient_for(dialog), None);
+    }
+}
@@ -0,0 +1,322 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later OR MPL-2.0
+// This file is a part of `windowless`.
+//
+// `windowless` is free software: you can redistribute it and/or modify it under the terms of
+// either:
+//
+// * GNU Lesser General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+// * Mozilla Public License as published by the Mozilla Foundation, version 2.
+//
+// `windowless` is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+// See the GNU Lesser General Public License or the Mozilla Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License and the Mozilla
+// Public License along with `windowless`. If not, see <https://www.gnu.org/licenses/> or
+// <https://www.mozilla.org/en-US/MPL/2.0/>.
+
+//! Checking the invariants of the window table.
+
+use crate::{Coordinate, WindowKey, WindowTable};
+use alloc::vec::Vec;
+use core::{cmp, fmt};
+
+/// The invariants of a window table that were found to be broken.
+///
+/// Returned by [`WindowTable::validate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationError {
+    /// The broken invariants.
+    violations: Vec<Violation>,
+}
+
+impl ValidationError {
+    /// Returns the broken invariants.
+    pub fn violations(&self) -> &[Violation] {
+        &self.violations
+    }
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} invariant(s) broken:", self.violations.len())?;
+        for violation in &self.violations {
+            write!(f, "\n- {}", violation)?;
+        }
+        Ok(())
+    }
+}
+
+/// A broken invariant of a window table.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Violation {
+    /// The table has windows, but no root window.
+    MissingRoot,
+
+    /// The root window does not exist.
+    DanglingRoot(WindowKey),
+
+    /// The root window has parents.
+    RootHasParents(WindowKey),
+
+    /// A window links to a window that does not exist.
+    DanglingLink {
+        /// The window with the link.
+        window: WindowKey,
+
+        /// The window that does not exist.
+        link: WindowKey,
+    },
+
+    /// A window lists a parent that does not list it as a child, or the other way around.
+    AsymmetricLink {
+        /// The parent window.
+        parent: WindowKey,
+
+        /// The child window.
+        child: WindowKey,
+    },
+
+    /// A window is stacked beneath one of its parents.
+    ParentAbove {
+        /// The parent window.
+        parent: WindowKey,
+
+        /// The child window.
+        child: WindowKey,
+    },
+
+    /// A window does not overlap one of its parents.
+    DisjointParent {
+        /// The parent window.
+        parent: WindowKey,
+
+        /// The child window.
+        child: WindowKey,
+    },
+
+    /// A window other than the root has no parents.
+    Orphan(WindowKey),
+
+    /// Two windows have the same position in the stacking order.
+    DuplicateStackingPosition(WindowKey, WindowKey),
+
+    /// A window has an empty rectangle.
+    EmptyRectangle(WindowKey),
+
+    /// A window is missing from the spatial index.
+    NotIndexed(WindowKey),
+
+    /// A window is out of place in the cached stacking order.
+    Unordered(WindowKey),
+
+    /// A window is stacked directly beneath a window in a lower layer, or with a lower z-index
+    /// in the same layer.
+    LayerInversion {
+        /// The window beneath.
+        below: WindowKey,
+
+        /// The window above.
+        above: WindowKey,
+    },
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Violation::MissingRoot => f.write_str("the table has windows but no root window"),
+            Violation::DanglingRoot(root) => {
+                write!(f, "the root window {:?} does not exist", root)
+            }
+            Violation::RootHasParents(root) => {
+                write!(f, "the root window {:?} has parents", root)
+            }
+            Violation::DanglingLink { window, link } => write!(
+                f,
+                "window {:?} links to {:?}, which does not exist",
+                window, link
+            ),
+            Violation::AsymmetricLink { parent, child } => write!(
+                f,
+                "the link between parent {:?} and child {:?} only goes one way",
+                parent, child
+            ),
+            Violation::ParentAbove { parent, child } => write!(
+                f,
+                "window {:?} is stacked beneath its parent {:?}",
+                child, parent
+            ),
+            Violation::DisjointParent { parent, child } => write!(
+                f,
+                "window {:?} does not overlap its parent {:?}",
+                child, parent
+            ),
+            Violation::Orphan(window) => write!(f, "window {:?} has no parents", window),
+            Violation::DuplicateStackingPosition(a, b) => write!(
+                f,
+                "windows {:?} and {:?} have the same stacking position",
+                a, b
+            ),
+            Violation::EmptyRectangle(window) => {
+                write!(f, "window {:?} has an empty rectangle", window)
+            }
+            Violation::NotIndexed(window) => {
+                write!(f, "window {:?} is missing from the spatial index", window)
+            }
+            Violation::LayerInversion { below, above } => write!(
+                f,
+                "window {:?} is stacked beneath {:?}, which is in a lower layer or z-index",
+                below, above
+            ),
+            Violation::Unordered(window) => {
+                write!(
+                    f,
+                    "window {:?} is out of place in the stacking order",
+                    window
+                )
+            }
+        }
+    }
+}
+
+/// Check the invariants of a table.
+pub(crate) fn validate<T: Coordinate, const N: usize, const R: usize>(
+    table: &WindowTable<T, N, R>,
+) -> Result<(), ValidationError> {
+    let mut violations = Vec::new();
+
+    match table.root {
+        None if !table.windows.is_empty() => violations.push(Violation::MissingRoot),
+        Some(root) => match table.windows.get(root.0) {
+            None => violations.push(Violation::DanglingRoot(root)),
+            Some(window) if !window.parents.is_empty() => {
+                violations.push(Violation::RootHasParents(root))
+            }
+            Some(_) => {}
+        },
+        None => {}
+    }
+
+    let mut by_z = Vec::with_capacity(table.windows.len());
+
+    for (key, window) in &table.windows {
+        let key = WindowKey(key);
+        by_z.push((window.z, key));
+
+        if window.rect.is_empty() {
+            violations.push(Violation::EmptyRectangle(key));
+        }
+
+        if !table.index.query_rect(window.rect).contains(&key) {
+            violations.push(Violation::NotIndexed(key));
+        }
+
+        if window.parents.is_empty() && table.root != Some(key) {
+            violations.push(Violation::Orphan(key));
+        }
+
+        for &parent in &window.parents {
+            let parent_window = match table.windows.get(parent.0) {
+                Some(parent_window) => parent_window,
+                None => {
+                    violations.push(Violation::DanglingLink {
+                        window: key,
+                        link: parent,
+                    });
+                    continue;
+                }
+            };
+
+            if !parent_window.children.contains(&key) {
+                violations.push(Violation::AsymmetricLink { parent, child: key });
+            }
+
+            if parent_window.z >= window.z {
+                violations.push(Violation::ParentAbove { parent, child: key });
+            }
+
+            if !parent_window.rect.intersects(window.rect) {
+                violations.push(Violation::DisjointParent { parent, child: key });
+            }
+        }
+
+        for &child in &window.children {
+            match table.windows.get(child.0) {
+                Some(child_window) if !child_window.parents.contains(&key) => {
+                    violations.push(Violation::AsymmetricLink { parent: key, child });
+                }
+                Some(_) => {}
+                None => violations.push(Violation::DanglingLink {
+                    window: key,
+                    link: child,
+                }),
+            }
+        }
+    }
+
+    by_z.sort_unstable();
+    for pair in by_z.windows(2) {
+        if pair[0].0 == pair[1].0 {
+            violations.push(Violation::DuplicateStackingPosition(pair[0].1, pair[1].1));
+        }
+
+        let (below, above) = (pair[0].1, pair[1].1);
+        let position = |key: WindowKey| (table.windows[key.0].layer, table.z_index(key));
+        if table.root != Some(below)
+            && table.background != Some(below)
+            && position(below) > position(above)
+        {
+            violations.push(Violation::LayerInversion { below, above });
+        }
+    }
+
+    // The cached stacking order must list every window, sorted by position.
+    for i in 0..cmp::max(table.order.len(), by_z.len()) {
+        let cached = table.order.get(i).copied();
+        let sorted = by_z.get(i).map(|&(_, key)| key);
+        if cached != sorted {
+            if let Some(key) = cached.or(sorted) {
+                violations.push(Violation::Unordered(key));
+            }
+        }
+    }
+
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(ValidationError { violations })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Violation;
+    use crate::{Rectangle, WindowTable};
+
+    #[test]
+    fn validate() {
+        let mut window_table = WindowTable::new();
+
+        let root = window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let a = window_table.insert(Rectangle::new(0, 0, 50, 50)).unwrap();
+        window_table.insert(Rectangle::new(25, 25, 75, 75)).unwrap();
+        window_table.raise(a);
+        assert_eq!(window_table.validate(), Ok(()));
+
+        window_table.windows[a.0].parents.clear();
+        let error = window_table.validate().unwrap_err();
+        assert!(error.violations().contains(&Violation::Orphan(a)));
+        assert!(error.violations().contains(&Violation::AsymmetricLink {
+            parent: root,
+            child: a
+        }));
+
+        let mut window_table = WindowTable::new();
+        window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let a = window_table.insert(Rectangle::new(0, 0, 50, 50)).unwrap();
+        window_table.order.pop();
+        let error = window_table.validate().unwrap_err();
+        assert_eq!(error.violations(), [Violation::Unordered(a)]);
+    }
+}
@@ -0,0 +1,184 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later OR MPL-2.0
+// This file is a part of `windowless`.
+//
+// `windowless` is free software: you can redistribute it and/or modify it under the terms of
+// either:
+//
+// * GNU Lesser General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+// * Mozilla Public License as published by the Mozilla Foundation, version 2.
+//
+// `windowless` is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+// See the GNU Lesser General Public License or the Mozilla Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License and the Mozilla
+// Public License along with `windowless`. If not, see <https://www.gnu.org/licenses/> or
+// <https://www.mozilla.org/en-US/MPL/2.0/>.
+
+//! Tracking how much of each window is obscured.
+
+use crate::{Coordinate, WindowKey, WindowTable};
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+/// How much of a window is covered by the windows above it, like X11's `VisibilityNotify`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum VisibilityState {
+    /// The whole window is visible.
+    Unobscured,
+
+    /// Part of the window is visible.
+    PartiallyObscured,
+
+    /// None of the window is visible.
+    FullyObscured,
+}
+
+/// A change in how much of a window is obscured.
+///
+/// Returned by [`VisibilityTracker::update`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct VisibilityEvent {
+    /// The window that changed.
+    pub window: WindowKey,
+
+    /// The new state of the window.
+    pub state: VisibilityState,
+}
+
+/// Tracks the [`VisibilityState`] of every viewable window.
+///
+/// Clients can use this to skip rendering windows that are fully obscured, without computing
+/// their visible regions every frame.
+#[derive(Debug, Default)]
+pub struct VisibilityTracker {
+    /// The last known state of each viewable window.
+    states: BTreeMap<WindowKey, VisibilityState>,
+}
+
+impl VisibilityTracker {
+    /// Creates a new tracker, which knows of no windows.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the last known state of a window, or `None` if it was not viewable.
+    pub fn state(&self, key: WindowKey) -> Option<VisibilityState> {
+        self.states.get(&key).copied()
+    }
+
+    /// Bring the tracker up to date with the table.
+    ///
+    /// Returns an event for every viewable window whose state has changed, including windows
+    /// that have become viewable, in paint order. Windows that stop being viewable are
+    /// forgotten without an event. This should be called after the table has been modified.
+    pub fn update<T: Coordinate, const N: usize, const R: usize>(
+        &mut self,
+        table: &WindowTable<T, N, R>,
+    ) -> Vec<VisibilityEvent> {
+        let mut states = BTreeMap::new();
+        let mut events = Vec::new();
+
+        for (key, _) in table.paint_order() {
+            let state = match table.visibility_state(key) {
+                Some(state) => state,
+                None => continue,
+            };
+
+            if self.states.get(&key) != Some(&state) {
+                events.push(VisibilityEvent { window: key, state });
+            }
+            states.insert(key, state);
+        }
+
+        self.states = states;
+        events
+    }
+}
+
+impl<T: Coordinate, const N: usize, const R: usize> WindowTable<T, N, R> {
+    /// Find how much of a window is covered by the windows above it.
+    ///
+    /// This compares the window's [visible region](Self::visible_region) against the part of
+    /// it that lies in the root window. Returns `None` if the window is not viewable.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn visibility_state(&self, key: WindowKey) -> Option<VisibilityState> {
+        if !self.is_viewable(key) {
+            return None;
+        }
+
+        let visible = self.visible_region(key);
+        if visible.is_empty() {
+            return Some(VisibilityState::FullyObscured);
+        }
+
+        let mut full = self.windows[key.0].bounding_region();
+        if let Some(root) = self.root {
+            full.intersect_rect(self.windows[root.0].rect);
+        }
+
+        if visible.area() < full.area() {
+            Some(VisibilityState::PartiallyObscured)
+        } else {
+            Some(VisibilityState::Unobscured)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{VisibilityEvent, VisibilityState, VisibilityTracker};
+    use crate::{Rectangle, WindowTable};
+
+    #[test]
+    fn tracker() {
+        let mut window_table = WindowTable::new();
+
+        let root = window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let a = window_table.insert(Rectangle::new(10, 10, 40, 40)).unwrap();
+
+        let mut tracker = VisibilityTracker::new();
+        assert_eq!(
+            tracker.update(&window_table),
+            [
+                VisibilityEvent {
+                    window: root,
+                    state: VisibilityState::PartiallyObscured
+                },
+                VisibilityEvent {
+                    window: a,
+                    state: VisibilityState::Unobscured
+                }
+            ]
+        );
+        assert!(tracker.update(&window_table).is_empty());
+
+        let b = window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        assert_eq!(
+            tracker.update(&window_table),
+            [
+                VisibilityEvent {
+                    window: root,
+                    state: VisibilityState::FullyObscured
+                },
+                VisibilityEvent {
+                    window: a,
+                    state: VisibilityState::FullyObscured
+                },
+                VisibilityEvent {
+                    window: b,
+                    state: VisibilityState::Unobscured
+                }
+            ]
+        );
+
+        window_table.unmap(b);
+        tracker.update(&window_table);
+        assert_eq!(tracker.state(a), Some(VisibilityState::Unobscured));
+        assert_eq!(tracker.state(b), None);
+    }
+}
@@ -0,0 +1,150 @@
+// SPDX-License-Identifier: LGPL-3.0-or-later OR MPL-2.0
+// This file is a part of `windowless`.
+//
+// `windowless` is free software: you can redistribute it and/or modify it under the terms of
+// either:
+//
+// * GNU Lesser General Public License as published by the Free Software Foundation, either
+// version 3 of the License, or (at your option) any later version.
+// * Mozilla Public License as published by the Mozilla Foundation, version 2.
+//
+// `windowless` is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+// See the GNU Lesser General Public License or the Mozilla Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License and the Mozilla
+// Public License along with `windowless`. If not, see <https://www.gnu.org/licenses/> or
+// <https://www.mozilla.org/en-US/MPL/2.0/>.
+
+//! Showing and hiding windows by workspace.
+
+use crate::{Coordinate, Region, WindowKey, WindowTable};
+use alloc::vec::Vec;
+
+/// The identifier of a workspace.
+///
+/// The meaning of the identifier is up to the caller. The table starts out on the workspace
+/// with the identifier `0`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct WorkspaceId(pub u32);
+
+impl<T: Coordinate, const N: usize, const R: usize> WindowTable<T, N, R> {
+    /// Set the workspaces that a window belongs to.
+    ///
+    /// A window with no workspaces is shown on every workspace, which is the default. The
+    /// change takes effect at the next call to
+    /// [`set_active_workspace`](Self::set_active_workspace).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn set_workspaces(&mut self, key: WindowKey, workspaces: &[WorkspaceId]) {
+        let attributes = self.windows[key.0].attributes_mut();
+        attributes.workspaces.clear();
+        attributes.workspaces.extend_from_slice(workspaces);
+    }
+
+    /// Returns the workspaces that a window belongs to.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the window does not exist.
+    pub fn workspaces(&self, key: WindowKey) -> &[WorkspaceId] {
+        match self.windows[key.0].attributes() {
+            Some(attributes) => &attributes.workspaces,
+            None => &[],
+        }
+    }
+
+    /// Returns the workspace being shown.
+    pub fn active_workspace(&self) -> WorkspaceId {
+        self.active_workspace
+    }
+
+    /// Switch to another workspace.
+    ///
+    /// Windows that don't belong to the workspace are unmapped, and windows that were unmapped
+    /// by an earlier switch and belong to it are mapped again, all in one
+    /// [transaction](Self::transaction). Windows that were unmapped some other way stay
+    /// unmapped. Returns the region of the screen damaged by the switch.
+    pub fn set_active_workspace(&mut self, workspace: WorkspaceId) -> Region<T, R> {
+        self.active_workspace = workspace;
+
+        let mut shown = Vec::new();
+        let mut hidden = Vec::new();
+        for (key, window) in &self.windows {
+            let attributes = match window.attributes() {
+                Some(attributes) if !attributes.workspaces.is_empty() => attributes,
+                _ => continue,
+            };
+
+            if attributes.workspaces.contains(&workspace) {
+                if attributes.hidden_by_workspace {
+                    shown.push(WindowKey(key));
+                }
+            } else if window.mapped {
+                hidden.push(WindowKey(key));
+            }
+        }
+
+        for &key in &shown {
+            self.windows[key.0].attributes_mut().hidden_by_workspace = false;
+        }
+        for &key in &hidden {
+            self.windows[key.0].attributes_mut().hidden_by_workspace = true;
+        }
+
+        let mut transaction = self.transaction();
+        for key in hidden {
+            transaction.unmap(key);
+        }
+        for key in shown {
+            transaction.map(key);
+        }
+        transaction.commit()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::WorkspaceId;
+    use crate::{Rectangle, WindowTable};
+
+    #[test]
+    fn workspaces() {
+        let mut window_table = WindowTable::new();
+
+        window_table.insert(Rectangle::new(0, 0, 100, 100)).unwrap();
+        let editor = window_table.insert(Rectangle::new(0, 0, 50, 50)).unwrap();
+        let browser = window_table
+            .insert(Rectangle::new(50, 50, 100, 100))
+            .unwrap();
+        let bar = window_table.insert(Rectangle::new(0, 60, 40, 70)).unwrap();
+        let (one, two) = (WorkspaceId(1), WorkspaceId(2));
+        window_table.set_workspaces(editor, &[one]);
+        window_table.set_workspaces(browser, &[two]);
+        assert_eq!(window_table.workspaces(editor), [one]);
+        assert!(window_table.workspaces(bar).is_empty());
+        window_table.take_damage();
+
+        let damage = window_table.set_active_workspace(one);
+        assert_eq!(window_table.active_workspace(), one);
+        assert!(window_table.is_viewable(editor));
+        assert!(!window_table.is_viewable(browser));
+        assert!(window_table.is_viewable(bar));
+        assert_eq!(damage.area(), 2500);
+
+        // Windows unmapped by hand stay unmapped.
+        window_table.unmap(editor);
+        window_table.set_active_workspace(two);
+        window_table.set_active_workspace(one);
+        assert!(!window_table.is_viewable(editor));
+        assert!(!window_table.is_viewable(browser));
+
+        window_table.map(editor);
+        let damage = window_table.set_active_workspace(two);
+        assert!(!window_table.is_viewable(editor));
+        assert!(window_table.is_viewable(browser));
+        assert_eq!(damage.area(), 5000);
+    }
+}